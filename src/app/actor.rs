@@ -31,7 +31,7 @@ impl AppActor {
         mut net_rx: mpsc::UnboundedReceiver<NetworkResponse>,
     ) {
         // Send initial render state
-        let _ = self.render_tx.send(self.state.to_render_state());
+        let _ = self.render_tx.send(self.state.render_state());
 
         loop {
             tokio::select! {
@@ -41,19 +41,51 @@ impl AppActor {
                         let _ = self.network_tx.send(NetworkCommand::Shutdown);
                         break;
                     }
-                    let _ = self.render_tx.send(self.state.to_render_state());
                 }
                 Some(response) = net_rx.recv() => {
                     self.state.handle_response(response);
-                    let _ = self.render_tx.send(self.state.to_render_state());
                 }
                 else => break,
             }
+
+            // Apply everything already queued before rendering, so a burst
+            // of keystrokes or WebSocket messages costs one render state
+            if self.drain_pending(&mut ui_rx, &mut net_rx) {
+                let _ = self.network_tx.send(NetworkCommand::Shutdown);
+                break;
+            }
+            let _ = self.render_tx.send(self.state.render_state());
+        }
+    }
+
+    /// Handle the queued UI events and network responses without waiting,
+    /// returns true if quit was requested
+    fn drain_pending(
+        &mut self,
+        ui_rx: &mut mpsc::UnboundedReceiver<UiEvent>,
+        net_rx: &mut mpsc::UnboundedReceiver<NetworkResponse>,
+    ) -> bool {
+        loop {
+            let mut handled = false;
+            if let Ok(event) = ui_rx.try_recv() {
+                if self.handle_ui_event(event) {
+                    return true;
+                }
+                handled = true;
+            }
+            if let Ok(response) = net_rx.try_recv() {
+                self.state.handle_response(response);
+                handled = true;
+            }
+            if !handled {
+                return false;
+            }
         }
     }
 
     /// Handle a UI event, returns true if quit was requested
     fn handle_ui_event(&mut self, event: UiEvent) -> bool {
+        self.state.notice = None;
         match event {
             // Tab switching
             UiEvent::SwitchTab(tab) => self.state.switch_tab(tab),
@@ -64,6 +96,8 @@ impl AppActor {
             UiEvent::FocusWorkspace => self.state.focus_workspace(),
             UiEvent::ScrollUp => self.state.scroll_up(),
             UiEvent::ScrollDown => self.state.scroll_down(),
+            UiEvent::ToggleResponseView => self.state.toggle_response_view(),
+            UiEvent::ToggleRawBody => self.state.toggle_raw_body(),
 
             // Input editing
             UiEvent::StartEditing => self.state.start_editing(),
@@ -120,10 +154,82 @@ impl AppActor {
             // cURL
             UiEvent::ShowCurlImport => self.state.show_curl_import(),
             UiEvent::CurlImportChar(c) => self.state.curl_import_char(c),
+            UiEvent::CurlImportPaste(text) => self.state.curl_import_paste(&text),
             UiEvent::CurlImportBackspace => self.state.curl_import_backspace(),
             UiEvent::ImportCurl => self.state.import_curl(),
             UiEvent::CancelCurlImport => self.state.cancel_curl_import(),
             UiEvent::ExportCurl => self.state.export_curl(),
+            UiEvent::ShowExport => self.state.show_export = true,
+            UiEvent::ExportNext => self.state.export_next(),
+            UiEvent::ExportPrev => self.state.export_prev(),
+            UiEvent::ToggleExportSubstitution => {
+                self.state.export_substitute = !self.state.export_substitute
+            }
+            UiEvent::ExportToClipboard => self.state.export_to_clipboard(),
+            UiEvent::ExportToFile => self.state.export_to_file(),
+            UiEvent::CancelExport => self.state.show_export = false,
+
+            // Collections
+            UiEvent::ShowCollections => self.state.show_collections = true,
+            UiEvent::CollectionNext => self.state.collection_next(),
+            UiEvent::CollectionPrev => self.state.collection_prev(),
+            UiEvent::OpenCollectionEntry => self.state.open_collection_entry(),
+            UiEvent::ExportCollection => self.state.export_collection(),
+            UiEvent::ExportOpenApiYaml => self.state.export_openapi(false),
+            UiEvent::ExportOpenApiJson => self.state.export_openapi(true),
+            UiEvent::CancelCollections => self.state.show_collections = false,
+            UiEvent::CycleEnvironment => self.state.cycle_environment(),
+            UiEvent::ExportHistoryHar => self.state.export_history_har(),
+
+            // Response search
+            UiEvent::OpenSearch => self.state.open_search(),
+            UiEvent::SearchChar(c) => self.state.search_char(c),
+            UiEvent::SearchBackspace => self.state.search_backspace(),
+            UiEvent::ConfirmSearch => self.state.search.editing = false,
+            UiEvent::ClearSearch => self.state.clear_search(),
+            UiEvent::ToggleSearchCase => self.state.toggle_search_case(),
+            UiEvent::ToggleSearchRegex => self.state.toggle_search_regex(),
+            UiEvent::SearchNext => self.state.search_step(true),
+            UiEvent::SearchPrev => self.state.search_step(false),
+            UiEvent::SaveHttpFile => self.state.save_http_file(),
+
+            // JSON tree
+            UiEvent::TreeUp => self.state.tree_step(false),
+            UiEvent::TreeDown => self.state.tree_step(true),
+            UiEvent::TreeToggle => self.state.tree_toggle(),
+            UiEvent::TreeExpand => self.state.tree_expand(),
+            UiEvent::TreeCollapse => self.state.tree_collapse(),
+            UiEvent::CopyTreePath => self.state.copy_tree_path(),
+            UiEvent::ShowCaptureInput => self.state.show_capture_input(),
+            UiEvent::CaptureInputChar(c) => self.state.capture_name.push(c),
+            UiEvent::CaptureInputBackspace => {
+                self.state.capture_name.pop();
+            }
+            UiEvent::ApplyCapture => self.state.apply_capture(),
+            UiEvent::CancelCaptureInput => self.state.show_capture_input = false,
+
+            // History diff
+            UiEvent::ShowHistoryDiff => self.state.show_history_diff(),
+            UiEvent::DiffUp => self.state.diff_step(false),
+            UiEvent::DiffDown => self.state.diff_step(true),
+            UiEvent::DiffPick => self.state.diff_pick(),
+            UiEvent::DiffPreviousRun => self.state.diff_previous_run(),
+            UiEvent::DiffBack => self.state.diff_back(),
+            UiEvent::ShowServers => self.state.show_servers(),
+            UiEvent::ServerUp => self.state.server_step(false),
+            UiEvent::ServerDown => self.state.server_step(true),
+            UiEvent::ServerPick => self.state.server_pick(),
+            UiEvent::ServerChoice(forward) => self.state.server_choice(forward),
+            UiEvent::MapServerToEnvironment => self.state.map_server_to_environment(),
+            UiEvent::CloseServers => self.state.show_servers = false,
+
+            // Proxy
+            UiEvent::ShowProxyInput => self.state.show_proxy_input(),
+            UiEvent::ProxyInputChar(c) => self.state.proxy_input_char(c),
+            UiEvent::ProxyInputBackspace => self.state.proxy_input_backspace(),
+            UiEvent::ApplyProxyInput => self.state.apply_proxy_input(),
+            UiEvent::SaveProxyInputAsDefault => self.state.save_proxy_input_as_default(),
+            UiEvent::CancelProxyInput => self.state.cancel_proxy_input(),
 
             // WebSocket
             UiEvent::WsConnect => {
@@ -7,17 +7,35 @@
 //! - **HTTP**: Method cycling, headers, auth
 //! - **Request**: Preparation, validation, and response handling
 //! - **Workspace**: Project discovery and endpoint loading
+//! - **Collections**: Browsing, importing and exporting saved requests
 //! - **WebSocket**: Connection and message handling
 
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use crate::app::state::{WsDirection, WsLogEntry};
+use crate::app::search::{ResponseSearch, SearchMatch};
+use crate::app::state::{OpenHttpFile, WsDirection};
 use crate::app::AppState;
+use crate::clipboard;
+use crate::codegen::{self, CodeTarget};
+use crate::constants::STREAM_PREVIEW_BYTES;
 use crate::curl;
+use crate::discovery::validate::{self, Violation};
 use crate::discovery::{self, detector, openapi, DiscoveredEndpoint};
-use crate::messages::ui_events::{AppTab, AuthField, InputMode, Panel};
+use crate::formatting::{self, BodyKind};
+use crate::indexed_text::IndexedText;
+use crate::interop::har::{self, HarFilter};
+use crate::interop::http_file::{self, HttpFile};
+use crate::interop::{self, postman, Exported};
+use crate::json_tree::{self, TreeRow};
+use crate::messages::ui_events::{AppTab, AuthField, InputMode, Panel, ResponseView};
 use crate::messages::{NetworkCommand, NetworkResponse};
-use crate::models::{AuthType, Header, HistoryEntry, HttpMethod, Request};
+use crate::models::{
+    split_unix_url, AuthType, Collection, CollectionEntry, Environment, Header, HistoryEntry, HttpMethod,
+    ProxyConfig, ProxySetting, Request, UNIX_SCHEME,
+};
+use crate::response_diff::ResponseDiff;
 
 impl AppState {
     // ========================
@@ -128,6 +146,418 @@ impl AppState {
         self.response_scroll = self.response_scroll.saturating_add(1);
     }
 
+    /// Switch between the pretty printed and the received body
+    pub fn toggle_raw_body(&mut self) {
+        self.response_raw = !self.response_raw;
+        self.response_scroll = 0;
+    }
+
+    /// Cycle the response panel between the body, the JSON tree and the Info view
+    pub fn toggle_response_view(&mut self) {
+        self.response_view = self.response_view.next();
+        self.response_scroll = 0;
+    }
+
+    // ========================
+    // Response search
+    // ========================
+
+    /// Open the search bar; typing selects the first match from the
+    /// current scroll position on
+    pub fn open_search(&mut self) {
+        self.response_view = ResponseView::Body;
+        self.search.editing = true;
+        self.search.origin_line = self.response_scroll;
+        self.update_search();
+    }
+
+    pub fn search_char(&mut self, c: char) {
+        self.search.query.push(c);
+        self.update_search();
+    }
+
+    pub fn search_backspace(&mut self) {
+        self.search.query.pop();
+        self.update_search();
+    }
+
+    /// Close the search and remove its highlights, keeping the toggles
+    pub fn clear_search(&mut self) {
+        self.search = ResponseSearch {
+            case_sensitive: self.search.case_sensitive,
+            regex: self.search.regex,
+            ..ResponseSearch::default()
+        };
+    }
+
+    pub fn toggle_search_case(&mut self) {
+        self.search.case_sensitive = !self.search.case_sensitive;
+        self.update_search();
+    }
+
+    pub fn toggle_search_regex(&mut self) {
+        self.search.regex = !self.search.regex;
+        self.update_search();
+    }
+
+    /// Select the next (or previous) match, wrapping around
+    pub fn search_step(&mut self, forward: bool) {
+        let matches = self.search_matches();
+        let Ok(matches) = matches.as_ref() else {
+            return;
+        };
+        if matches.is_empty() {
+            return;
+        }
+        let current = self.search.current.min(matches.len() - 1);
+        self.search.current = if forward {
+            (current + 1) % matches.len()
+        } else {
+            current.checked_sub(1).unwrap_or(matches.len() - 1)
+        };
+        self.scroll_to_match(&matches[self.search.current]);
+    }
+
+    fn update_search(&mut self) {
+        let matches = self.search_matches();
+        let Ok(matches) = matches.as_ref() else {
+            return;
+        };
+        self.search.current = matches
+            .iter()
+            .position(|m| m.line >= self.search.origin_line)
+            .unwrap_or(0);
+        if let Some(m) = matches.get(self.search.current) {
+            self.scroll_to_match(m);
+        }
+    }
+
+    /// Scroll so the match is near the top, with a little context above
+    fn scroll_to_match(&mut self, m: &SearchMatch) {
+        // A filter error is shown in two lines above the body
+        let offset = match self.filtered_body() {
+            Some(Err(_)) => 2,
+            _ => 0,
+        };
+        let line = (m.line + offset).saturating_sub(2);
+        self.response_scroll = line;
+    }
+
+    // ========================
+    // JSON tree
+    // ========================
+
+    /// Run `f` on the visible rows of the tree view and the clamped
+    /// selection, if the shown body is JSON
+    fn with_tree_rows<T>(&mut self, f: impl FnOnce(&[TreeRow], usize) -> T) -> Option<T> {
+        let rows = self.tree_rows();
+        let rows = rows.as_ref().as_ref().ok()?;
+        let selected = self.tree_selected.min(rows.len().saturating_sub(1));
+        Some(f(rows, selected))
+    }
+
+    pub fn tree_step(&mut self, forward: bool) {
+        let Some(selected) = self.with_tree_rows(|rows, selected| {
+            if forward {
+                (selected + 1).min(rows.len() - 1)
+            } else {
+                selected.saturating_sub(1)
+            }
+        }) else {
+            return;
+        };
+        self.tree_selected = selected;
+    }
+
+    /// Fold or unfold the selected object or array
+    pub fn tree_toggle(&mut self) {
+        let Some(Some(path)) = self.with_tree_rows(|rows, selected| {
+            let row = &rows[selected];
+            row.line.node.is_container().then(|| row.path.clone())
+        }) else {
+            return;
+        };
+        if !self.tree_collapsed.remove(&path) {
+            self.tree_collapsed.insert(path);
+        }
+    }
+
+    pub fn tree_expand(&mut self) {
+        if let Some(path) = self.with_tree_rows(|rows, selected| rows[selected].path.clone()) {
+            self.tree_collapsed.remove(&path);
+        }
+    }
+
+    /// Fold the selected node, or select its parent if there is nothing
+    /// to fold
+    pub fn tree_collapse(&mut self) {
+        let Some((path, foldable, parent)) = self.with_tree_rows(|rows, selected| {
+            let row = &rows[selected];
+            let foldable = row.line.node.is_container() && !row.line.node.is_collapsed();
+            let parent = rows[..selected]
+                .iter()
+                .rposition(|candidate| candidate.line.depth < row.line.depth);
+            (row.path.clone(), foldable, parent)
+        }) else {
+            return;
+        };
+        if foldable {
+            self.tree_collapsed.insert(path);
+        } else if let Some(parent) = parent {
+            self.tree_selected = parent;
+        }
+    }
+
+    pub fn copy_tree_path(&mut self) {
+        let Some(path) = self.with_tree_rows(|rows, selected| rows[selected].path.clone()) else {
+            return;
+        };
+        self.notice = Some(match clipboard::copy(&path) {
+            Ok(tool) => format!("Copied {} ({})", path, tool),
+            Err(e) => format!("Could not copy to the clipboard: {:#}", e),
+        });
+    }
+
+    /// Open the capture popup for the selected value, named after its key
+    pub fn show_capture_input(&mut self) {
+        let document = self.tree_document();
+        let Some((name, Some(value))) = self.with_tree_rows(|rows, selected| {
+            let row = &rows[selected];
+            let value = document.as_ref().as_ref().ok().and_then(|d| d.pointer(&row.pointer));
+            (json_tree::variable_name(&row.line), value.map(json_tree::capture_text))
+        }) else {
+            return;
+        };
+        self.capture_name = name;
+        self.capture_value = value;
+        self.show_capture_input = true;
+    }
+
+    /// Store the captured value in the active environment, creating and
+    /// activating a "Captured" environment when there is none
+    pub fn apply_capture(&mut self) {
+        let name = self.capture_name.trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        self.show_capture_input = false;
+        let index = match self.storage.current_env {
+            Some(index) => index,
+            None => {
+                let environment = self
+                    .storage
+                    .environment("Captured")
+                    .cloned()
+                    .unwrap_or_else(|| Environment::new("Captured"));
+                self.storage.insert_environment(environment);
+                let index = self
+                    .storage
+                    .environments
+                    .iter()
+                    .position(|e| e.name == "Captured")
+                    .unwrap_or_default();
+                self.storage.current_env = Some(index);
+                index
+            }
+        };
+        let environment = &mut self.storage.environments[index];
+        environment.set(name.clone(), self.capture_value.clone());
+        let environment = environment.clone();
+        self.notice = Some(match self.storage.save_environment(&environment) {
+            Ok(()) => format!("Captured {{{{{}}}}} in {}", name, environment.name),
+            Err(e) => format!("Captured {{{{{}}}}} but could not save {}: {:#}", name, environment.name, e),
+        });
+    }
+
+    // ========================
+    // Workspace servers
+    // ========================
+
+    pub fn show_servers(&mut self) {
+        let servers = self.server_candidates();
+        if servers.is_empty() {
+            self.notice = Some("The workspace lists no servers".to_string());
+            return;
+        }
+        self.show_servers = true;
+        let picked = self.picked_server.as_ref().and_then(|p| servers.iter().position(|s| s.url == p.url));
+        self.server_row = self
+            .server_row_targets()
+            .iter()
+            .position(|(server, variable)| Some(*server) == picked && variable.is_none())
+            .unwrap_or_default();
+    }
+
+    pub fn server_step(&mut self, forward: bool) {
+        let len = self.server_row_targets().len();
+        if len > 0 {
+            self.server_row = if forward {
+                (self.server_row + 1) % len
+            } else {
+                (self.server_row + len - 1) % len
+            };
+        }
+    }
+
+    /// Select the highlighted server, or move its variable to the next
+    /// choice
+    pub fn server_pick(&mut self) {
+        match self.server_row_targets().get(self.server_row) {
+            Some((server, None)) => {
+                self.picked_server = self.server_candidates().get(*server).cloned();
+                self.show_servers = false;
+            }
+            Some((_, Some(_))) => self.server_choice(true),
+            None => {}
+        }
+    }
+
+    /// Cycle the highlighted variable through its `enum` choices
+    pub fn server_choice(&mut self, forward: bool) {
+        let Some((server, Some(variable))) = self.server_row_targets().get(self.server_row).copied() else {
+            return;
+        };
+        let Some(variable) = self.server_candidates().get(server).map(|s| s.variables[variable].clone()) else {
+            return;
+        };
+        if variable.choices.is_empty() {
+            self.notice = Some(format!("{} has no choices besides {}", variable.name, variable.default));
+            return;
+        }
+        let len = variable.choices.len();
+        let current = variable.value(&self.server_values);
+        let index = variable.choices.iter().position(|c| c == current).unwrap_or_default();
+        let next = if forward { (index + 1) % len } else { (index + len - 1) % len };
+        self.server_values.insert(variable.name.clone(), variable.choices[next].clone());
+    }
+
+    /// Link the highlighted server to the active environment, which
+    /// endpoints then load against whenever it is active
+    pub fn map_server_to_environment(&mut self) {
+        let Some((server, _)) = self.server_row_targets().get(self.server_row).copied() else {
+            return;
+        };
+        let Some(url) = self.server_candidates().get(server).map(|s| s.resolve(&self.server_values)) else {
+            return;
+        };
+        let Some(environment) = self.storage.current_env.and_then(|i| self.storage.environments.get_mut(i)) else {
+            self.notice = Some("No active environment to map the server to (E cycles them)".to_string());
+            return;
+        };
+        environment.server = Some(url.clone());
+        let environment = environment.clone();
+        // The link decides from now on, and follows environment switches
+        self.picked_server = None;
+        self.show_servers = false;
+        self.notice = Some(match self.storage.save_environment(&environment) {
+            Ok(()) => format!("Linked {} to {}", url, environment.name),
+            Err(e) => format!("Mapped {} but could not save {}: {:#}", url, environment.name, e),
+        });
+    }
+
+    /// Switch environment; one with a linked server replaces the server
+    /// picked in the popup
+    pub fn cycle_environment(&mut self) {
+        self.storage.cycle_environment();
+        if self.storage.current_environment().is_some_and(|env| env.server.is_some()) {
+            self.picked_server = None;
+        }
+    }
+
+    // ========================
+    // History diff
+    // ========================
+
+    pub fn show_history_diff(&mut self) {
+        if self.storage.history_len() == 0 {
+            self.notice = Some("No history to compare yet".to_string());
+            return;
+        }
+        self.show_history_diff = true;
+        self.diff_selected = 0;
+        self.diff_base = None;
+        self.history_diff = None;
+    }
+
+    /// Scroll the comparison, or move through the history list
+    pub fn diff_step(&mut self, forward: bool) {
+        if let Some(diff) = &self.history_diff {
+            let last = diff.rows.len().saturating_sub(1);
+            self.diff_scroll = if forward {
+                (self.diff_scroll + 1).min(last)
+            } else {
+                self.diff_scroll.saturating_sub(1)
+            };
+            return;
+        }
+        let len = self.storage.history_len();
+        if len > 0 {
+            self.diff_selected = if forward {
+                (self.diff_selected + 1) % len
+            } else {
+                (self.diff_selected + len - 1) % len
+            };
+        }
+    }
+
+    /// Pick the highlighted entry; the second pick shows the comparison,
+    /// picking the same entry again unpicks it
+    pub fn diff_pick(&mut self) {
+        if self.history_diff.is_some() {
+            return;
+        }
+        match self.diff_base {
+            Some(base) if base == self.diff_selected => self.diff_base = None,
+            Some(base) => self.compare_history(base, self.diff_selected),
+            None => self.diff_base = Some(self.diff_selected),
+        }
+    }
+
+    /// Compare the highlighted entry with the previous run of the same
+    /// request
+    pub fn diff_previous_run(&mut self) {
+        if self.history_diff.is_some() {
+            return;
+        }
+        let Some(entry) = self.storage.get_history(self.diff_selected) else {
+            return;
+        };
+        let previous = self
+            .storage
+            .history
+            .iter()
+            .enumerate()
+            .skip(self.diff_selected + 1)
+            .find(|(_, older)| older.request.method == entry.request.method && older.request.url == entry.request.url)
+            .map(|(index, _)| index);
+        match previous {
+            Some(index) => self.compare_history(index, self.diff_selected),
+            None => self.notice = Some("No earlier run of this request in history".to_string()),
+        }
+    }
+
+    /// Leave the comparison, drop the picked entry, or close the popup
+    pub fn diff_back(&mut self) {
+        if self.history_diff.is_some() {
+            self.history_diff = None;
+        } else if self.diff_base.is_some() {
+            self.diff_base = None;
+        } else {
+            self.show_history_diff = false;
+        }
+    }
+
+    /// Show the comparison of two history entries, the older one on the left
+    fn compare_history(&mut self, a: usize, b: usize) {
+        let (older, newer) = (a.max(b), a.min(b));
+        let (Some(old), Some(new)) = (self.storage.get_history(older), self.storage.get_history(newer)) else {
+            return;
+        };
+        self.history_diff = Some(Arc::new(ResponseDiff::new(old, new)));
+        self.diff_base = None;
+        self.diff_scroll = 0;
+    }
+
     // ========================
     // Headers
     // ========================
@@ -213,6 +643,8 @@ impl AppState {
             if let Some(entry) = self.storage.get_history(idx) {
                 self.request = entry.request.clone();
                 self.history_index = Some(idx);
+                self.http_file_request = None;
+                self.spec_endpoint = None;
                 self.cursor_position = self.request.url.len();
             }
         }
@@ -224,12 +656,16 @@ impl AppState {
                 if let Some(entry) = self.storage.get_history(idx - 1) {
                     self.request = entry.request.clone();
                     self.history_index = Some(idx - 1);
+                    self.http_file_request = None;
+                    self.spec_endpoint = None;
                     self.cursor_position = self.request.url.len();
                 }
             } else {
                 // Back to newest/empty
                 self.request = Request::default();
                 self.history_index = None;
+                self.http_file_request = None;
+                self.spec_endpoint = None;
                 self.cursor_position = self.request.url.len();
             }
         }
@@ -247,14 +683,34 @@ impl AppState {
         self.curl_import_buffer.push(c);
     }
 
+    pub fn curl_import_paste(&mut self, text: &str) {
+        self.curl_import_buffer.push_str(text);
+    }
+
     pub fn curl_import_backspace(&mut self) {
         self.curl_import_buffer.pop();
     }
 
     pub fn import_curl(&mut self) {
-        if let Ok(request) = curl::parse_curl(&self.curl_import_buffer) {
-            self.request = request;
-            self.cursor_position = self.request.url.len();
+        match curl::import_curl(&self.curl_import_buffer) {
+            Ok(import) => {
+                self.request = import.request;
+                self.http_file_request = None;
+                self.spec_endpoint = None;
+                self.cursor_position = self.request.url.len();
+                if !import.unsupported.is_empty() {
+                    self.response.body = format!(
+                        "Imported cURL command\n\nIgnored unsupported options:\n  {}",
+                        import.unsupported.join("\n  ")
+                    )
+                    .into();
+                    self.response.status_code = None;
+                }
+            }
+            Err(e) => {
+                self.response.body = format!("cURL import failed: {:#}", e).into();
+                self.response.status_code = None;
+            }
         }
         self.curl_import_buffer.clear();
         self.show_curl_import = false;
@@ -266,8 +722,132 @@ impl AppState {
     }
 
     pub fn export_curl(&mut self) {
-        self.response.body = curl::to_curl(&self.request);
+        self.response.body = curl::to_curl(&self.exported_request()).into();
+        self.response.status_code = None;
+    }
+
+    // ========================
+    // Code export
+    // ========================
+
+    pub fn export_next(&mut self) {
+        self.export_target = (self.export_target + 1) % CodeTarget::ALL.len();
+    }
+
+    pub fn export_prev(&mut self) {
+        self.export_target = self
+            .export_target
+            .checked_sub(1)
+            .unwrap_or(CodeTarget::ALL.len() - 1);
+    }
+
+    /// The selected target and its code for the outgoing request and
+    /// the TLS profile it is sent with, with environment variables
+    /// substituted when enabled
+    fn generate_export(&self) -> (CodeTarget, String) {
+        let target = CodeTarget::ALL[self.export_target];
+        let mut request = self.exported_request();
+        if self.export_substitute {
+            request = self.substitute_request(request);
+        }
+        (target, target.generate(&request))
+    }
+
+    /// Replace environment variables in the parts of a request sent on the wire
+    fn substitute_request(&self, mut request: Request) -> Request {
+        let substitute = |text: &str| self.storage.substitute(text);
+        request.url = substitute(&request.url);
+        for header in &mut request.headers {
+            header.value = substitute(&header.value);
+        }
+        request.body = substitute(&request.body);
+        request.auth = match request.auth {
+            AuthType::Bearer(token) => AuthType::Bearer(substitute(&token)),
+            AuthType::Basic { username, password } => AuthType::Basic {
+                username: substitute(&username),
+                password: substitute(&password),
+            },
+            AuthType::None => AuthType::None,
+        };
+        request
+    }
+
+    pub fn export_to_clipboard(&mut self) {
+        let (target, code) = self.generate_export();
+        let message = match clipboard::copy(&code) {
+            Ok(tool) => format!("Copied {} code to the clipboard ({})", target.label(), tool),
+            Err(e) => format!("Could not copy to the clipboard: {:#}", e),
+        };
+        self.response.body = format!("{}\n\n{}", message, code).into();
+        self.response.status_code = None;
+        self.show_export = false;
+    }
+
+    /// Save the snippet in the current directory without overwriting files
+    pub fn export_to_file(&mut self) {
+        let (target, code) = self.generate_export();
+        let name = codegen::file_name(&self.request, target);
+        let message = match write_new_file(Path::new("."), &name, &code) {
+            Ok(path) => format!("Saved {} code to {}", target.label(), path.display()),
+            Err(e) => format!("Could not write {}: {}", name, e),
+        };
+        self.response.body = format!("{}\n\n{}", message, code).into();
+        self.response.status_code = None;
+        self.show_export = false;
+    }
+
+    // ========================
+    // Proxy
+    // ========================
+
+    /// Open the proxy popup, pre-filled with the request's own override
+    pub fn show_proxy_input(&mut self) {
+        self.proxy_input = match &self.request.proxy {
+            Some(ProxySetting::Manual(proxy)) => proxy.url.clone(),
+            Some(ProxySetting::Direct) => "direct".to_string(),
+            Some(ProxySetting::System) => "system".to_string(),
+            None => String::new(),
+        };
+        self.show_proxy_input = true;
+    }
+
+    pub fn proxy_input_char(&mut self, c: char) {
+        self.proxy_input.push(c);
+    }
+
+    pub fn proxy_input_backspace(&mut self) {
+        self.proxy_input.pop();
+    }
+
+    /// Apply the proxy popup input to the current request.
+    /// Empty inherits, `direct` bypasses proxies, `system` uses the
+    /// environment variables, anything else is a proxy URL.
+    pub fn apply_proxy_input(&mut self) {
+        let existing = self.request.proxy.take();
+        self.request.proxy = proxy_setting(&self.proxy_input, existing);
+        self.proxy_input.clear();
+        self.show_proxy_input = false;
+    }
+
+    /// Save the proxy popup input as the global proxy in `settings.yaml`,
+    /// used by requests and environments without their own. Empty uses
+    /// the environment variables.
+    pub fn save_proxy_input_as_default(&mut self) {
+        let existing = std::mem::take(&mut self.storage.settings.proxy);
+        self.storage.settings.proxy = proxy_setting(&self.proxy_input, Some(existing)).unwrap_or_default();
+        self.response.body = match self.storage.save_settings() {
+            Ok(path) => format!("✓ Saved the default proxy to {}", path.display()),
+            Err(e) => format!("Could not save the default proxy: {:#}", e),
+        }
+        .into();
         self.response.status_code = None;
+        self.proxy_input.clear();
+        self.show_proxy_input = false;
+    }
+
+    pub fn cancel_proxy_input(&mut self) {
+        self.proxy_input.clear();
+        self.show_proxy_input = false;
     }
 
     // ========================
@@ -343,7 +923,6 @@ impl AppState {
         if let Ok(entries) = fs::read_dir(&parent) {
             let mut matches: Vec<String> = entries
                 .filter_map(|e| e.ok())
-                .filter(|e| e.path().is_dir())
                 .filter_map(|e| e.file_name().into_string().ok())
                 .filter(|name| name.starts_with(&prefix) && !name.starts_with('.'))
                 .collect();
@@ -353,7 +932,11 @@ impl AppState {
             if matches.len() == 1 {
                 // Single match - complete it
                 let completed = parent.join(&matches[0]);
-                self.workspace_path_input = format!("{}/", completed.to_string_lossy());
+                self.workspace_path_input = if completed.is_dir() {
+                    format!("{}/", completed.to_string_lossy())
+                } else {
+                    completed.to_string_lossy().to_string()
+                };
             } else if matches.len() > 1 {
                 // Multiple matches - complete common prefix
                 if let Some(common) = common_prefix(&matches) {
@@ -367,7 +950,15 @@ impl AppState {
     }
 
     pub fn load_workspace(&mut self) {
-        let path = self.workspace_path_input.clone();
+        // HAR imports may end with entry filters, e.g. `domain:example.com`
+        let (path, har_filter) = match HarFilter::split(&self.workspace_path_input) {
+            Ok((path, filter)) => (path.to_string(), filter),
+            Err(e) => {
+                self.response.body = format!("Import failed: {:#}", e).into();
+                self.response.status_code = None;
+                return;
+            }
+        };
 
         // Expand ~ to home directory
         let expanded = if path.starts_with('~') {
@@ -381,20 +972,40 @@ impl AppState {
         };
         let path_buf = PathBuf::from(&expanded);
 
+        if http_file::is_http_file(&path_buf) && har_filter.is_empty() {
+            self.open_http_file(path_buf);
+            self.show_workspace_input = false;
+            self.workspace_path_input.clear();
+            return;
+        }
+
+        // A file (or Bruno folder) is a collection from another tool
+        if path_buf.is_file() || interop::bruno::is_collection(&path_buf) {
+            self.import_path(&path_buf, &har_filter);
+            self.show_workspace_input = false;
+            self.workspace_path_input.clear();
+            return;
+        }
+
         // Try to find and parse OpenAPI spec first
         if let Some(spec_path) = detector::find_openapi_spec(&path_buf) {
-            match openapi::parse_openapi(&spec_path) {
+            match openapi::parse_openapi(&spec_path, &path_buf) {
                 Ok(project) => {
-                    let count = project.endpoints.len();
-                    self.workspace = Some(project);
-                    self.selected_endpoint = 0;
-                    self.response.body = format!("✓ Loaded {} endpoints from OpenAPI spec", count);
+                    let mut summary = format!("✓ Loaded {} endpoints from OpenAPI spec", project.endpoints.len());
+                    if !project.unresolved_refs.is_empty() {
+                        summary.push_str("\n\nUnresolved $refs:");
+                        for warning in &project.unresolved_refs {
+                            summary.push_str(&format!("\n  • {}", warning));
+                        }
+                    }
+                    self.set_workspace(project);
+                    self.response.body = summary.into();
                     self.show_workspace_input = false;
                     self.workspace_path_input.clear();
                     return;
                 }
                 Err(e) => {
-                    self.response.body = format!("Error parsing OpenAPI: {}", e);
+                    self.response.body = format!("Error parsing OpenAPI: {}", e).into();
                 }
             }
         }
@@ -417,21 +1028,31 @@ impl AppState {
         if let Some(proj) = project {
             let count = proj.endpoints.len();
             let fw_name = proj.framework.as_str().to_string();
-            self.workspace = Some(proj);
-            self.selected_endpoint = 0;
+            self.set_workspace(proj);
             self.response.body =
-                format!("✓ Loaded {} endpoints from {} source code", count, fw_name);
+                format!("✓ Loaded {} endpoints from {} source code", count, fw_name).into();
         } else {
             self.response.body = format!(
                 "No supported framework detected in {}\n\nSupported: OpenAPI, FastAPI, Flask, Django, Express.js, NestJS, Spring Boot, Laravel",
                 expanded
-            );
+            )
+            .into();
         }
 
         self.show_workspace_input = false;
         self.workspace_path_input.clear();
     }
 
+    /// Show a newly loaded workspace, with no server picked
+    fn set_workspace(&mut self, project: discovery::WorkspaceProject) {
+        self.workspace = Some(Arc::new(project));
+        self.spec_endpoint = None;
+        self.selected_endpoint = 0;
+        self.picked_server = None;
+        self.server_values.clear();
+        self.show_servers = false;
+    }
+
     pub fn next_endpoint(&mut self) {
         if let Some(ws) = &self.workspace {
             if !ws.endpoints.is_empty() {
@@ -460,26 +1081,19 @@ impl AppState {
 
         if let Some(endpoint) = endpoint_opt {
             self.load_endpoint(&endpoint);
+            self.spec_endpoint = Some(self.selected_endpoint);
             self.active_panel = Panel::Url;
         }
     }
 
     fn load_endpoint(&mut self, endpoint: &DiscoveredEndpoint) {
         // Set method
-        self.request.method = match endpoint.method.to_uppercase().as_str() {
-            "GET" => HttpMethod::GET,
-            "POST" => HttpMethod::POST,
-            "PUT" => HttpMethod::PUT,
-            "PATCH" => HttpMethod::PATCH,
-            "DELETE" => HttpMethod::DELETE,
-            _ => HttpMethod::GET,
-        };
+        self.request.method = HttpMethod::parse(&endpoint.method).unwrap_or(HttpMethod::GET);
 
         // Set URL (combine base URL with path)
         let base = self
-            .workspace
-            .as_ref()
-            .and_then(|w| w.base_url.clone())
+            .server_base(endpoint)
+            .or_else(|| self.workspace.as_ref().and_then(|w| w.base_url.clone()))
             .unwrap_or_else(|| "http://localhost:8000".to_string());
         self.request.url = format!("{}{}", base.trim_end_matches('/'), endpoint.path);
         self.cursor_position = self.request.url.len();
@@ -507,8 +1121,278 @@ impl AppState {
             endpoint.method,
             endpoint.path,
             endpoint.auth.as_str()
+        )
+        .into();
+        self.response.status_code = None;
+        self.spec_violations = None;
+    }
+
+    // ========================
+    // Collections
+    // ========================
+
+    /// Import collections and environments exported from another tool
+    /// and save them next to the existing ones, renaming any whose name
+    /// is taken
+    fn import_path(&mut self, path: &Path, filter: &HarFilter) {
+        self.response.status_code = None;
+        let imported = match interop::import_path(path, filter) {
+            Ok(imported) => imported,
+            Err(e) => {
+                self.response.body = format!("Import failed: {:#}", e).into();
+                return;
+            }
+        };
+        let mut summary = imported.summary();
+        let mut save_errors = Vec::new();
+        let mut renamed = Vec::new();
+        for collection in imported.collections {
+            let name = collection.name.clone();
+            match self.storage.add_collection(collection) {
+                Ok(saved) if saved != name => renamed.push(format!("collection {} as {}", name, saved)),
+                Ok(_) => {}
+                Err(e) => save_errors.push(format!("{}: {:#}", name, e)),
+            }
+        }
+        for environment in imported.environments {
+            let name = environment.name.clone();
+            match self.storage.add_environment(environment) {
+                Ok(saved) if saved != name => renamed.push(format!("environment {} as {}", name, saved)),
+                Ok(_) => {}
+                Err(e) => save_errors.push(format!("{}: {:#}", name, e)),
+            }
+        }
+        if !renamed.is_empty() {
+            summary.push_str("\n\nSaved under a new name, the old one being taken:");
+            for rename in renamed {
+                summary.push_str(&format!("\n  • {}", rename));
+            }
+        }
+        if !save_errors.is_empty() {
+            summary.push_str("\n\nCould not save:");
+            for error in save_errors {
+                summary.push_str(&format!("\n  • {}", error));
+            }
+        }
+        summary.push_str("\n\nPress 'C' to browse collections, 'E' to switch environment.");
+        self.response.body = summary.into();
+    }
+
+    fn collection_row_count(&self) -> usize {
+        self.storage
+            .collections
+            .iter()
+            .map(|collection| collection.entries().len())
+            .sum()
+    }
+
+    pub fn collection_next(&mut self) {
+        let count = self.collection_row_count();
+        if count > 0 {
+            self.selected_collection_row = (self.selected_collection_row + 1) % count;
+        }
+    }
+
+    pub fn collection_prev(&mut self) {
+        let count = self.collection_row_count();
+        if count > 0 {
+            self.selected_collection_row = self
+                .selected_collection_row
+                .checked_sub(1)
+                .unwrap_or(count - 1);
+        }
+    }
+
+    /// The collection holding the selected row, and the row itself
+    fn selected_collection_entry(&self) -> Option<(&Collection, CollectionEntry<'_>)> {
+        let mut row = self.selected_collection_row;
+        for collection in &self.storage.collections {
+            let mut entries = collection.entries();
+            if row < entries.len() {
+                return Some((collection, entries.swap_remove(row)));
+            }
+            row -= entries.len();
+        }
+        None
+    }
+
+    /// Load the selected request into the editor. The environment named
+    /// after its collection (its imported variables) becomes active
+    /// unless another one already is.
+    pub fn open_collection_entry(&mut self) {
+        let Some((collection, entry)) = self.selected_collection_entry() else {
+            return;
+        };
+        let Some(request) = entry.request.cloned() else {
+            return;
+        };
+        let collection_name = collection.name.clone();
+        self.http_file_request = self
+            .http_file
+            .as_ref()
+            .filter(|open| open.name == collection_name)
+            .and_then(|_| {
+                let request = entry.request?;
+                collection.requests.iter().position(|r| std::ptr::eq(r, request))
+            });
+
+        if self.storage.current_env.is_none() {
+            self.storage.current_env = self
+                .storage
+                .environments
+                .iter()
+                .position(|env| env.name == collection_name);
+        }
+        self.response.body = format!("Loaded: {} / {}", collection_name, request.name).into();
+        self.response.status_code = None;
+        self.cursor_position = request.url.len();
+        self.request = request;
+        self.spec_endpoint = None;
+        self.active_panel = Panel::Url;
+        self.show_collections = false;
+    }
+
+    /// Open a `.http` file as a collection, with its variables as an
+    /// environment. Neither is copied to `~/.freeman`: the file stays the
+    /// source and `S` writes edits back to it.
+    fn open_http_file(&mut self, path: PathBuf) {
+        self.response.status_code = None;
+        let file = match fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| HttpFile::parse(&content))
+        {
+            Ok(file) => file,
+            Err(e) => {
+                self.response.body = format!("Could not open {}: {:#}", path.display(), e).into();
+                return;
+            }
+        };
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let collection = file.collection(&name);
+        let environment = file.environment(&name);
+
+        let mut summary = format!(
+            "✓ Opened {} with {} request(s) and {} variable(s)",
+            path.display(),
+            collection.requests.len(),
+            environment.variables.len()
+        );
+        if !file.warnings().is_empty() {
+            summary.push_str("\n\nNot supported:");
+            for warning in file.warnings() {
+                summary.push_str(&format!("\n  • {}", warning));
+            }
+        }
+        summary.push_str("\n\nPress 'C' to pick a request, 'S' to save edits back to the file.");
+        self.response.body = summary.into();
+
+        self.storage.insert_collection(collection);
+        self.storage.insert_environment(environment);
+        self.http_file_request = None;
+        self.spec_endpoint = None;
+        self.http_file = Some(OpenHttpFile { path, name, file });
+    }
+
+    /// Write the editor's request, and the values of the file's
+    /// environment, back into the `.http` file they were opened from
+    pub fn save_http_file(&mut self) {
+        self.response.status_code = None;
+        let (Some(open), Some(index)) = (self.http_file.as_mut(), self.http_file_request) else {
+            self.response.body =
+                "Only requests opened from a .http file can be saved (press 'o' to open one)".into();
+            return;
+        };
+        if let Some(environment) = self.storage.environment(&open.name) {
+            open.file.update_variables(environment);
+        }
+        let saved = open
+            .file
+            .update(index, &self.request)
+            .and_then(|()| Ok(fs::write(&open.path, open.file.render())?));
+        self.response.body = match saved {
+            Ok(()) => {
+                let request = open.file.requests().nth(index).cloned();
+                if let (Some(collection), Some(request)) = (
+                    self.storage.collections.iter_mut().find(|c| c.name == open.name),
+                    request,
+                ) {
+                    if let Some(slot) = collection.requests.get_mut(index) {
+                        *slot = request;
+                    }
+                }
+                format!("✓ Saved {} to {}", self.request.name, open.path.display())
+            }
+            Err(e) => format!("Could not save {}: {:#}", open.path.display(), e),
+        }
+        .into();
+    }
+
+    /// Export the selected row's collection as a Postman v2.1 file in
+    /// the current directory, with its environment as variables
+    pub fn export_collection(&mut self) {
+        let Some((collection, _)) = self.selected_collection_entry() else {
+            return;
+        };
+        let variables = self.storage.environment(&collection.name);
+        let name = format!("{}.postman_collection.json", codegen::slug(&collection.name, "collection"));
+        let message = write_export(
+            &collection.name,
+            &name,
+            postman::export(collection, variables),
+            "Not exported (no Postman equivalent):",
+        );
+        self.response.body = message.into();
+        self.response.status_code = None;
+        self.show_collections = false;
+    }
+
+    /// Describe the selected row's collection as an OpenAPI 3.0 document
+    /// in the current directory. The active environment (or the
+    /// collection's own) resolves server URLs; history supplies the
+    /// example responses.
+    pub fn export_openapi(&mut self, as_json: bool) {
+        let Some((collection, _)) = self.selected_collection_entry() else {
+            return;
+        };
+        let variables = self
+            .storage
+            .current_environment()
+            .or_else(|| self.storage.environment(&collection.name));
+        let history: Vec<HistoryEntry> = self.storage.history.iter().cloned().collect();
+        let extension = if as_json { "json" } else { "yaml" };
+        let name = format!("{}.openapi.{}", codegen::slug(&collection.name, "collection"), extension);
+        let message = write_export(
+            &collection.name,
+            &name,
+            interop::openapi::export(collection, variables, &history, as_json),
+            "Not described:",
         );
+        self.response.body = message.into();
         self.response.status_code = None;
+        self.show_collections = false;
+    }
+
+    /// Save the request history, oldest first, as a HAR file in the
+    /// current directory, each request as it was sent
+    pub fn export_history_har(&mut self) {
+        self.response.status_code = None;
+        if self.storage.history.is_empty() {
+            self.response.body = "No history to export yet".into();
+            return;
+        }
+        let entries: Vec<HistoryEntry> = self.storage.history.iter().rev().cloned().collect();
+        let name = "freeman-history.har";
+        self.response.body = match har::export(&entries) {
+            Ok(content) => match write_new_file(Path::new("."), name, &content) {
+                Ok(path) => format!("✓ Exported {} history entries to {}", entries.len(), path.display()),
+                Err(e) => format!("Could not write {}: {}", name, e),
+            },
+            Err(e) => format!("Export failed: {:#}", e),
+        }
+        .into();
     }
 
     // ========================
@@ -522,16 +1406,26 @@ impl AppState {
         }
 
         self.is_loading = true;
-        self.response.body = String::from("Loading...");
+        self.spec_violations = None;
+        self.response.body = "Loading...".into();
         self.response.status_code = None;
+        self.response.timing = None;
+        self.response.tls = None;
+        self.response.headers.clear();
+        self.response.http_version = None;
 
         let id = self.next_id();
         self.pending_request_id = Some(id);
+        self.sent_request = Some(self.substitute_request(self.request.clone()));
 
         Some(NetworkCommand::ExecuteRequest {
             id,
-            request: self.request.clone(),
+            request: self.outgoing_request(),
             environment: self.storage.current_environment().cloned(),
+            tls: self.storage.resolve_tls(self.request.tls.as_ref(), &self.request.url),
+            host_override: self
+                .storage
+                .resolve_host_override(&self.request.host_overrides, &self.request.url),
         })
     }
 
@@ -541,6 +1435,13 @@ impl AppState {
             return Err("URL cannot be empty".to_string());
         }
 
+        if url.starts_with(UNIX_SCHEME) {
+            return match split_unix_url(url) {
+                Some(_) => Ok(()),
+                None => Err("unix:// URL must contain a socket path".to_string()),
+            };
+        }
+
         // Check for basic URL structure
         if !url.starts_with("http://") && !url.starts_with("https://") {
             return Err("URL must start with http:// or https://".to_string());
@@ -552,6 +1453,24 @@ impl AppState {
             return Err("URL must contain a host".to_string());
         }
 
+        // Connections always go to an explicit URL port, so an override
+        // can only change the port of URLs using the scheme default
+        let connect_port = self
+            .storage
+            .resolve_host_override(&self.request.host_overrides, url)
+            .and_then(|host_override| host_override.connect_port);
+        let url_port = reqwest::Url::parse(&self.storage.substitute(url))
+            .ok()
+            .and_then(|url| url.port());
+        if let (Some(connect_port), Some(url_port)) = (connect_port, url_port) {
+            if connect_port != url_port {
+                return Err(format!(
+                    "host override connects to port {} but the URL sets port {}; drop the port from the URL",
+                    connect_port, url_port
+                ));
+            }
+        }
+
         Ok(())
     }
 
@@ -563,27 +1482,67 @@ impl AppState {
 
         // Validate URL before sending
         if let Err(error) = self.validate_url(&self.request.url) {
-            self.response.body = format!("Invalid URL: {}", error);
+            self.response.body = format!("Invalid URL: {}", error).into();
             self.response.status_code = None;
             return None;
         }
 
         self.is_loading = true;
-        self.response.body = String::from("Starting request...");
+        self.spec_violations = None;
+        self.response.body = "Starting request...".into();
         self.response.status_code = None;
+        self.response.timing = None;
+        self.response.tls = None;
+        self.response.headers.clear();
+        self.response.http_version = None;
         self.streaming_body.clear();
         self.bytes_received = 0;
 
         let id = self.next_id();
         self.pending_request_id = Some(id);
+        self.sent_request = Some(self.substitute_request(self.request.clone()));
 
         Some(NetworkCommand::ExecuteStreamingRequest {
             id,
-            request: self.request.clone(),
+            request: self.outgoing_request(),
             environment: self.storage.current_environment().cloned(),
+            tls: self.storage.resolve_tls(self.request.tls.as_ref(), &self.request.url),
+            host_override: self
+                .storage
+                .resolve_host_override(&self.request.host_overrides, &self.request.url),
         })
     }
 
+    /// The current request as it goes over the wire, with the proxy
+    /// resolved from the request, environment and global settings and
+    /// a `unix://` URL split into its socket and HTTP path. A host
+    /// override from the environment is added to the request's own.
+    fn outgoing_request(&self) -> Request {
+        let mut request = self.request.clone();
+        request.proxy = Some(self.storage.resolve_proxy(&self.request));
+        if let Some(host_override) = self
+            .storage
+            .resolve_host_override(&self.request.host_overrides, &self.request.url)
+        {
+            if !request.host_overrides.contains(&host_override) {
+                request.host_overrides.push(host_override);
+            }
+        }
+        request.resolve_unix_url();
+        request.unix_socket = request
+            .unix_socket
+            .map(|socket| self.storage.substitute(&socket));
+        request
+    }
+
+    /// The outgoing request with the TLS profile it is sent with, as
+    /// exported to cURL and other tools
+    fn exported_request(&self) -> Request {
+        let mut request = self.outgoing_request();
+        request.tls = self.storage.resolve_tls(self.request.tls.as_ref(), &self.request.url);
+        request
+    }
+
     /// Cancel the current pending request
     pub fn cancel_request(&mut self) -> Option<NetworkCommand> {
         self.pending_request_id.map(NetworkCommand::CancelRequest)
@@ -593,6 +1552,17 @@ impl AppState {
     // Response handling
     // ========================
 
+    /// Store a received body with its pretty printed form
+    fn set_response_body(&mut self, body: String, headers: &[Header]) {
+        let content_type = headers
+            .iter()
+            .find(|h| h.key.eq_ignore_ascii_case("content-type"))
+            .map(|h| h.value.as_str());
+        self.body_kind = BodyKind::detect(content_type, &body);
+        self.pretty_body = formatting::pretty(self.body_kind, &body).map(IndexedText::from);
+        self.response.body = body.into();
+    }
+
     pub fn handle_response(&mut self, response: NetworkResponse) {
         // Only process if it matches the pending request (for HTTP responses)
         let response_id = response.id();
@@ -603,12 +1573,20 @@ impl AppState {
                 status,
                 body,
                 time_ms,
+                timing,
+                tls,
+                headers,
+                http_version,
                 ..
             } => {
                 if is_for_pending {
+                    self.set_response_body(body, &headers);
                     self.response.status_code = Some(status);
-                    self.response.body = body;
                     self.response.time_ms = time_ms;
+                    self.response.timing = Some(timing);
+                    self.response.tls = tls;
+                    self.response.headers = headers;
+                    self.response.http_version = Some(http_version);
                     self.finalize_request();
                 }
             }
@@ -621,11 +1599,17 @@ impl AppState {
                     // Append chunk to streaming body
                     self.streaming_body.push_str(&chunk);
                     self.bytes_received = bytes_received;
-                    // Show streaming progress
+                    // Show streaming progress and the start of the body
+                    let mut preview = self.streaming_body.len().min(STREAM_PREVIEW_BYTES);
+                    while !self.streaming_body.is_char_boundary(preview) {
+                        preview -= 1;
+                    }
                     self.response.body = format!(
                         "Streaming... {} bytes received\n\n{}",
-                        bytes_received, &self.streaming_body
-                    );
+                        bytes_received,
+                        &self.streaming_body[..preview]
+                    )
+                    .into();
                 }
             }
             NetworkResponse::StreamComplete {
@@ -635,19 +1619,14 @@ impl AppState {
                 ..
             } => {
                 if is_for_pending {
-                    // Format final body as JSON if possible
-                    let formatted = if let Ok(json) =
-                        serde_json::from_str::<serde_json::Value>(&self.streaming_body)
-                    {
-                        serde_json::to_string_pretty(&json)
-                            .unwrap_or_else(|_| self.streaming_body.clone())
-                    } else {
-                        self.streaming_body.clone()
-                    };
-
+                    let body = std::mem::take(&mut self.streaming_body);
+                    self.set_response_body(body, &[]);
                     self.response.status_code = Some(status);
-                    self.response.body = formatted;
                     self.response.time_ms = time_ms;
+                    self.response.timing = None;
+                    self.response.tls = None;
+                    self.response.headers.clear();
+                    self.response.http_version = None;
                     self.bytes_received = total_bytes;
                     self.finalize_request();
                 }
@@ -657,16 +1636,24 @@ impl AppState {
             } => {
                 if is_for_pending {
                     self.response.status_code = None;
-                    self.response.body = message;
+                    self.response.body = message.into();
                     self.response.time_ms = time_ms;
+                    self.response.timing = None;
+                    self.response.tls = None;
+                    self.response.headers.clear();
+                    self.response.http_version = None;
                     self.finalize_request();
                 }
             }
             NetworkResponse::Cancelled { .. } => {
                 if is_for_pending {
                     self.response.status_code = None;
-                    self.response.body = String::from("Request cancelled");
+                    self.response.body = "Request cancelled".into();
                     self.response.time_ms = 0;
+                    self.response.timing = None;
+                    self.response.tls = None;
+                    self.response.headers.clear();
+                    self.response.http_version = None;
                     self.is_loading = false;
                     self.pending_request_id = None;
                     self.streaming_body.clear();
@@ -677,42 +1664,26 @@ impl AppState {
             NetworkResponse::WebSocketConnected { id } => {
                 if self.ws.connection_id == Some(id) {
                     self.ws.connected = true;
-                    self.ws.messages.push(WsLogEntry {
-                        direction: WsDirection::System,
-                        content: "Connected!".to_string(),
-                        timestamp: chrono::Utc::now(),
-                    });
+                    self.ws.log(WsDirection::System, "Connected!".to_string());
                 }
             }
             NetworkResponse::WebSocketMessage { id, message } => {
                 if self.ws.connection_id == Some(id) {
-                    self.ws.messages.push(WsLogEntry {
-                        direction: WsDirection::Received,
-                        content: message,
-                        timestamp: chrono::Utc::now(),
-                    });
+                    self.ws.log(WsDirection::Received, message);
                 }
             }
             NetworkResponse::WebSocketClosed { id } => {
                 if self.ws.connection_id == Some(id) {
                     self.ws.connected = false;
                     self.ws.connection_id = None;
-                    self.ws.messages.push(WsLogEntry {
-                        direction: WsDirection::System,
-                        content: "Connection closed".to_string(),
-                        timestamp: chrono::Utc::now(),
-                    });
+                    self.ws.log(WsDirection::System, "Connection closed".to_string());
                 }
             }
             NetworkResponse::WebSocketError { id, error } => {
                 if self.ws.connection_id == Some(id) {
                     self.ws.connected = false;
                     self.ws.connection_id = None;
-                    self.ws.messages.push(WsLogEntry {
-                        direction: WsDirection::System,
-                        content: format!("Error: {}", error),
-                        timestamp: chrono::Utc::now(),
-                    });
+                    self.ws.log(WsDirection::System, format!("Error: {}", error));
                 }
             }
         }
@@ -740,25 +1711,18 @@ impl AppState {
         self.ws.connection_id = Some(id);
 
         // Add system message
-        self.ws.messages.push(WsLogEntry {
-            direction: WsDirection::System,
-            content: format!("Connecting to {}...", self.ws.url),
-            timestamp: chrono::Utc::now(),
-        });
+        self.ws.log(WsDirection::System, format!("Connecting to {}...", self.ws.url));
 
         Some(NetworkCommand::ConnectWebSocket {
             id,
             url: self.ws.url.clone(),
+            tls: self.storage.resolve_tls(None, &self.ws.url),
         })
     }
 
     pub fn ws_disconnect(&mut self) -> Option<NetworkCommand> {
         if let Some(id) = self.ws.connection_id {
-            self.ws.messages.push(WsLogEntry {
-                direction: WsDirection::System,
-                content: "Disconnecting...".to_string(),
-                timestamp: chrono::Utc::now(),
-            });
+            self.ws.log(WsDirection::System, "Disconnecting...".to_string());
             Some(NetworkCommand::CloseWebSocket(id))
         } else {
             None
@@ -774,11 +1738,7 @@ impl AppState {
             let message = self.ws.input.clone();
 
             // Add to log
-            self.ws.messages.push(WsLogEntry {
-                direction: WsDirection::Sent,
-                content: message.clone(),
-                timestamp: chrono::Utc::now(),
-            });
+            self.ws.log(WsDirection::Sent, message.clone());
 
             // Clear input
             self.ws.input.clear();
@@ -854,13 +1814,36 @@ impl AppState {
         self.bytes_received = 0;
 
         // Add to history
+        let sent = self
+            .sent_request
+            .take()
+            .unwrap_or_else(|| self.substitute_request(self.request.clone()));
         let entry = HistoryEntry {
             request: self.request.clone(),
+            sent,
             response: self.response.clone(),
             timestamp: chrono::Utc::now(),
         };
         self.storage.add_to_history(entry);
         self.history_index = None;
+        self.spec_violations = self.validate_against_spec();
+        // Keep the diff popup on the same entries as they move down
+        if self.show_history_diff {
+            self.diff_selected = (self.diff_selected + 1).min(self.storage.history_len() - 1);
+            self.diff_base = self.diff_base.map(|index| index + 1);
+        }
+    }
+
+    /// Check the response against the spec of the workspace endpoint the
+    /// request was loaded from, unless its method has been changed since
+    fn validate_against_spec(&self) -> Option<Vec<Violation>> {
+        let status = self.response.status_code?;
+        let workspace = self.workspace.as_ref()?;
+        let endpoint = workspace.endpoints.get(self.spec_endpoint?)?;
+        if endpoint.method != self.request.method.as_str() {
+            return None;
+        }
+        validate::validate_response(endpoint, workspace.spec.as_ref(), status, &self.response.body)
     }
 
     // ========================
@@ -875,12 +1858,12 @@ impl AppState {
 
         // Validate endpoint
         if let Err(error) = self.validate_url(&self.gql.endpoint) {
-            self.gql.response = format!("Invalid endpoint: {}", error);
+            self.gql.response = format!("Invalid endpoint: {}", error).into();
             return None;
         }
 
         self.gql.is_loading = true;
-        self.gql.response = String::from("Executing query...");
+        self.gql.response = "Executing query...".into();
 
         let id = self.next_id();
         self.gql.pending_request_id = Some(id);
@@ -900,6 +1883,14 @@ impl AppState {
             variables,
             headers: self.request.headers.clone(),
             auth: self.request.auth.clone(),
+            proxy: match self.storage.current_environment() {
+                Some(environment) => self.storage.resolve_proxy(&self.request).substitute(environment),
+                None => self.storage.resolve_proxy(&self.request),
+            },
+            tls: self.storage.resolve_tls(self.request.tls.as_ref(), &self.gql.endpoint),
+            host_override: self
+                .storage
+                .resolve_host_override(&self.request.host_overrides, &self.gql.endpoint),
         })
     }
 
@@ -907,7 +1898,7 @@ impl AppState {
     #[allow(dead_code)] // Reserved for expanded response handling
     pub fn handle_gql_response(&mut self, id: u64, _status: u16, body: String, time_ms: u64) {
         if self.gql.pending_request_id == Some(id) {
-            self.gql.response = body;
+            self.gql.response = formatting::pretty(BodyKind::Json, &body).unwrap_or(body).into();
             self.gql.time_ms = time_ms;
             self.gql.is_loading = false;
             self.gql.pending_request_id = None;
@@ -918,7 +1909,7 @@ impl AppState {
     #[allow(dead_code)] // Reserved for expanded error handling
     pub fn handle_gql_error(&mut self, id: u64, error: String, time_ms: u64) {
         if self.gql.pending_request_id == Some(id) {
-            self.gql.response = format!("Error: {}", error);
+            self.gql.response = format!("Error: {}", error).into();
             self.gql.time_ms = time_ms;
             self.gql.is_loading = false;
             self.gql.pending_request_id = None;
@@ -1073,6 +2064,82 @@ impl AppState {
     }
 }
 
+/// Write an exported collection to a new file, describing the result
+fn write_export(
+    collection: &str,
+    name: &str,
+    exported: anyhow::Result<Exported>,
+    heading: &str,
+) -> String {
+    let exported = match exported {
+        Ok(exported) => exported,
+        Err(e) => return format!("Export failed: {:#}", e),
+    };
+    let mut message = match write_new_file(Path::new("."), name, &exported.content) {
+        Ok(path) => format!("✓ Exported {} to {}", collection, path.display()),
+        Err(e) => format!("Could not write {}: {}", name, e),
+    };
+    if !exported.warnings.is_empty() {
+        message.push_str("\n\n");
+        message.push_str(heading);
+        for warning in &exported.warnings {
+            message.push_str(&format!("\n  • {}", warning));
+        }
+    }
+    message
+}
+
+/// The proxy setting a proxy popup input stands for, `None` when empty.
+/// Credentials and bypass list of an `existing` manual proxy are kept
+/// when only the URL changes.
+fn proxy_setting(input: &str, existing: Option<ProxySetting>) -> Option<ProxySetting> {
+    match input.trim() {
+        "" => None,
+        "direct" | "none" => Some(ProxySetting::Direct),
+        "system" => Some(ProxySetting::System),
+        url => {
+            let url = if url.contains("://") {
+                url.to_string()
+            } else {
+                format!("http://{}", url)
+            };
+            let mut proxy = match existing {
+                Some(ProxySetting::Manual(proxy)) => proxy,
+                _ => ProxyConfig::new(""),
+            };
+            proxy.url = url;
+            Some(ProxySetting::Manual(proxy))
+        }
+    }
+}
+
+/// Write a file in `dir`, adding `-1`, `-2`, ... to the name rather
+/// than overwriting; returns the absolute path
+fn write_new_file(dir: &Path, name: &str, content: &str) -> std::io::Result<PathBuf> {
+    use std::io::Write;
+
+    let (stem, extension) = match name.split_once('.') {
+        Some((stem, extension)) => (stem, format!(".{}", extension)),
+        None => (name, String::new()),
+    };
+    for n in 0.. {
+        let path = match n {
+            0 => dir.join(name),
+            n => dir.join(format!("{}-{}{}", stem, n, extension)),
+        };
+        // create_new fails rather than replacing a file made meanwhile
+        match fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                file.write_all(content.as_bytes())?;
+                return Ok(fs::canonicalize(&path).unwrap_or(path));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!("ran out of file name suffixes")
+}
+
 /// Find common prefix among strings
 fn common_prefix(strings: &[String]) -> Option<String> {
     if strings.is_empty() {
@@ -1102,12 +2169,63 @@ mod tests {
     use super::*;
     use crate::app::AppState;
     use crate::messages::ui_events::Panel;
-    use crate::models::HttpMethod;
+    use crate::models::{HttpMethod, Response};
 
     fn create_test_state() -> AppState {
         AppState::new()
     }
 
+    #[test]
+    fn test_write_new_file_never_overwrites() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("api.yaml"), "original").unwrap();
+        fs::write(dir.path().join("api-1.yaml"), "first copy").unwrap();
+
+        let path = write_new_file(dir.path(), "api.yaml", "new").unwrap();
+        assert_eq!(path.file_name().unwrap(), "api-2.yaml");
+        assert_eq!(fs::read_to_string(dir.path().join("api.yaml")).unwrap(), "original");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+
+        let path = write_new_file(dir.path(), "Makefile", "all:").unwrap();
+        assert_eq!(path.file_name().unwrap(), "Makefile");
+        let path = write_new_file(dir.path(), "Makefile", "all:").unwrap();
+        assert_eq!(path.file_name().unwrap(), "Makefile-1");
+    }
+
+    #[test]
+    fn test_curl_exports_include_the_tls_profile() {
+        let mut state = create_test_state();
+        state.request.url = "https://api.test/users".to_string();
+        state.request.tls = Some(crate::models::TlsProfile {
+            hosts: vec!["api.test".to_string()],
+            ca_certs: vec![PathBuf::from("corp-ca.pem")],
+            client_cert: None,
+        });
+
+        state.export_curl();
+        let quick = state.response.body.to_string();
+        assert!(quick.contains("--cacert 'corp-ca.pem'"), "{}", quick);
+
+        state.export_target = CodeTarget::ALL.iter().position(|t| *t == CodeTarget::Curl).unwrap();
+        state.export_substitute = false;
+        assert_eq!(state.generate_export().1, quick);
+    }
+
+    #[test]
+    fn test_proxy_setting_keeps_credentials() {
+        let mut proxy = ProxyConfig::new("http://old:3128");
+        proxy.username = Some("alice".to_string());
+        let setting = proxy_setting(" proxy.corp:8080 ", Some(ProxySetting::Manual(proxy)));
+        let Some(ProxySetting::Manual(proxy)) = setting else {
+            panic!("expected a manual proxy, got {:?}", setting);
+        };
+        assert_eq!(proxy.url, "http://proxy.corp:8080");
+        assert_eq!(proxy.username.as_deref(), Some("alice"));
+
+        assert_eq!(proxy_setting("direct", None), Some(ProxySetting::Direct));
+        assert_eq!(proxy_setting("  ", Some(ProxySetting::Direct)), None);
+    }
+
     // ========================
     // Navigation tests
     // ========================
@@ -1150,6 +2268,262 @@ mod tests {
         assert_eq!(state.active_panel, Panel::Workspace);
     }
 
+    #[test]
+    fn test_search_scrolls_to_matches() {
+        let mut state = create_test_state();
+        state.response.body = (0..50).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n").into();
+        state.response_scroll = 20;
+
+        // Typing selects the first match from the visible line on
+        state.open_search();
+        for c in "line 3".chars() {
+            state.search_char(c);
+        }
+        assert_eq!(state.search.current, 1);
+        assert_eq!(state.response_scroll, 28);
+
+        state.search_step(false);
+        assert_eq!(state.search.current, 0);
+        assert_eq!(state.response_scroll, 1);
+        state.search_step(false);
+        assert_eq!(state.search.current, 10);
+
+        state.search.query = "^line [0-9]$".to_string();
+        state.toggle_search_regex();
+        assert_eq!(state.search_matches().as_ref().as_ref().unwrap().len(), 10);
+        assert_eq!(state.search.current, 0);
+        // Stepping and rendering reuse the matches found for the query
+        let matches = state.search_matches();
+        state.search_step(true);
+        assert!(Arc::ptr_eq(&matches, &state.render_state().search_matches));
+
+        state.clear_search();
+        assert!(!state.search.is_active());
+        assert!(state.search.regex);
+    }
+
+    #[test]
+    fn test_filtered_body_is_applied_once() {
+        let mut state = create_test_state();
+        state.response.status_code = Some(200);
+        state.response.body = r#"{"items": [{"id": 1}, {"id": 2}]}"#.into();
+        state.request.response_filter = "$.items[*].id".to_string();
+
+        let first = state.filtered_body().unwrap().unwrap();
+        let render = state.render_state();
+        let shared = render.filtered_body.unwrap().unwrap();
+        assert_eq!(first.as_str().as_ptr(), shared.as_str().as_ptr());
+
+        // A new expression is applied again
+        state.request.response_filter = "$.items[0].id".to_string();
+        assert_eq!(state.filtered_body().unwrap().unwrap(), "[\n  1\n]");
+    }
+
+    #[test]
+    fn test_history_keeps_the_request_as_sent() {
+        let mut state = create_test_state();
+        let mut staging = Environment::new("Staging");
+        staging.set("version", "v2");
+        state.storage.insert_environment(staging);
+        state.storage.insert_environment(Environment::new("Production"));
+        state.storage.current_env = Some(0);
+        state.request.url = "https://api.test/{{version}}/items?q={{missing}}".to_string();
+
+        state.prepare_streaming_request().unwrap();
+        // Switching before the response arrives does not change what was sent
+        state.storage.cycle_environment();
+        let id = state.pending_request_id.unwrap();
+        state.handle_response(NetworkResponse::Success {
+            id,
+            status: 200,
+            body: "[]".to_string(),
+            time_ms: 5,
+            timing: Default::default(),
+            tls: None,
+            headers: Vec::new(),
+            http_version: "HTTP/1.1".to_string(),
+        });
+
+        let entry = &state.storage.history[0];
+        assert_eq!(entry.request.url, "https://api.test/{{version}}/items?q={{missing}}");
+        assert_eq!(entry.sent.url, "https://api.test/v2/items?q={{missing}}");
+    }
+
+    #[test]
+    fn test_received_body_is_pretty_printed_until_raw() {
+        let mut state = create_test_state();
+        state.pending_request_id = Some(7);
+        state.handle_response(NetworkResponse::Success {
+            id: 7,
+            status: 200,
+            body: "<a><b>1</b></a>".to_string(),
+            time_ms: 5,
+            timing: Default::default(),
+            tls: None,
+            headers: vec![Header::new("Content-Type", "application/xml")],
+            http_version: "HTTP/1.1".to_string(),
+        });
+        assert_eq!(state.shown_kind(), BodyKind::Xml);
+        assert_eq!(state.shown_body(), "<a>\n  <b>1</b>\n</a>");
+        assert_eq!(state.storage.history[0].response.body, "<a><b>1</b></a>");
+
+        state.toggle_raw_body();
+        assert_eq!(state.shown_body(), "<a><b>1</b></a>");
+    }
+
+    #[test]
+    fn test_tree_folding_and_capture_selection() {
+        let mut state = create_test_state();
+        state.response.body = r#"{"tags": ["a"], "user": {"id": 7, "name": "Ann"}}"#.into();
+        state.response_view = ResponseView::Tree;
+
+        // Down to "id", then left jumps to its parent, "user"
+        for _ in 0..4 {
+            state.tree_step(true);
+        }
+        state.show_capture_input();
+        assert_eq!(state.capture_name, "id");
+        assert_eq!(state.capture_value, "7");
+        state.tree_collapse();
+        assert_eq!(state.tree_selected, 3);
+
+        // Left again folds "user", which becomes the last row
+        state.tree_collapse();
+        assert!(state.tree_collapsed.contains("$.user"));
+        state.tree_step(true);
+        assert_eq!(state.with_tree_rows(|rows, selected| rows[selected].path.clone()).unwrap(), "$.user");
+        state.tree_toggle();
+        assert!(state.tree_collapsed.is_empty());
+
+        // Moving and rendering reuse the rows; folding builds them again
+        let rows = state.tree_rows();
+        state.tree_step(true);
+        state.tree_step(false);
+        assert!(Arc::ptr_eq(&rows, state.render_state().tree.as_ref().unwrap()));
+        state.tree_toggle();
+        assert!(!Arc::ptr_eq(&rows, &state.tree_rows()));
+    }
+
+    #[test]
+    fn test_render_state_shares_websocket_log() {
+        let mut state = create_test_state();
+        state.ws.connection_id = Some(1);
+        state.handle_response(NetworkResponse::WebSocketMessage { id: 1, message: "one".to_string() });
+        let render = state.render_state();
+        assert!(render.ws_messages.ptr_eq(&state.ws.messages));
+
+        // A new message leaves the rendered log as it was
+        state.handle_response(NetworkResponse::WebSocketMessage { id: 1, message: "two".to_string() });
+        assert_eq!(render.ws_messages.len(), 1);
+        assert_eq!(state.ws.messages.len(), 2);
+
+        // Unchanged request text is shared with the next render state
+        let next = state.render_state();
+        assert!(Arc::ptr_eq(&render.body, &next.body));
+        assert!(Arc::ptr_eq(&render.headers, &next.headers));
+        assert!(Arc::ptr_eq(&render.gql_query, &next.gql_query));
+    }
+
+    #[test]
+    fn test_diff_previous_run_of_same_request() {
+        let mut state = create_test_state();
+        state.storage.history.clear();
+        for (url, body) in [("https://a.test/x", r#"{"v": 1}"#), ("https://b.test", "{}"), ("https://a.test/x", r#"{"v": 2}"#)] {
+            state.request.url = url.to_string();
+            state.response = Response {
+                status_code: Some(200),
+                body: body.into(),
+                ..Response::default()
+            };
+            state.finalize_request();
+        }
+
+        state.show_history_diff();
+        state.diff_previous_run();
+        let diff = state.history_diff.clone().expect("diff shown");
+        assert!(diff.left_title.contains("https://a.test/x"));
+        assert_eq!(diff.changes, 1);
+
+        // Esc leaves the comparison, then closes the popup
+        state.diff_back();
+        assert!(state.history_diff.is_none() && state.show_history_diff);
+        state.diff_back();
+        assert!(!state.show_history_diff);
+    }
+
+    #[test]
+    fn test_select_server_and_variable() {
+        use crate::discovery::{DiscoveredEndpoint, ServerSpec, ServerVariable, WorkspaceProject};
+
+        let server = |url: &str| ServerSpec {
+            url: url.to_string(),
+            description: None,
+            variables: Vec::new(),
+        };
+        let mut project = WorkspaceProject::new(PathBuf::from("."));
+        project.servers = vec![
+            ServerSpec {
+                url: "https://{env}.example.com".to_string(),
+                description: None,
+                variables: vec![ServerVariable {
+                    name: "env".to_string(),
+                    default: "prod".to_string(),
+                    choices: vec!["prod".to_string(), "staging".to_string()],
+                    description: None,
+                }],
+            },
+            server("http://localhost:3000"),
+        ];
+        let mut upload = DiscoveredEndpoint::new("POST", "/files");
+        upload.servers = vec![server("https://upload.example.com")];
+        project.endpoints = vec![DiscoveredEndpoint::new("GET", "/users"), upload];
+        let mut state = create_test_state();
+        // A `baseUrl` from an imported collection does not pick the server
+        let mut imported = Environment::new("Imported");
+        imported.set("baseUrl", "https://postman.example.com");
+        state.storage.insert_environment(imported);
+        state.storage.current_env = Some(0);
+        state.set_workspace(project);
+
+        // Nothing picked: the spec's first server, or the endpoint's own
+        state.select_endpoint();
+        assert_eq!(state.request.url, "https://prod.example.com/users");
+        state.next_endpoint();
+        state.select_endpoint();
+        assert_eq!(state.request.url, "https://upload.example.com/files");
+
+        // The endpoint's own server is listed first and can be picked
+        state.show_servers();
+        assert!(state.show_servers);
+        assert_eq!(state.server_candidates().len(), 3);
+        state.server_pick();
+        assert_eq!(state.picked_server.as_ref().map(|s| s.url.as_str()), Some("https://upload.example.com"));
+        state.prev_endpoint();
+        state.select_endpoint();
+        assert_eq!(state.request.url, "https://upload.example.com/users");
+
+        // Rows: server 0, its `env` variable, server 1
+        state.show_servers();
+        state.server_step(true);
+        state.server_choice(true);
+        assert_eq!(state.server_values.get("env").map(String::as_str), Some("staging"));
+        state.server_step(false);
+        state.server_pick();
+        assert!(!state.show_servers);
+        state.next_endpoint();
+        state.select_endpoint();
+        assert_eq!(state.request.url, "https://staging.example.com/files");
+
+        // A server linked to an environment replaces the pick on switching
+        let mut linked = Environment::new("Local");
+        linked.server = Some("http://localhost:3000".to_string());
+        state.storage.insert_environment(linked);
+        state.cycle_environment();
+        assert!(state.picked_server.is_none());
+        state.select_endpoint();
+        assert_eq!(state.request.url, "http://localhost:3000/files");
+    }
+
     // ========================
     // URL validation tests
     // ========================
@@ -1205,6 +2579,12 @@ mod tests {
         state.cycle_method();
         assert_eq!(state.request.method, HttpMethod::DELETE);
 
+        state.cycle_method();
+        assert_eq!(state.request.method, HttpMethod::HEAD);
+
+        state.cycle_method();
+        assert_eq!(state.request.method, HttpMethod::OPTIONS);
+
         state.cycle_method();
         assert_eq!(state.request.method, HttpMethod::GET);
     }
@@ -0,0 +1,83 @@
+//! Values derived from app state, kept until their inputs change
+
+use std::sync::Arc;
+
+/// A value computed from a key, recomputed only when the key differs
+/// from the one it was last computed from
+#[derive(Debug)]
+pub struct Memo<K, V> {
+    cached: Option<(K, V)>,
+}
+
+impl<K, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Memo { cached: None }
+    }
+}
+
+impl<K: PartialEq, V: Clone> Memo<K, V> {
+    pub fn get(&mut self, key: K, compute: impl FnOnce(&K) -> V) -> V {
+        match &self.cached {
+            Some((cached, value)) if *cached == key => value.clone(),
+            _ => {
+                let value = compute(&key);
+                self.cached = Some((key, value.clone()));
+                value
+            }
+        }
+    }
+}
+
+/// A shared copy of a value that is edited in place, copied again only
+/// once the value has changed
+#[derive(Debug)]
+pub struct Snapshot<T: ?Sized> {
+    shared: Option<Arc<T>>,
+}
+
+impl<T: ?Sized> Default for Snapshot<T> {
+    fn default() -> Self {
+        Snapshot { shared: None }
+    }
+}
+
+impl<T: ?Sized + PartialEq> Snapshot<T> {
+    pub fn of(&mut self, current: &T) -> Arc<T>
+    where
+        for<'a> Arc<T>: From<&'a T>,
+    {
+        match &self.shared {
+            Some(shared) if **shared == *current => shared.clone(),
+            _ => self.shared.insert(Arc::from(current)).clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recomputes_only_for_new_keys() {
+        let mut memo = Memo::default();
+        let mut runs = 0;
+        for key in [1, 1, 2, 2, 1] {
+            memo.get(key, |k| {
+                runs += 1;
+                k * 10
+            });
+        }
+        assert_eq!(runs, 3);
+        assert_eq!(memo.get(1, |_| 0), 10);
+    }
+
+    #[test]
+    fn test_snapshot_copies_only_changes() {
+        let mut snapshot = Snapshot::default();
+        let mut body = String::from("{}");
+        let first = snapshot.of(body.as_str());
+        assert!(Arc::ptr_eq(&first, &snapshot.of(body.as_str())));
+        body.push('\n');
+        assert_eq!(&*snapshot.of(body.as_str()), "{}\n");
+    }
+}
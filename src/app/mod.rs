@@ -6,6 +6,8 @@
 pub mod state;
 pub mod actor;
 pub mod commands;
+pub mod memo;
+pub mod search;
 
 pub use state::AppState;
 pub use actor::AppActor;
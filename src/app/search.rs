@@ -0,0 +1,149 @@
+//! Incremental search in the response body
+
+use regex::{Regex, RegexBuilder};
+
+/// The `/` search of the response panel
+#[derive(Clone, Debug, Default)]
+pub struct ResponseSearch {
+    pub query: String,
+    /// Whether the search bar is taking input
+    pub editing: bool,
+    pub case_sensitive: bool,
+    /// Treat the query as a regular expression instead of plain text
+    pub regex: bool,
+    /// Index of the selected match
+    pub current: usize,
+    /// Body line the search started from; typing selects the first
+    /// match at or after it
+    pub origin_line: usize,
+}
+
+/// The matches of a search, or why its pattern is invalid
+pub type Matches = Result<Vec<SearchMatch>, String>;
+
+/// A match in the displayed body: a byte range within one line
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchMatch {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl ResponseSearch {
+    /// Whether there is a query to highlight, or the bar is open
+    pub fn is_active(&self) -> bool {
+        self.editing || !self.query.is_empty()
+    }
+
+    fn pattern(&self) -> Result<Regex, String> {
+        let pattern = if self.regex {
+            self.query.clone()
+        } else {
+            regex::escape(&self.query)
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .build()
+            .map_err(|e| match e {
+                regex::Error::Syntax(message) => {
+                    let reason = message.lines().last().unwrap_or_default().trim();
+                    reason.strip_prefix("error:").unwrap_or(reason).trim().to_string()
+                }
+                other => other.to_string(),
+            })
+    }
+
+    /// Every non-empty match in `text`, line by line
+    pub fn find(&self, text: &str) -> Result<Vec<SearchMatch>, String> {
+        if self.query.is_empty() {
+            return Ok(Vec::new());
+        }
+        let pattern = self.pattern()?;
+        Ok(text
+            .lines()
+            .enumerate()
+            .flat_map(|(line, content)| {
+                pattern
+                    .find_iter(content)
+                    .filter(|m| !m.is_empty())
+                    .map(move |m| SearchMatch {
+                        line,
+                        start: m.start(),
+                        end: m.end(),
+                    })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "{\n  \"Name\": \"name\",\n  \"id\": 42\n}";
+
+    #[test]
+    fn test_find() {
+        let mut search = ResponseSearch {
+            query: "name".to_string(),
+            ..ResponseSearch::default()
+        };
+        let matches = search.find(TEXT).unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0], SearchMatch { line: 1, start: 3, end: 7 });
+
+        search.case_sensitive = true;
+        assert_eq!(search.find(TEXT).unwrap().len(), 1);
+
+        search.query = "\\d+".to_string();
+        assert!(search.find(TEXT).unwrap().is_empty());
+        search.regex = true;
+        assert_eq!(search.find(TEXT).unwrap(), vec![SearchMatch { line: 2, start: 8, end: 10 }]);
+
+        search.query = "(".to_string();
+        assert!(search.find(TEXT).is_err());
+    }
+
+    #[test]
+    fn test_find_invalid_regex() {
+        let mut search = ResponseSearch {
+            query: "\"id\": (".to_string(),
+            regex: true,
+            ..ResponseSearch::default()
+        };
+        assert_eq!(search.find(TEXT), Err("unclosed group".to_string()));
+
+        search.query = "[a-".to_string();
+        assert!(search.find(TEXT).is_err());
+
+        // The same text is fine when matched literally
+        search.query = "\"id\": (".to_string();
+        search.regex = false;
+        assert_eq!(search.find(TEXT), Ok(Vec::new()));
+        search.query = "\"id\":".to_string();
+        assert_eq!(search.find(TEXT).unwrap(), vec![SearchMatch { line: 2, start: 2, end: 7 }]);
+    }
+
+    #[test]
+    fn test_find_case_toggle() {
+        let text = "Straße STRASSE\nÉTÉ été";
+        let mut search = ResponseSearch {
+            query: "été".to_string(),
+            ..ResponseSearch::default()
+        };
+        assert_eq!(search.find(text).unwrap().len(), 2);
+        search.case_sensitive = true;
+        assert_eq!(
+            search.find(text).unwrap(),
+            vec![SearchMatch { line: 1, start: 6, end: 11 }]
+        );
+
+        search.query = "^n.me$".to_string();
+        search.regex = true;
+        assert!(search.find(TEXT).unwrap().is_empty());
+        search.query = "\"n.me\"".to_string();
+        assert_eq!(search.find(TEXT).unwrap(), vec![SearchMatch { line: 1, start: 10, end: 16 }]);
+        search.case_sensitive = false;
+        assert_eq!(search.find(TEXT).unwrap().len(), 2);
+    }
+}
@@ -1,11 +1,32 @@
 //! App state - pure data structure with no I/O logic
 
-use crate::discovery::WorkspaceProject;
-use crate::messages::ui_events::{AppTab, AuthField, GqlField, InputMode, Panel};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::app::memo::{Memo, Snapshot};
+use crate::append_log::AppendLog;
+use crate::app::search::{Matches, ResponseSearch};
+use crate::discovery::validate::Violation;
+use crate::discovery::{DiscoveredEndpoint, ServerSpec, WorkspaceProject};
+use crate::interop::http_file::HttpFile;
+use crate::formatting::BodyKind;
+use crate::indexed_text::IndexedText;
+use crate::json_filter;
+use crate::json_tree::{self, TreeRow};
+use crate::messages::ui_events::{AppTab, AuthField, GqlField, InputMode, Panel, ResponseView};
+use crate::messages::render::{CollectionRow, ServerRow};
 use crate::messages::RenderState;
-use crate::models::{AuthType, Request, Response};
+use crate::models::{split_unix_url, AuthType, Header, ProxySetting, Request, Response};
+use crate::response_diff::{self, ResponseDiff};
 use crate::storage::Storage;
 
+/// Searched text, query, case sensitivity and regex mode
+type SearchKey = (IndexedText, String, bool, bool);
+
+/// Rows of the JSON tree view, or why the body is not JSON
+pub type TreeRows = Result<Vec<TreeRow>, String>;
+
 /// Direction of WebSocket message
 #[derive(Clone, Debug)]
 pub enum WsDirection {
@@ -23,6 +44,15 @@ pub struct WsLogEntry {
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
+/// A `.http` file opened as a collection, saved back on request
+#[derive(Clone, Debug)]
+pub struct OpenHttpFile {
+    pub path: PathBuf,
+    /// Name of the collection and environment it was opened as
+    pub name: String,
+    pub file: HttpFile,
+}
+
 /// WebSocket connection state
 #[derive(Clone, Debug)]
 pub struct WebSocketState {
@@ -31,7 +61,8 @@ pub struct WebSocketState {
     pub editing_url: bool, // true = editing URL, false = editing message input
     pub connected: bool,
     pub connection_id: Option<u64>,
-    pub messages: Vec<WsLogEntry>,
+    /// Shared with render states; appended through `log`
+    pub messages: AppendLog<WsLogEntry>,
     pub input: String,
     pub cursor_position: usize,
     pub scroll: u16,
@@ -46,7 +77,7 @@ impl Default for WebSocketState {
             editing_url: false,
             connected: false,
             connection_id: None,
-            messages: Vec::new(),
+            messages: AppendLog::default(),
             input: String::new(),
             cursor_position: 0,
             scroll: 0,
@@ -54,6 +85,17 @@ impl Default for WebSocketState {
     }
 }
 
+impl WebSocketState {
+    /// Append an entry to the message log
+    pub fn log(&mut self, direction: WsDirection, content: String) {
+        self.messages.push(WsLogEntry {
+            direction,
+            content,
+            timestamp: chrono::Utc::now(),
+        });
+    }
+}
+
 /// GraphQL state
 #[derive(Clone, Debug)]
 pub struct GraphQLState {
@@ -64,7 +106,7 @@ pub struct GraphQLState {
     pub variables: String,
     pub variables_cursor: usize,
     pub active_field: GqlField,
-    pub response: String,
+    pub response: IndexedText,
     pub response_scroll: u16,
     pub is_loading: bool,
     pub time_ms: u64,
@@ -81,7 +123,7 @@ impl Default for GraphQLState {
             variables: String::from("{}"),
             variables_cursor: 0,
             active_field: GqlField::Query,
-            response: String::new(),
+            response: IndexedText::default(),
             response_scroll: 0,
             is_loading: false,
             time_ms: 0,
@@ -102,13 +144,36 @@ pub struct AppState {
     // UI state
     pub active_panel: Panel,
     pub input_mode: InputMode,
-    pub response_scroll: u16,
+    /// First body line shown in the response panel
+    pub response_scroll: usize,
+    pub response_view: ResponseView,
 
     // HTTP Response
     pub response: Response,
+    /// Format of the last received body
+    pub body_kind: BodyKind,
+    /// The last received body pretty printed, if its format allows
+    pub pretty_body: Option<IndexedText>,
+    /// The body through the request's filter, by body and expression
+    filtered_body: Memo<(IndexedText, String), Result<IndexedText, String>>,
+    /// Copies of the request's editable text shared with render states
+    body_snapshot: Snapshot<str>,
+    headers_snapshot: Snapshot<[Header]>,
+    gql_query_snapshot: Snapshot<str>,
+    gql_variables_snapshot: Snapshot<str>,
+    /// Matches of the response search, by searched text and search
+    search_matches: Memo<SearchKey, Arc<Matches>>,
+    /// The shown body parsed as JSON, by text
+    tree_document: Memo<IndexedText, Arc<Result<serde_json::Value, String>>>,
+    /// Rows of the tree view, by parsed text and folded nodes
+    tree_rows: Memo<(IndexedText, HashSet<String>), Arc<TreeRows>>,
+    /// Show the body as received instead of pretty printed
+    pub response_raw: bool,
     pub is_loading: bool,
     pub next_request_id: u64,
     pub pending_request_id: Option<u64>,
+    /// The pending request as sent, kept for its history entry
+    pub sent_request: Option<Request>,
 
     // Streaming state
     pub streaming_body: String,
@@ -127,15 +192,62 @@ pub struct AppState {
     pub storage: Storage,
 
     // Workspace discovery
-    pub workspace: Option<WorkspaceProject>,
+    pub workspace: Option<Arc<WorkspaceProject>>,
     pub workspace_path_input: String,
     pub selected_endpoint: usize,
+    /// Workspace endpoint the request was loaded from, whose responses
+    /// are validated against the spec
+    pub spec_endpoint: Option<usize>,
+    /// Result of validating the last response; `None` when not validated
+    pub spec_violations: Option<Vec<Violation>>,
+    /// Server picked in the servers popup, ahead of the one linked to
+    /// the environment and the endpoint's and spec's own
+    pub picked_server: Option<ServerSpec>,
+    /// Values picked for server variables, by name
+    pub server_values: HashMap<String, String>,
 
     // Popups
     pub show_help: bool,
     pub show_curl_import: bool,
     pub curl_import_buffer: String,
     pub show_workspace_input: bool,
+    pub show_proxy_input: bool,
+    pub proxy_input: String,
+    pub show_export: bool,
+    /// Index into `CodeTarget::ALL`
+    pub export_target: usize,
+    /// Substitute environment variables into exported code
+    pub export_substitute: bool,
+    pub show_collections: bool,
+    /// Index into the rows of all collections' trees
+    pub selected_collection_row: usize,
+    pub http_file: Option<OpenHttpFile>,
+    /// Index of the editor's request in `http_file`, if it came from there
+    pub http_file_request: Option<usize>,
+    pub search: ResponseSearch,
+    /// JSONPaths of the folded nodes of the tree view
+    pub tree_collapsed: HashSet<String>,
+    /// Index of the selected row of the tree view
+    pub tree_selected: usize,
+    pub show_capture_input: bool,
+    /// Variable name typed in the capture popup
+    pub capture_name: String,
+    /// Value the capture popup stores, taken from the tree selection
+    pub capture_value: String,
+    /// One-off message for the status bar, cleared by the next key
+    pub notice: Option<String>,
+    pub show_history_diff: bool,
+    /// Index of the highlighted history entry in the diff popup
+    pub diff_selected: usize,
+    /// History entry picked as one side of the comparison
+    pub diff_base: Option<usize>,
+    /// The comparison shown, once two entries are picked
+    pub history_diff: Option<Arc<ResponseDiff>>,
+    /// First row of the comparison on screen
+    pub diff_scroll: usize,
+    pub show_servers: bool,
+    /// Index of the highlighted row of the servers popup
+    pub server_row: usize,
 
     // WebSocket state (persists across tab switches)
     pub ws: WebSocketState,
@@ -159,10 +271,23 @@ impl AppState {
             active_panel: Panel::Url,
             input_mode: InputMode::Normal,
             response_scroll: 0,
+            response_view: ResponseView::Body,
             response: Response::default(),
+            body_kind: BodyKind::Text,
+            pretty_body: None,
+            filtered_body: Memo::default(),
+            body_snapshot: Snapshot::default(),
+            headers_snapshot: Snapshot::default(),
+            gql_query_snapshot: Snapshot::default(),
+            gql_variables_snapshot: Snapshot::default(),
+            search_matches: Memo::default(),
+            tree_document: Memo::default(),
+            tree_rows: Memo::default(),
+            response_raw: false,
             is_loading: false,
             next_request_id: 1,
             pending_request_id: None,
+            sent_request: None,
             streaming_body: String::new(),
             bytes_received: 0,
             selected_header: 0,
@@ -172,10 +297,37 @@ impl AppState {
             workspace: None,
             workspace_path_input: String::new(),
             selected_endpoint: 0,
+            spec_endpoint: None,
+            spec_violations: None,
+            picked_server: None,
+            server_values: HashMap::new(),
             show_help: false,
             show_curl_import: false,
             curl_import_buffer: String::new(),
             show_workspace_input: false,
+            show_proxy_input: false,
+            proxy_input: String::new(),
+            show_export: false,
+            export_target: 0,
+            export_substitute: true,
+            show_collections: false,
+            selected_collection_row: 0,
+            http_file: None,
+            http_file_request: None,
+            search: ResponseSearch::default(),
+            tree_collapsed: HashSet::new(),
+            tree_selected: 0,
+            show_capture_input: false,
+            capture_name: String::new(),
+            capture_value: String::new(),
+            notice: None,
+            show_history_diff: false,
+            diff_selected: 0,
+            diff_base: None,
+            history_diff: None,
+            diff_scroll: 0,
+            show_servers: false,
+            server_row: 0,
             ws: WebSocketState::default(),
             gql: GraphQLState::default(),
         }
@@ -202,6 +354,7 @@ impl AppState {
                 },
                 AuthType::None => "",
             },
+            Panel::Response => &self.request.response_filter,
             _ => "",
         }
     }
@@ -222,25 +375,253 @@ impl AppState {
                 }
                 AuthType::None => &mut self.request.url, // fallback
             },
+            Panel::Response => &mut self.request.response_filter,
             _ => &mut self.request.url, // fallback
         }
     }
 
-    /// Convert state to RenderState for UI
-    pub fn to_render_state(&self) -> RenderState {
+    /// Short label for the TLS profile applying to the active tab's URL
+    fn tls_indicator(&self) -> Option<&'static str> {
+        let (request_profile, url) = match self.active_tab {
+            AppTab::Http => (self.request.tls.as_ref(), &self.request.url),
+            AppTab::WebSocket => (None, &self.ws.url),
+            AppTab::GraphQL => (self.request.tls.as_ref(), &self.gql.endpoint),
+        };
+        let profile = self.storage.resolve_tls(request_profile, url)?;
+        if profile.client_cert.is_some() {
+            Some("mTLS")
+        } else if !profile.ca_certs.is_empty() {
+            Some("custom CA")
+        } else {
+            None
+        }
+    }
+
+    /// Connection target of the host override applying to the active tab's URL
+    fn host_override_indicator(&self) -> Option<String> {
+        let url = match self.active_tab {
+            AppTab::Http => &self.request.url,
+            AppTab::WebSocket => return None,
+            AppTab::GraphQL => &self.gql.endpoint,
+        };
+        self.storage
+            .resolve_host_override(&self.request.host_overrides, url)
+            .map(|host_override| host_override.target())
+    }
+
+    /// Rows of every collection's tree, in display order
+    fn collection_rows(&self) -> Vec<CollectionRow> {
+        self.storage
+            .collections
+            .iter()
+            .flat_map(|collection| collection.entries())
+            .map(|entry| CollectionRow {
+                depth: entry.depth,
+                name: entry.name.to_string(),
+                method: entry.request.map(|request| request.method.clone()),
+            })
+            .collect()
+    }
+
+    /// Servers of the highlighted endpoint, given on its path or
+    /// operation, followed by the spec's own
+    pub fn server_candidates(&self) -> Vec<ServerSpec> {
+        let Some(workspace) = &self.workspace else {
+            return Vec::new();
+        };
+        let own = workspace.endpoints.get(self.selected_endpoint).map(|e| e.servers.as_slice()).unwrap_or_default();
+        let mut servers: Vec<ServerSpec> = Vec::new();
+        for server in own.iter().chain(&workspace.servers) {
+            if !servers.iter().any(|s| s.url == server.url) {
+                servers.push(server.clone());
+            }
+        }
+        servers
+    }
+
+    /// Server linked to the active environment with `m`
+    fn linked_server(&self) -> Option<String> {
+        self.storage.current_environment()?.server.clone()
+    }
+
+    /// Base URL an endpoint loads against: the server picked in the
+    /// popup or linked to the environment, then the endpoint's own
+    /// servers, then the spec's
+    pub fn server_base(&self, endpoint: &DiscoveredEndpoint) -> Option<String> {
+        let workspace = self.workspace.as_ref()?;
+        self.picked_server
+            .as_ref()
+            .map(|server| server.resolve(&self.server_values))
+            .or_else(|| self.linked_server())
+            .or_else(|| endpoint.servers.first().map(|server| server.resolve(&self.server_values)))
+            .or_else(|| workspace.servers.first().map(|server| server.resolve(&self.server_values)))
+    }
+
+    /// Base URL of the highlighted endpoint, shown in the panel title
+    fn active_server(&self) -> Option<String> {
+        let endpoint = self.workspace.as_ref()?.endpoints.get(self.selected_endpoint)?;
+        self.server_base(endpoint)
+    }
+
+    /// Server and variable of each row of the servers popup; variables
+    /// are listed under their server
+    pub fn server_row_targets(&self) -> Vec<(usize, Option<usize>)> {
+        self.server_candidates()
+            .iter()
+            .enumerate()
+            .flat_map(|(index, server)| {
+                std::iter::once((index, None)).chain((0..server.variables.len()).map(move |v| (index, Some(v))))
+            })
+            .collect()
+    }
+
+    fn server_rows(&self) -> Vec<ServerRow> {
+        let servers = self.server_candidates();
+        let active = self.active_server();
+        self.server_row_targets()
+            .into_iter()
+            .map(|(index, variable)| {
+                let server = &servers[index];
+                match variable.map(|v| &server.variables[v]) {
+                    None => {
+                        let text = server.resolve(&self.server_values);
+                        ServerRow {
+                            variable: None,
+                            active: active.as_deref() == Some(text.as_str()),
+                            text,
+                        }
+                    }
+                    Some(variable) => ServerRow {
+                        variable: Some(variable.name.clone()),
+                        text: variable.value(&self.server_values).to_string(),
+                        active: variable.choices.len() > 1,
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Format of the shown body; messages written into the response
+    /// panel are plain text
+    pub fn shown_kind(&self) -> BodyKind {
+        match self.response.status_code {
+            Some(_) => self.body_kind,
+            None => BodyKind::Text,
+        }
+    }
+
+    /// The response body as shown: pretty printed unless the raw view
+    /// is on or the format could not be printed
+    pub fn shown_body(&self) -> &IndexedText {
+        match &self.pretty_body {
+            Some(pretty) if self.response.status_code.is_some() && !self.response_raw => pretty,
+            _ => &self.response.body,
+        }
+    }
+
+    /// Matches of the response search in the body as shown, through the
+    /// filter when it applies; found again only when the query, its
+    /// options or the text change
+    pub fn search_matches(&mut self) -> Arc<Matches> {
+        if !self.search.is_active() || self.response_view != ResponseView::Body {
+            return Arc::new(Ok(Vec::new()));
+        }
+        let text = match self.filtered_body() {
+            Some(Ok(filtered)) => filtered,
+            _ => self.shown_body().clone(),
+        };
+        let search = &self.search;
+        let key = (text, search.query.clone(), search.case_sensitive, search.regex);
+        self.search_matches.get(key, |(text, ..)| Arc::new(search.find(text)))
+    }
+
+    /// The response body through the request's filter, if it has one
+    /// and the response came from the server; applied once per body and
+    /// expression
+    pub fn filtered_body(&mut self) -> Option<Result<IndexedText, String>> {
+        let filter = self.request.response_filter.trim();
+        if filter.is_empty() || self.response.status_code.is_none() {
+            return None;
+        }
+        let key = (self.response.body.clone(), filter.to_string());
+        Some(self.filtered_body.get(key, |(body, filter)| {
+            json_filter::apply(filter, body)
+                .map(IndexedText::from)
+                .map_err(|e| format!("{:#}", e))
+        }))
+    }
+
+    /// Text the tree view shows: the filter's output when it applies,
+    /// the raw body otherwise
+    fn tree_text(&mut self) -> IndexedText {
+        match self.filtered_body() {
+            Some(Ok(filtered)) => filtered,
+            _ => self.response.body.clone(),
+        }
+    }
+
+    /// The shown body parsed as JSON, parsed once per text
+    pub fn tree_document(&mut self) -> Arc<Result<serde_json::Value, String>> {
+        let text = self.tree_text();
+        self.tree_document.get(text, |text| {
+            Arc::new(serde_json::from_str(text).map_err(|e| format!("Not a JSON body: {}", e)))
+        })
+    }
+
+    /// Rows of the tree view, built again only when the text or the
+    /// folded nodes change
+    pub fn tree_rows(&mut self) -> Arc<TreeRows> {
+        let document = self.tree_document();
+        let key = (self.tree_text(), self.tree_collapsed.clone());
+        self.tree_rows.get(key, |(_, collapsed)| {
+            Arc::new(match document.as_ref() {
+                Ok(document) => Ok(json_tree::rows(document, collapsed)),
+                Err(e) => Err(e.clone()),
+            })
+        })
+    }
+
+    /// Build the RenderState for the UI, bringing the derived views of
+    /// the response up to date first
+    pub fn render_state(&mut self) -> RenderState {
+        let filtered_body = self.filtered_body();
         RenderState {
             active_tab: self.active_tab,
             method: self.request.method.clone(),
             url: self.request.url.clone(),
-            body: self.request.body.clone(),
-            headers: self.request.headers.clone(),
+            body: self.body_snapshot.of(self.request.body.as_str()),
+            headers: self.headers_snapshot.of(self.request.headers.as_slice()),
             auth: self.request.auth.clone(),
             ignore_ssl_errors: self.request.ignore_ssl_errors,
+            proxy_indicator: match self.storage.resolve_proxy(&self.request) {
+                ProxySetting::System => None,
+                ProxySetting::Direct => Some("direct".to_string()),
+                ProxySetting::Manual(proxy) => Some(proxy.url),
+            },
+            tls_indicator: self.tls_indicator(),
+            unix_socket: split_unix_url(&self.request.url)
+                .map(|(socket, _)| socket.to_string())
+                .or_else(|| self.request.unix_socket.clone()),
+            host_override: self.host_override_indicator(),
             active_panel: self.active_panel,
             input_mode: self.input_mode,
             cursor_position: self.cursor_position,
             response: self.response.clone(),
+            body_kind: self.shown_kind(),
+            pretty_body: if self.response_raw || self.response.status_code.is_none() {
+                None
+            } else {
+                self.pretty_body.clone()
+            },
+            response_raw: self.response_raw,
+            response_filter: self.request.response_filter.clone(),
+            search: self.search.clone(),
+            search_matches: self.search_matches(),
+            filtered_body,
+            tree: (self.response_view == ResponseView::Tree).then(|| self.tree_rows()),
+            tree_selected: self.tree_selected,
             response_scroll: self.response_scroll,
+            response_view: self.response_view,
             is_loading: self.is_loading,
             selected_header: self.selected_header,
             auth_field: self.auth_field,
@@ -248,10 +629,49 @@ impl AppState {
             workspace: self.workspace.clone(),
             workspace_path_input: self.workspace_path_input.clone(),
             selected_endpoint: self.selected_endpoint,
+            spec_violations: self.spec_violations.clone(),
             show_help: self.show_help,
             show_curl_import: self.show_curl_import,
             curl_import_buffer: self.curl_import_buffer.clone(),
             show_workspace_input: self.show_workspace_input,
+            show_proxy_input: self.show_proxy_input,
+            proxy_input: self.proxy_input.clone(),
+            show_export: self.show_export,
+            export_target: self.export_target,
+            export_substitute: self.export_substitute,
+            show_capture_input: self.show_capture_input,
+            capture_name: self.capture_name.clone(),
+            capture_value: self.capture_value.clone(),
+            notice: self.notice.clone(),
+            show_history_diff: self.show_history_diff,
+            history_rows: if self.show_history_diff {
+                self.storage.history.iter().map(response_diff::entry_label).collect()
+            } else {
+                Vec::new()
+            },
+            diff_selected: self.diff_selected,
+            diff_base: self.diff_base,
+            history_diff: self.history_diff.clone(),
+            diff_scroll: self.diff_scroll,
+            show_servers: self.show_servers,
+            server_rows: if self.show_servers {
+                self.server_rows()
+            } else {
+                Vec::new()
+            },
+            server_row: self.server_row,
+            active_server: self.active_server(),
+            show_collections: self.show_collections,
+            collection_rows: if self.show_collections {
+                self.collection_rows()
+            } else {
+                Vec::new()
+            },
+            selected_collection_row: self.selected_collection_row,
+            environment: self
+                .storage
+                .current_environment()
+                .map(|env| env.name.clone()),
             ws_url: self.ws.url.clone(),
             ws_url_cursor: self.ws.url_cursor,
             ws_editing_url: self.ws.editing_url,
@@ -262,9 +682,9 @@ impl AppState {
             ws_scroll: self.ws.scroll,
             gql_endpoint: self.gql.endpoint.clone(),
             gql_endpoint_cursor: self.gql.endpoint_cursor,
-            gql_query: self.gql.query.clone(),
+            gql_query: self.gql_query_snapshot.of(self.gql.query.as_str()),
             gql_query_cursor: self.gql.query_cursor,
-            gql_variables: self.gql.variables.clone(),
+            gql_variables: self.gql_variables_snapshot.of(self.gql.variables.as_str()),
             gql_variables_cursor: self.gql.variables_cursor,
             gql_active_field: self.gql.active_field,
             gql_response: self.gql.response.clone(),
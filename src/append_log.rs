@@ -0,0 +1,89 @@
+//! Append-only log shared with render states
+//!
+//! Entries are kept in segments of `SEGMENT_LEN`. Full segments are
+//! frozen and shared by every clone, so a snapshot never copies them;
+//! appending copies at most the segment being filled, and only while a
+//! snapshot still holds it.
+
+use std::sync::Arc;
+
+const SEGMENT_LEN: usize = 64;
+
+#[derive(Clone, Debug)]
+pub struct AppendLog<T> {
+    /// Full segments, oldest first
+    frozen: Arc<Vec<Arc<[T]>>>,
+    /// The segment being filled
+    tail: Arc<Vec<T>>,
+}
+
+impl<T> Default for AppendLog<T> {
+    fn default() -> Self {
+        AppendLog {
+            frozen: Arc::default(),
+            tail: Arc::default(),
+        }
+    }
+}
+
+impl<T: Clone> AppendLog<T> {
+    pub fn push(&mut self, entry: T) {
+        let tail = Arc::make_mut(&mut self.tail);
+        tail.push(entry);
+        if tail.len() == SEGMENT_LEN {
+            let full: Arc<[T]> = std::mem::take(tail).into();
+            Arc::make_mut(&mut self.frozen).push(full);
+        }
+    }
+}
+
+impl<T> AppendLog<T> {
+    pub fn len(&self) -> usize {
+        self.frozen.len() * SEGMENT_LEN + self.tail.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The entries from `start` on, without walking the ones before it
+    pub fn iter_from(&self, start: usize) -> impl Iterator<Item = &T> {
+        let segment = start / SEGMENT_LEN;
+        let frozen = self.frozen.get(segment..).unwrap_or_default();
+        let skip = if segment <= self.frozen.len() { start % SEGMENT_LEN } else { self.tail.len() };
+        frozen
+            .iter()
+            .flat_map(|segment| segment.iter())
+            .chain(self.tail.iter())
+            .skip(skip)
+    }
+
+    /// Whether both logs hold the same entries without having copied any
+    #[allow(dead_code)] // Checked by render state tests
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.frozen, &other.frozen) && Arc::ptr_eq(&self.tail, &other.tail)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshots_share_full_segments() {
+        let mut log = AppendLog::default();
+        for i in 0..SEGMENT_LEN + 3 {
+            log.push(i);
+        }
+        let snapshot = log.clone();
+        log.push(999);
+
+        assert_eq!(snapshot.len(), SEGMENT_LEN + 3);
+        assert_eq!(log.len(), SEGMENT_LEN + 4);
+        assert!(Arc::ptr_eq(&snapshot.frozen[0], &log.frozen[0]));
+        assert_eq!(log.iter_from(SEGMENT_LEN + 2).copied().collect::<Vec<_>>(), vec![SEGMENT_LEN + 2, 999]);
+        assert_eq!(log.iter_from(1).next(), Some(&1));
+        assert_eq!(log.iter_from(10_000).next(), None);
+        assert_eq!(snapshot.iter_from(0).count(), SEGMENT_LEN + 3);
+    }
+}
@@ -0,0 +1,38 @@
+//! System clipboard access through the platform's command-line tools
+
+use anyhow::{anyhow, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Clipboard tools in order of preference, with their arguments
+const TOOLS: &[(&str, &[&str])] = &[
+    ("pbcopy", &[]),
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+    ("clip.exe", &[]),
+];
+
+/// Copy text to the clipboard, returning the name of the tool that was used
+pub fn copy(text: &str) -> Result<&'static str> {
+    for (tool, args) in TOOLS {
+        let Ok(mut child) = Command::new(tool)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        if child.wait()?.success() {
+            return Ok(tool);
+        }
+    }
+    Err(anyhow!(
+        "no clipboard tool found (install wl-copy, xclip or xsel)"
+    ))
+}
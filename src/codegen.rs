@@ -0,0 +1,619 @@
+//! Code generation - the current request as a snippet for other HTTP clients
+
+use base64::Engine;
+
+use crate::curl;
+use crate::models::{AuthType, ClientCertificate, ProxySetting, Request};
+
+/// Languages and tools a request can be exported to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CodeTarget {
+    Curl,
+    Httpie,
+    Wget,
+    PythonRequests,
+    JsFetch,
+    NodeAxios,
+    GoNetHttp,
+    RustReqwest,
+    PowerShell,
+}
+
+impl CodeTarget {
+    pub const ALL: [CodeTarget; 9] = [
+        CodeTarget::Curl,
+        CodeTarget::Httpie,
+        CodeTarget::Wget,
+        CodeTarget::PythonRequests,
+        CodeTarget::JsFetch,
+        CodeTarget::NodeAxios,
+        CodeTarget::GoNetHttp,
+        CodeTarget::RustReqwest,
+        CodeTarget::PowerShell,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CodeTarget::Curl => "cURL",
+            CodeTarget::Httpie => "HTTPie",
+            CodeTarget::Wget => "wget",
+            CodeTarget::PythonRequests => "Python (requests)",
+            CodeTarget::JsFetch => "JavaScript (fetch)",
+            CodeTarget::NodeAxios => "Node.js (axios)",
+            CodeTarget::GoNetHttp => "Go (net/http)",
+            CodeTarget::RustReqwest => "Rust (reqwest)",
+            CodeTarget::PowerShell => "PowerShell (Invoke-RestMethod)",
+        }
+    }
+
+    /// File extension used when the snippet is saved
+    pub fn extension(&self) -> &'static str {
+        match self {
+            CodeTarget::Curl | CodeTarget::Httpie | CodeTarget::Wget => "sh",
+            CodeTarget::PythonRequests => "py",
+            CodeTarget::JsFetch | CodeTarget::NodeAxios => "js",
+            CodeTarget::GoNetHttp => "go",
+            CodeTarget::RustReqwest => "rs",
+            CodeTarget::PowerShell => "ps1",
+        }
+    }
+
+    /// Line comment marker of the snippet's language
+    fn comment(&self) -> &'static str {
+        match self {
+            CodeTarget::JsFetch | CodeTarget::NodeAxios | CodeTarget::GoNetHttp | CodeTarget::RustReqwest => "//",
+            _ => "#",
+        }
+    }
+
+    /// Generate the snippet for a request. Connection settings only
+    /// cURL can express are listed in a comment above the code.
+    pub fn generate(&self, request: &Request) -> String {
+        if *self == CodeTarget::Curl {
+            return curl::to_curl(request);
+        }
+        let mut out = String::new();
+        let settings = connection_settings(request);
+        if !settings.is_empty() {
+            out.push_str(&format!("{} Not expressed in this snippet, set up separately:\n", self.comment()));
+            for setting in settings {
+                out.push_str(&format!("{}   - {}\n", self.comment(), setting));
+            }
+            out.push('\n');
+        }
+        out.push_str(&self.code(request));
+        out
+    }
+
+    fn code(&self, request: &Request) -> String {
+        match self {
+            CodeTarget::Curl => curl::to_curl(request),
+            CodeTarget::Httpie => httpie(request),
+            CodeTarget::Wget => wget(request),
+            CodeTarget::PythonRequests => python_requests(request),
+            CodeTarget::JsFetch => js_fetch(request),
+            CodeTarget::NodeAxios => node_axios(request),
+            CodeTarget::GoNetHttp => go_net_http(request),
+            CodeTarget::RustReqwest => rust_reqwest(request),
+            CodeTarget::PowerShell => powershell(request),
+        }
+    }
+}
+
+/// Proxy, Unix socket, host override and TLS settings the request is
+/// sent with
+fn connection_settings(request: &Request) -> Vec<String> {
+    let mut settings = Vec::new();
+    if let Some(socket) = &request.unix_socket {
+        settings.push(format!("Unix socket {}", socket));
+    } else {
+        match &request.proxy {
+            Some(ProxySetting::Manual(proxy)) => settings.push(format!("proxy {}", proxy.url)),
+            Some(ProxySetting::Direct) => settings.push("no proxy, even if HTTP(S)_PROXY is set".to_string()),
+            Some(ProxySetting::System) | None => {}
+        }
+    }
+    for host_override in &request.host_overrides {
+        let target = [
+            host_override.address.clone(),
+            host_override.connect_port.map(|port| port.to_string()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(":");
+        let host = match host_override.port {
+            Some(port) => format!("{}:{}", host_override.host, port),
+            None => host_override.host.clone(),
+        };
+        settings.push(format!("connect to {} for {}", target, host));
+    }
+    if let Some(tls) = &request.tls {
+        for ca in &tls.ca_certs {
+            settings.push(format!("CA certificates {}", ca.display()));
+        }
+        match &tls.client_cert {
+            Some(ClientCertificate::Pem { cert, key }) => {
+                settings.push(format!("client certificate {} with key {}", cert.display(), key.display()))
+            }
+            Some(ClientCertificate::Pkcs12 { path, .. }) => {
+                settings.push(format!("client certificate {}", path.display()))
+            }
+            None => {}
+        }
+    }
+    settings
+}
+
+/// Enabled headers as (name, value) pairs
+fn headers(request: &Request) -> Vec<(&str, &str)> {
+    request
+        .headers
+        .iter()
+        .filter(|h| h.enabled)
+        .map(|h| (h.key.as_str(), h.value.as_str()))
+        .collect()
+}
+
+/// Enabled headers plus the `Authorization` header for the request's auth,
+/// for targets without native auth support
+fn headers_with_auth(request: &Request) -> Vec<(String, String)> {
+    let mut all: Vec<(String, String)> = headers(request)
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    match &request.auth {
+        AuthType::Bearer(token) => {
+            all.push(("Authorization".to_string(), format!("Bearer {}", token)));
+        }
+        AuthType::Basic { username, password } => {
+            let encoded = base64::engine::general_purpose::STANDARD
+                .encode(format!("{}:{}", username, password));
+            all.push(("Authorization".to_string(), format!("Basic {}", encoded)));
+        }
+        AuthType::None => {}
+    }
+    all
+}
+
+/// The body as it is sent: only for methods that carry one
+fn body(request: &Request) -> Option<&str> {
+    Some(request.body.as_str()).filter(|body| request.method.has_body() && !body.is_empty())
+}
+
+/// Double-quoted string literal, valid in Python, JavaScript and Go
+fn quoted(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_default()
+}
+
+/// Single-quoted PowerShell string
+fn ps_quoted(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+fn httpie(request: &Request) -> String {
+    let mut parts = vec!["http".to_string()];
+    if request.ignore_ssl_errors {
+        parts.push("--verify=no".to_string());
+    }
+    match &request.auth {
+        AuthType::Bearer(token) => {
+            parts.push(format!("-A bearer -a {}", curl::quote(token)));
+        }
+        AuthType::Basic { username, password } => {
+            parts.push(format!("-a {}", curl::quote(&format!("{}:{}", username, password))));
+        }
+        AuthType::None => {}
+    }
+    if let Some(body) = body(request) {
+        parts.push(format!("--raw {}", curl::quote(body)));
+    }
+    parts.push(format!("{} {}", request.method.as_str(), curl::quote(&request.url)));
+    for (key, value) in headers(request) {
+        parts.push(curl::quote(&format!("{}:{}", key, value)));
+    }
+    parts.join(" \\\n  ")
+}
+
+fn wget(request: &Request) -> String {
+    let mut parts = vec![
+        "wget --quiet --output-document=-".to_string(),
+        format!("--method={}", request.method.as_str()),
+    ];
+    let auth_headers = match &request.auth {
+        AuthType::Basic { username, password } => {
+            parts.push(format!("--user={}", curl::quote(username)));
+            parts.push(format!("--password={}", curl::quote(password)));
+            parts.push("--auth-no-challenge".to_string());
+            headers(request)
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        }
+        _ => headers_with_auth(request),
+    };
+    for (key, value) in auth_headers {
+        parts.push(format!("--header={}", curl::quote(&format!("{}: {}", key, value))));
+    }
+    if let Some(body) = body(request) {
+        parts.push(format!("--body-data={}", curl::quote(body)));
+    }
+    if request.ignore_ssl_errors {
+        parts.push("--no-check-certificate".to_string());
+    }
+    parts.push(curl::quote(&request.url));
+    parts.join(" \\\n  ")
+}
+
+fn python_requests(request: &Request) -> String {
+    let mut out = String::from("import requests\n\n");
+    out.push_str(&format!("url = {}\n", quoted(&request.url)));
+
+    let headers = headers(request);
+    let mut args = vec![quoted(request.method.as_str()), "url".to_string()];
+    if !headers.is_empty() {
+        out.push_str("headers = {\n");
+        for (key, value) in &headers {
+            out.push_str(&format!("    {}: {},\n", quoted(key), quoted(value)));
+        }
+        out.push_str("}\n");
+        args.push("headers=headers".to_string());
+    }
+    match &request.auth {
+        AuthType::Bearer(token) => {
+            // requests has no bearer helper; add the header
+            if headers.is_empty() {
+                out.push_str("headers = {}\n");
+                args.push("headers=headers".to_string());
+            }
+            out.push_str(&format!(
+                "headers[\"Authorization\"] = {}\n",
+                quoted(&format!("Bearer {}", token))
+            ));
+        }
+        AuthType::Basic { username, password } => {
+            args.push(format!("auth=({}, {})", quoted(username), quoted(password)));
+        }
+        AuthType::None => {}
+    }
+    if let Some(body) = body(request) {
+        out.push_str(&format!("data = {}\n", quoted(body)));
+        args.push("data=data".to_string());
+    }
+    if request.ignore_ssl_errors {
+        args.push("verify=False".to_string());
+    }
+
+    out.push_str("\nresponse = requests.request(\n");
+    for arg in args {
+        out.push_str(&format!("    {},\n", arg));
+    }
+    out.push_str(")\nprint(response.status_code)\nprint(response.text)\n");
+    out
+}
+
+fn js_fetch(request: &Request) -> String {
+    let mut out = String::new();
+    if request.ignore_ssl_errors {
+        out.push_str(
+            "// fetch cannot skip certificate checks; in Node.js run with NODE_TLS_REJECT_UNAUTHORIZED=0\n",
+        );
+    }
+    out.push_str(&format!("fetch({}, {{\n", quoted(&request.url)));
+    out.push_str(&format!("  method: {},\n", quoted(request.method.as_str())));
+    let headers = headers_with_auth(request);
+    if !headers.is_empty() {
+        out.push_str("  headers: {\n");
+        for (key, value) in &headers {
+            out.push_str(&format!("    {}: {},\n", quoted(key), quoted(value)));
+        }
+        out.push_str("  },\n");
+    }
+    if let Some(body) = body(request) {
+        out.push_str(&format!("  body: {},\n", quoted(body)));
+    }
+    out.push_str("})\n");
+    out.push_str("  .then((response) => response.text())\n");
+    out.push_str("  .then((body) => console.log(body));\n");
+    out
+}
+
+fn node_axios(request: &Request) -> String {
+    let mut out = String::from("const axios = require(\"axios\");\n");
+    if request.ignore_ssl_errors {
+        out.push_str("const https = require(\"https\");\n");
+    }
+    out.push_str("\naxios\n  .request({\n");
+    out.push_str(&format!(
+        "    method: {},\n",
+        quoted(&request.method.as_str().to_lowercase())
+    ));
+    out.push_str(&format!("    url: {},\n", quoted(&request.url)));
+
+    let mut headers: Vec<(String, String)> = headers(request)
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    if let AuthType::Bearer(token) = &request.auth {
+        headers.push(("Authorization".to_string(), format!("Bearer {}", token)));
+    }
+    if !headers.is_empty() {
+        out.push_str("    headers: {\n");
+        for (key, value) in &headers {
+            out.push_str(&format!("      {}: {},\n", quoted(key), quoted(value)));
+        }
+        out.push_str("    },\n");
+    }
+    if let Some(body) = body(request) {
+        out.push_str(&format!("    data: {},\n", quoted(body)));
+    }
+    if let AuthType::Basic { username, password } = &request.auth {
+        out.push_str(&format!(
+            "    auth: {{ username: {}, password: {} }},\n",
+            quoted(username),
+            quoted(password)
+        ));
+    }
+    if request.ignore_ssl_errors {
+        out.push_str("    httpsAgent: new https.Agent({ rejectUnauthorized: false }),\n");
+    }
+    out.push_str("  })\n");
+    out.push_str("  .then((response) => {\n");
+    out.push_str("    console.log(response.status);\n");
+    out.push_str("    console.log(response.data);\n");
+    out.push_str("  });\n");
+    out
+}
+
+fn go_net_http(request: &Request) -> String {
+    let body = body(request);
+
+    let mut imports = Vec::new();
+    if request.ignore_ssl_errors {
+        imports.push("crypto/tls");
+    }
+    imports.extend(["fmt", "io", "net/http"]);
+    if body.is_some() {
+        imports.push("strings");
+    }
+
+    let mut out = String::from("package main\n\nimport (\n");
+    for import in imports {
+        out.push_str(&format!("\t\"{}\"\n", import));
+    }
+    out.push_str(")\n\nfunc main() {\n");
+
+    let body_arg = match body {
+        Some(body) => {
+            out.push_str(&format!("\tbody := strings.NewReader({})\n", quoted(body)));
+            "body"
+        }
+        None => "nil",
+    };
+    out.push_str(&format!(
+        "\treq, err := http.NewRequest({}, {}, {})\n",
+        quoted(request.method.as_str()),
+        quoted(&request.url),
+        body_arg
+    ));
+    out.push_str("\tif err != nil {\n\t\tpanic(err)\n\t}\n");
+    for (key, value) in headers(request) {
+        out.push_str(&format!("\treq.Header.Add({}, {})\n", quoted(key), quoted(value)));
+    }
+    match &request.auth {
+        AuthType::Bearer(token) => out.push_str(&format!(
+            "\treq.Header.Set(\"Authorization\", {})\n",
+            quoted(&format!("Bearer {}", token))
+        )),
+        AuthType::Basic { username, password } => out.push_str(&format!(
+            "\treq.SetBasicAuth({}, {})\n",
+            quoted(username),
+            quoted(password)
+        )),
+        AuthType::None => {}
+    }
+
+    out.push('\n');
+    if request.ignore_ssl_errors {
+        out.push_str("\tclient := &http.Client{\n");
+        out.push_str("\t\tTransport: &http.Transport{\n");
+        out.push_str("\t\t\tTLSClientConfig: &tls.Config{InsecureSkipVerify: true},\n");
+        out.push_str("\t\t},\n\t}\n");
+    } else {
+        out.push_str("\tclient := &http.Client{}\n");
+    }
+    out.push_str("\tresp, err := client.Do(req)\n");
+    out.push_str("\tif err != nil {\n\t\tpanic(err)\n\t}\n");
+    out.push_str("\tdefer resp.Body.Close()\n\n");
+    out.push_str("\tdata, err := io.ReadAll(resp.Body)\n");
+    out.push_str("\tif err != nil {\n\t\tpanic(err)\n\t}\n");
+    out.push_str("\tfmt.Println(resp.Status)\n");
+    out.push_str("\tfmt.Println(string(data))\n}\n");
+    out
+}
+
+fn rust_reqwest(request: &Request) -> String {
+    let mut out = String::from("#[tokio::main]\nasync fn main() -> Result<(), reqwest::Error> {\n");
+    if request.ignore_ssl_errors {
+        out.push_str("    let client = reqwest::Client::builder()\n");
+        out.push_str("        .danger_accept_invalid_certs(true)\n");
+        out.push_str("        .build()?;\n");
+    } else {
+        out.push_str("    let client = reqwest::Client::new();\n");
+    }
+    out.push_str("    let response = client\n");
+    out.push_str(&format!(
+        "        .request(reqwest::Method::{}, {:?})\n",
+        request.method.as_str(),
+        request.url
+    ));
+    for (key, value) in headers(request) {
+        out.push_str(&format!("        .header({:?}, {:?})\n", key, value));
+    }
+    match &request.auth {
+        AuthType::Bearer(token) => out.push_str(&format!("        .bearer_auth({:?})\n", token)),
+        AuthType::Basic { username, password } => out.push_str(&format!(
+            "        .basic_auth({:?}, Some({:?}))\n",
+            username, password
+        )),
+        AuthType::None => {}
+    }
+    if let Some(body) = body(request) {
+        out.push_str(&format!("        .body({:?})\n", body));
+    }
+    out.push_str("        .send()\n        .await?;\n\n");
+    out.push_str("    println!(\"{}\", response.status());\n");
+    out.push_str("    println!(\"{}\", response.text().await?);\n");
+    out.push_str("    Ok(())\n}\n");
+    out
+}
+
+fn powershell(request: &Request) -> String {
+    let mut out = String::new();
+    let mut args = vec![
+        format!("-Uri {}", ps_quoted(&request.url)),
+        format!("-Method {}", request.method.as_str()),
+    ];
+    let headers = headers_with_auth(request);
+    if !headers.is_empty() {
+        out.push_str("$headers = @{\n");
+        for (key, value) in &headers {
+            out.push_str(&format!("    {} = {}\n", ps_quoted(key), ps_quoted(value)));
+        }
+        out.push_str("}\n");
+        args.push("-Headers $headers".to_string());
+    }
+    if let Some(body) = body(request) {
+        out.push_str(&format!("$body = {}\n", ps_quoted(body)));
+        args.push("-Body $body".to_string());
+    }
+    if request.ignore_ssl_errors {
+        args.push("-SkipCertificateCheck".to_string());
+    }
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out.push_str(&format!("$response = Invoke-RestMethod {}\n", args.join(" ")));
+    out.push_str("$response\n");
+    out
+}
+
+/// Default file name for a saved snippet, derived from the request name
+pub fn file_name(request: &Request, target: CodeTarget) -> String {
+    format!("{}.{}", slug(&request.name, "request"), target.extension())
+}
+
+/// Lowercase ASCII words of `name` joined by dashes, `fallback` if none
+pub(crate) fn slug(name: &str, fallback: &str) -> String {
+    let slug: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    let slug = slug
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        fallback.to_string()
+    } else {
+        slug
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Header, HttpMethod};
+
+    fn sample() -> Request {
+        Request {
+            name: "Create user".to_string(),
+            method: HttpMethod::POST,
+            url: "https://api.example.com/users".to_string(),
+            headers: vec![Header::new("Content-Type", "application/json")],
+            body: r#"{"name":"O'Brien"}"#.to_string(),
+            auth: AuthType::Bearer("t0k".to_string()),
+            ..Request::default()
+        }
+    }
+
+    #[test]
+    fn test_every_target_includes_request_parts() {
+        let mut request = sample();
+        request.method = HttpMethod::PUT;
+        for target in CodeTarget::ALL {
+            let code = target.generate(&request);
+            assert!(code.to_uppercase().contains("PUT"), "{:?}", target);
+            assert!(code.contains("https://api.example.com/users"), "{:?}", target);
+            assert!(code.contains("Content-Type"), "{:?}", target);
+            assert!(code.contains("application/json"), "{:?}", target);
+            assert!(code.contains("t0k"), "{:?}", target);
+            assert!(code.contains("name") && code.contains("Brien"), "{:?}", target);
+            assert!(!code.contains("Not expressed"), "{:?}", target);
+        }
+    }
+
+    #[test]
+    fn test_connection_settings_are_listed() {
+        use crate::models::{HostOverride, ProxyConfig, TlsProfile};
+        use std::path::PathBuf;
+
+        let mut request = sample();
+        request.proxy = Some(ProxySetting::Manual(ProxyConfig::new("http://proxy.corp:3128")));
+        request.host_overrides = vec![HostOverride {
+            host: "api.example.com".to_string(),
+            port: Some(443),
+            address: Some("10.0.0.5".to_string()),
+            connect_port: None,
+        }];
+        request.tls = Some(TlsProfile {
+            hosts: Vec::new(),
+            ca_certs: vec![PathBuf::from("corp-ca.pem")],
+            client_cert: Some(ClientCertificate::Pkcs12 { path: PathBuf::from("me.p12"), password: "pw".to_string() }),
+        });
+        for target in CodeTarget::ALL {
+            let code = target.generate(&request);
+            if target == CodeTarget::Curl {
+                assert!(!code.contains("Not expressed"));
+                continue;
+            }
+            let marker = target.comment();
+            for setting in [
+                "proxy http://proxy.corp:3128",
+                "connect to 10.0.0.5 for api.example.com:443",
+                "CA certificates corp-ca.pem",
+                "client certificate me.p12",
+            ] {
+                assert!(code.contains(&format!("{}   - {}\n", marker, setting)), "{:?}: {}", target, setting);
+            }
+            assert!(!code.contains("pw"), "{:?}", target);
+        }
+
+        request.unix_socket = Some("/var/run/docker.sock".to_string());
+        let code = CodeTarget::PythonRequests.generate(&request);
+        assert!(code.contains("#   - Unix socket /var/run/docker.sock\n"));
+        assert!(!code.contains("proxy.corp"));
+    }
+
+    #[test]
+    fn test_string_escaping() {
+        let request = sample();
+        assert!(python_requests(&request).contains(r#"data = "{\"name\":\"O'Brien\"}""#));
+        assert!(powershell(&request).contains(r#"$body = '{"name":"O''Brien"}'"#));
+        assert!(rust_reqwest(&request).contains(r#".body("{\"name\":\"O'Brien\"}")"#));
+        assert!(httpie(&request).contains(r#"--raw '{"name":"O'\''Brien"}'"#));
+    }
+
+    #[test]
+    fn test_go_imports_follow_request() {
+        let mut request = sample();
+        request.method = HttpMethod::GET;
+        request.ignore_ssl_errors = true;
+        let code = go_net_http(&request);
+        assert!(code.contains("\"crypto/tls\""));
+        assert!(!code.contains("\"strings\""));
+        assert!(code.contains("http.NewRequest(\"GET\", \"https://api.example.com/users\", nil)"));
+        assert_eq!(file_name(&request, CodeTarget::PythonRequests), "create-user.py");
+    }
+}
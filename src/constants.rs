@@ -8,6 +8,16 @@ pub const DEFAULT_HTTP_URL: &str = "https://httpbin.org/get";
 /// Default URL for WebSocket connections
 pub const DEFAULT_WS_URL: &str = "wss://echo.websocket.org";
 
+/// Certificates expiring within this many days are flagged in the response
+pub const CERT_EXPIRY_WARNING_DAYS: i64 = 30;
+
+/// Bytes of a body still streaming that are shown; copying all of it on
+/// every chunk would make large downloads quadratic
+pub const STREAM_PREVIEW_BYTES: usize = 64 * 1024;
+
+/// Spec violations listed under the response before the rest are counted
+pub const SPEC_VIOLATION_LINES: usize = 5;
+
 /// Application name
 #[allow(dead_code)]
 pub const APP_NAME: &str = "Freeman TUI";
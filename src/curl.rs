@@ -1,104 +1,613 @@
-use crate::models::{AuthType, Header, HttpMethod, Request};
-use anyhow::{anyhow, Result};
+use crate::models::{
+    AuthType, ClientCertificate, Header, HostOverride, HttpMethod, ProxyConfig, ProxySetting,
+    Request, TlsProfile,
+};
+use anyhow::{anyhow, Context, Result};
+use base64::Engine;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::str::Chars;
+
+/// Boundary of multipart bodies built from `-F` fields
+pub(crate) const FORM_BOUNDARY: &str = "------------------------FreemanFormBoundary";
+
+/// Options understood by the parser: short form, long form and whether a
+/// value follows. Unsupported options that take a value are listed too so
+/// their value is not mistaken for the URL.
+const OPTIONS: &[(Option<char>, &str, bool)] = &[
+    (Some('X'), "--request", true),
+    (Some('H'), "--header", true),
+    (Some('d'), "--data", true),
+    (None, "--data-ascii", true),
+    (None, "--data-binary", true),
+    (None, "--data-raw", true),
+    (None, "--data-urlencode", true),
+    (None, "--json", true),
+    (Some('F'), "--form", true),
+    (None, "--form-string", true),
+    (Some('G'), "--get", false),
+    (Some('I'), "--head", false),
+    (None, "--url", true),
+    (Some('u'), "--user", true),
+    (None, "--oauth2-bearer", true),
+    (Some('A'), "--user-agent", true),
+    (Some('e'), "--referer", true),
+    (Some('b'), "--cookie", true),
+    (Some('k'), "--insecure", false),
+    (None, "--cacert", true),
+    (Some('E'), "--cert", true),
+    (None, "--cert-type", true),
+    (None, "--key", true),
+    (None, "--pass", true),
+    (Some('x'), "--proxy", true),
+    (None, "--socks5", true),
+    (None, "--socks5-hostname", true),
+    (Some('U'), "--proxy-user", true),
+    (None, "--noproxy", true),
+    (None, "--resolve", true),
+    (None, "--connect-to", true),
+    (None, "--unix-socket", true),
+    // Output and transfer details that do not change the request
+    (None, "--compressed", false),
+    (Some('s'), "--silent", false),
+    (Some('S'), "--show-error", false),
+    (Some('v'), "--verbose", false),
+    (Some('L'), "--location", false),
+    (Some('i'), "--include", false),
+    (Some('f'), "--fail", false),
+    (Some('g'), "--globoff", false),
+    (Some('N'), "--no-buffer", false),
+    (Some('#'), "--progress-bar", false),
+    (None, "--http1.1", false),
+    (None, "--http2", false),
+    (None, "--basic", false),
+    // Unsupported
+    (Some('o'), "--output", true),
+    (Some('w'), "--write-out", true),
+    (Some('m'), "--max-time", true),
+    (None, "--connect-timeout", true),
+    (None, "--retry", true),
+    (None, "--max-redirs", true),
+    (Some('c'), "--cookie-jar", true),
+    (Some('T'), "--upload-file", true),
+    (Some('r'), "--range", true),
+    (Some('D'), "--dump-header", true),
+    (Some('K'), "--config", true),
+    (None, "--capath", true),
+    (None, "--limit-rate", true),
+    (None, "--interface", true),
+];
+
+/// Options that have no effect on the request we build
+const NO_EFFECT: &[&str] = &[
+    "--compressed",
+    "--silent",
+    "--show-error",
+    "--verbose",
+    "--location",
+    "--include",
+    "--fail",
+    "--globoff",
+    "--no-buffer",
+    "--progress-bar",
+    "--http1.1",
+    "--http2",
+    "--basic",
+];
+
+/// A request imported from a cURL command
+#[derive(Clone, Debug)]
+pub struct CurlImport {
+    pub request: Request,
+    /// Options (with their values) that could not be represented
+    pub unsupported: Vec<String>,
+}
 
 /// Parse a cURL command into a Request
+#[allow(dead_code)] // Library API; the app uses import_curl to report dropped options
 pub fn parse_curl(input: &str) -> Result<Request> {
-    let mut request = Request::default();
-    
+    import_curl(input).map(|import| import.request)
+}
+
+/// Parse a cURL command, also reporting the options that were dropped
+pub fn import_curl(input: &str) -> Result<CurlImport> {
+    let mut request = Request {
+        headers: Vec::new(),
+        ..Request::default()
+    };
+    let mut unsupported = Vec::new();
+
     // Remove line continuations and normalize
     let normalized = input
         .replace("\\\n", " ")
         .replace("\\\r\n", " ");
-    
+
     let mut tokens = tokenize(&normalized)?;
-    
+
     // Skip 'curl' command if present
     if tokens.first().map(|s| s.as_str()) == Some("curl") {
         tokens.remove(0);
     }
-    
-    let mut i = 0;
-    while i < tokens.len() {
-        let token = &tokens[i];
-        
-        match token.as_str() {
-            "-X" | "--request" => {
-                if i + 1 < tokens.len() {
-                    request.method = parse_method(&tokens[i + 1])?;
-                    i += 1;
+
+    let mut url: Option<String> = None;
+    let mut method: Option<HttpMethod> = None;
+    let mut head = false;
+    let mut get = false;
+    let mut data: Vec<String> = Vec::new();
+    let mut json: Vec<String> = Vec::new();
+    let mut form: Vec<String> = Vec::new();
+    let mut cookies: Vec<String> = Vec::new();
+    let mut proxy: Option<ProxyConfig> = None;
+    let mut proxy_user: Option<String> = None;
+    let mut no_proxy: Option<String> = None;
+    let mut ca_certs: Vec<PathBuf> = Vec::new();
+    let mut cert: Option<String> = None;
+    let mut cert_type: Option<String> = None;
+    let mut key: Option<String> = None;
+    let mut key_password: Option<String> = None;
+
+    for arg in parse_args(tokens) {
+        let value = arg.value.as_deref().unwrap_or("");
+        match arg.option.as_deref() {
+            None => {
+                if url.is_none() {
+                    url = Some(value.to_string());
+                } else {
+                    unsupported.push(format!("additional URL {}", value));
                 }
             }
-            "-H" | "--header" => {
-                if i + 1 < tokens.len() {
-                    let header = parse_header(&tokens[i + 1])?;
-                    // Don't add duplicate headers
-                    if !request.headers.iter().any(|h| h.key.to_lowercase() == header.key.to_lowercase()) {
-                        request.headers.push(header);
-                    }
-                    i += 1;
+            Some("--url") => url = Some(value.to_string()),
+            Some("--request") => {
+                method = Some(
+                    HttpMethod::parse(value)
+                        .ok_or_else(|| anyhow!("Unknown HTTP method: {}", value))?,
+                );
+            }
+            Some("--head") => head = true,
+            Some("--get") => get = true,
+            Some("--header") => {
+                if value.starts_with('@') {
+                    unsupported.push(format!("--header {}", value));
+                } else if let Some(header) = parse_header(value)? {
+                    add_header(&mut request, header);
                 }
             }
-            "-d" | "--data" | "--data-raw" | "--data-binary" => {
-                if i + 1 < tokens.len() {
-                    request.body = tokens[i + 1].clone();
-                    // Infer POST if not set
-                    if request.method == HttpMethod::GET {
-                        request.method = HttpMethod::POST;
-                    }
-                    i += 1;
+            Some("--data") | Some("--data-ascii") => {
+                data.push(match value.strip_prefix('@') {
+                    Some(path) => read_data_file(path)?.replace(['\r', '\n'], ""),
+                    None => value.to_string(),
+                });
+            }
+            Some("--data-binary") => {
+                data.push(match value.strip_prefix('@') {
+                    Some(path) => read_data_file(path)?,
+                    None => value.to_string(),
+                });
+            }
+            Some("--data-raw") => data.push(value.to_string()),
+            Some("--data-urlencode") => data.push(url_encode_data(value)?),
+            Some("--json") => {
+                json.push(match value.strip_prefix('@') {
+                    Some(path) => read_data_file(path)?,
+                    None => value.to_string(),
+                });
+            }
+            Some("--form") => form.push(form_part(value, false)?),
+            Some("--form-string") => form.push(form_part(value, true)?),
+            Some("--user") => {
+                let (username, password) = parse_basic_auth(value);
+                request.auth = AuthType::Basic { username, password };
+            }
+            Some("--oauth2-bearer") => request.auth = AuthType::Bearer(value.to_string()),
+            Some("--user-agent") => add_header(&mut request, Header::new("User-Agent", value)),
+            Some("--referer") => {
+                let referer = value.strip_suffix(";auto").unwrap_or(value);
+                if !referer.is_empty() {
+                    add_header(&mut request, Header::new("Referer", referer));
                 }
             }
-            "-u" | "--user" => {
-                if i + 1 < tokens.len() {
-                    let (user, pass) = parse_basic_auth(&tokens[i + 1]);
-                    request.auth = AuthType::Basic {
-                        username: user,
-                        password: pass,
-                    };
-                    i += 1;
+            Some("--cookie") => {
+                // Without a '=' the value names a cookie file
+                if value.contains('=') {
+                    cookies.push(value.to_string());
+                } else {
+                    unsupported.push(format!("--cookie {}", value));
                 }
             }
-            "--compressed" | "-k" | "--insecure" | "-L" | "--location" | "-s" | "--silent" | "-v" | "--verbose" => {
-                // Ignored flags
+            Some("--insecure") => request.ignore_ssl_errors = true,
+            Some("--cacert") => ca_certs.push(PathBuf::from(value)),
+            Some("--cert") => cert = Some(value.to_string()),
+            Some("--cert-type") => cert_type = Some(value.to_string()),
+            Some("--key") => key = Some(value.to_string()),
+            Some("--pass") => key_password = Some(value.to_string()),
+            Some(option @ ("--proxy" | "--socks5" | "--socks5-hostname")) => {
+                let url = match option {
+                    _ if value.contains("://") => value.to_string(),
+                    "--socks5" => format!("socks5://{}", value),
+                    "--socks5-hostname" => format!("socks5h://{}", value),
+                    _ => format!("http://{}", value),
+                };
+                proxy = Some(ProxyConfig::new(url));
             }
-            _ => {
-                // Check for URL (doesn't start with -)
-                if !token.starts_with('-') && (token.starts_with("http://") || token.starts_with("https://") || token.starts_with("'http") || token.starts_with("\"http")) {
-                    request.url = token.trim_matches(|c| c == '\'' || c == '"').to_string();
+            Some("--proxy-user") => proxy_user = Some(value.to_string()),
+            Some("--noproxy") => no_proxy = Some(value.to_string()),
+            Some("--resolve") => request.host_overrides.push(parse_resolve(value)?),
+            Some("--connect-to") => request.host_overrides.push(parse_connect_to(value)?),
+            Some("--unix-socket") => request.unix_socket = Some(value.to_string()),
+            Some(option) if NO_EFFECT.contains(&option) => {}
+            Some(option) => unsupported.push(match &arg.value {
+                Some(value) => format!("{} {}", option, value),
+                None => option.to_string(),
+            }),
+        }
+    }
+
+    let url = url.ok_or_else(|| anyhow!("No URL found in cURL command"))?;
+    request.url = if url.contains("://") {
+        url
+    } else {
+        // curl assumes HTTP when the scheme is missing
+        format!("http://{}", url)
+    };
+
+    if !cookies.is_empty() {
+        add_header(&mut request, Header::new("Cookie", cookies.join("; ")));
+    }
+
+    // Body, and the content type curl would send with it
+    if !form.is_empty() {
+        request.body = format!("{}--{}--\r\n", form.concat(), FORM_BOUNDARY);
+        set_default_header(
+            &mut request,
+            "Content-Type",
+            &format!("multipart/form-data; boundary={}", FORM_BOUNDARY),
+        );
+    } else if !json.is_empty() {
+        request.body = json.concat();
+        set_default_header(&mut request, "Content-Type", "application/json");
+        set_default_header(&mut request, "Accept", "application/json");
+    } else if !data.is_empty() {
+        let joined = data.join("&");
+        if get {
+            // -G moves the data into the query string
+            let separator = if request.url.contains('?') { '&' } else { '?' };
+            request.url = format!("{}{}{}", request.url, separator, joined);
+        } else {
+            request.body = joined;
+            set_default_header(&mut request, "Content-Type", "application/x-www-form-urlencoded");
+        }
+    }
+
+    let has_body = !request.body.is_empty();
+    request.method = match method {
+        Some(method) => method,
+        None if head => HttpMethod::HEAD,
+        None if has_body => HttpMethod::POST,
+        None => HttpMethod::GET,
+    };
+
+    request.proxy = match (proxy, no_proxy) {
+        (Some(mut config), no_proxy) => {
+            if let Some(user) = proxy_user {
+                let (username, password) = parse_basic_auth(&user);
+                config.username = Some(username);
+                config.password = Some(password);
+            }
+            if let Some(list) = no_proxy {
+                config.no_proxy = list.split(',').map(|h| h.trim().to_string()).collect();
+            }
+            Some(ProxySetting::Manual(config))
+        }
+        (None, Some(list)) if list.trim() == "*" => Some(ProxySetting::Direct),
+        (None, _) => None,
+    };
+
+    // --cert takes FILE[:PASSWORD]; PKCS#12 is picked by type or extension
+    let client_cert = cert.map(|cert| {
+        let (path, password) = match cert.split_once(':') {
+            Some((path, password)) => (path.to_string(), Some(password.to_string())),
+            None => (cert, None),
+        };
+        let is_pkcs12 = cert_type.as_deref().is_some_and(|t| t.eq_ignore_ascii_case("p12"))
+            || Path::new(&path).extension().is_some_and(|ext| {
+                ext.eq_ignore_ascii_case("p12") || ext.eq_ignore_ascii_case("pfx")
+            });
+        if is_pkcs12 {
+            ClientCertificate::Pkcs12 {
+                path: PathBuf::from(&path),
+                password: password.or(key_password).unwrap_or_default(),
+            }
+        } else {
+            ClientCertificate::Pem {
+                key: PathBuf::from(key.as_deref().unwrap_or(&path)),
+                cert: PathBuf::from(path),
+            }
+        }
+    });
+    if client_cert.is_some() || !ca_certs.is_empty() {
+        request.tls = Some(TlsProfile {
+            hosts: Vec::new(),
+            ca_certs,
+            client_cert,
+        });
+    }
+
+    Ok(CurlImport {
+        request,
+        unsupported,
+    })
+}
+
+/// A command-line option with its value, or a positional argument
+struct Arg {
+    /// Long option name; `None` for positional arguments
+    option: Option<String>,
+    value: Option<String>,
+}
+
+/// Split tokens into options and values. Short options may be bundled
+/// (`-sSL`) and take their value attached (`-XPOST`) or from the next token.
+fn parse_args(tokens: Vec<String>) -> Vec<Arg> {
+    let lookup_long = |name: &str| OPTIONS.iter().find(|(_, long, _)| *long == name);
+    let lookup_short = |c: char| OPTIONS.iter().find(|(short, _, _)| *short == Some(c));
+
+    let mut args = Vec::new();
+    let mut tokens = tokens.into_iter();
+    while let Some(token) = tokens.next() {
+        if token.starts_with("--") {
+            let takes_value = match lookup_long(&token) {
+                Some((_, _, takes_value)) => *takes_value,
+                // Like curl, an unknown option takes the next word as its
+                // value, unless that is the last word left for the URL
+                None => {
+                    let url_seen = args
+                        .iter()
+                        .any(|arg: &Arg| arg.option.is_none() || arg.option.as_deref() == Some("--url"));
+                    let words = tokens.as_slice().iter().filter(|t| !t.starts_with('-')).count();
+                    tokens.as_slice().first().is_some_and(|next| !next.starts_with('-'))
+                        && (url_seen || words > 1)
                 }
-                // Check for Bearer token in Authorization header
-                if token.to_lowercase().starts_with("authorization:") {
-                    let value = token.split_once(':').map(|x| x.1).unwrap_or("").trim();
-                    if value.to_lowercase().starts_with("bearer ") {
-                        let token = value[7..].to_string();
-                        request.auth = AuthType::Bearer(token);
+            };
+            let value = if takes_value { tokens.next() } else { None };
+            args.push(Arg {
+                option: Some(token),
+                value,
+            });
+        } else if token.len() > 1 && token.starts_with('-') {
+            let flags = &token[1..];
+            for (pos, c) in flags.char_indices() {
+                match lookup_short(c) {
+                    Some((_, long, true)) => {
+                        let rest = &flags[pos + c.len_utf8()..];
+                        let value = if rest.is_empty() {
+                            tokens.next()
+                        } else {
+                            Some(rest.to_string())
+                        };
+                        args.push(Arg {
+                            option: Some(long.to_string()),
+                            value,
+                        });
+                        break;
                     }
+                    Some((_, long, false)) => args.push(Arg {
+                        option: Some(long.to_string()),
+                        value: None,
+                    }),
+                    None => args.push(Arg {
+                        option: Some(format!("-{}", c)),
+                        value: None,
+                    }),
                 }
             }
+        } else {
+            args.push(Arg {
+                option: None,
+                value: Some(token),
+            });
         }
-        i += 1;
     }
-    
-    Ok(request)
+    args
 }
 
-fn parse_method(s: &str) -> Result<HttpMethod> {
-    match s.to_uppercase().as_str() {
-        "GET" => Ok(HttpMethod::GET),
-        "POST" => Ok(HttpMethod::POST),
-        "PUT" => Ok(HttpMethod::PUT),
-        "PATCH" => Ok(HttpMethod::PATCH),
-        "DELETE" => Ok(HttpMethod::DELETE),
-        _ => Err(anyhow!("Unknown HTTP method: {}", s)),
+/// Add a header, turning `Authorization` into the request's auth
+pub(crate) fn add_header(request: &mut Request, header: Header) {
+    if header.key.eq_ignore_ascii_case("authorization") {
+        let (scheme, credentials) = header.value.split_once(' ').unwrap_or((&header.value, ""));
+        if scheme.eq_ignore_ascii_case("bearer") {
+            request.auth = AuthType::Bearer(credentials.trim().to_string());
+            return;
+        }
+        if scheme.eq_ignore_ascii_case("basic") {
+            let decoded = base64::engine::general_purpose::STANDARD
+                .decode(credentials.trim())
+                .ok()
+                .and_then(|bytes| String::from_utf8(bytes).ok());
+            if let Some(decoded) = decoded {
+                let (username, password) = parse_basic_auth(&decoded);
+                request.auth = AuthType::Basic { username, password };
+                return;
+            }
+        }
     }
+    request.headers.push(header);
 }
 
-fn parse_header(s: &str) -> Result<Header> {
-    let parts: Vec<&str> = s.splitn(2, ':').collect();
-    if parts.len() == 2 {
-        Ok(Header::new(parts[0].trim(), parts[1].trim()))
-    } else {
-        Err(anyhow!("Invalid header format: {}", s))
+/// Add a header unless one with the same name is already set
+pub(crate) fn set_default_header(request: &mut Request, key: &str, value: &str) {
+    if !request.headers.iter().any(|h| h.key.eq_ignore_ascii_case(key)) {
+        request.headers.push(Header::new(key, value));
+    }
+}
+
+/// Read the file behind an `@file` data argument
+fn read_data_file(path: &str) -> Result<String> {
+    if path == "-" {
+        return Err(anyhow!("Reading data from stdin is not supported"));
+    }
+    std::fs::read_to_string(path).with_context(|| format!("Cannot read data file {}", path))
+}
+
+/// Encode a `--data-urlencode` argument: `content`, `=content`,
+/// `name=content`, `@file` or `name@file`
+fn url_encode_data(value: &str) -> Result<String> {
+    match value.find(['=', '@']) {
+        Some(pos) if value[pos..].starts_with('=') => {
+            let (name, content) = (&value[..pos], &value[pos + 1..]);
+            if name.is_empty() {
+                Ok(percent_encode(content))
+            } else {
+                Ok(format!("{}={}", name, percent_encode(content)))
+            }
+        }
+        Some(pos) => {
+            let (name, path) = (&value[..pos], &value[pos + 1..]);
+            let content = read_data_file(path)?;
+            if name.is_empty() {
+                Ok(percent_encode(&content))
+            } else {
+                Ok(format!("{}={}", name, percent_encode(&content)))
+            }
+        }
+        None => Ok(percent_encode(value)),
+    }
+}
+
+/// Percent-encode everything but unreserved characters, as curl does
+pub(crate) fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+/// Build one multipart part from a `-F name=value` argument. Unless
+/// `literal`, `@file` uploads a (text) file and `<file` reads the value.
+pub(crate) fn form_part(arg: &str, literal: bool) -> Result<String> {
+    let (name, value) = arg
+        .split_once('=')
+        .ok_or_else(|| anyhow!("Invalid form field: {}", arg))?;
+    let mut disposition = format!("Content-Disposition: form-data; name=\"{}\"", name);
+    let mut content_type = None;
+
+    let content = match value.strip_prefix('@').filter(|_| !literal) {
+        Some(spec) => {
+            let mut params = spec.split(';');
+            let path = params.next().unwrap_or_default();
+            let mut filename = Path::new(path)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            for param in params {
+                match param.split_once('=') {
+                    Some(("type", value)) => content_type = Some(value.to_string()),
+                    Some(("filename", value)) => filename = value.to_string(),
+                    _ => {}
+                }
+            }
+            disposition.push_str(&format!("; filename=\"{}\"", filename));
+            content_type.get_or_insert_with(|| "application/octet-stream".to_string());
+            std::fs::read_to_string(path)
+                .with_context(|| format!("Cannot read form file {} (only text files are supported)", path))?
+        }
+        None => match value.strip_prefix('<').filter(|_| !literal) {
+            Some(path) => read_data_file(path)?,
+            None => value.to_string(),
+        },
+    };
+
+    let mut part = format!("--{}\r\n{}\r\n", FORM_BOUNDARY, disposition);
+    if let Some(content_type) = content_type {
+        part.push_str(&format!("Content-Type: {}\r\n", content_type));
+    }
+    part.push_str(&format!("\r\n{}\r\n", content));
+    Ok(part)
+}
+
+/// Parse a `-H` value. `Name: value` sets a header, `Name;` sends it
+/// empty and `Name:` (which removes a header in curl) yields nothing.
+fn parse_header(s: &str) -> Result<Option<Header>> {
+    if let Some(name) = s.strip_suffix(';').filter(|name| !name.contains(':')) {
+        return Ok(Some(Header::new(name.trim(), "")));
+    }
+    let (key, value) = s
+        .split_once(':')
+        .ok_or_else(|| anyhow!("Invalid header format: {}", s))?;
+    if value.trim().is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Header::new(key.trim(), value.trim())))
+}
+
+/// Parse `--resolve HOST:PORT:ADDR[,ADDR...]`; only the first address is used
+fn parse_resolve(s: &str) -> Result<HostOverride> {
+    let invalid = || anyhow!("Invalid --resolve value: {}", s);
+    let mut parts = s.splitn(3, ':');
+    let host = parts.next().ok_or_else(invalid)?.trim_start_matches('+');
+    let port = parts.next().ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+    let address = parts.next().ok_or_else(invalid)?.split(',').next().unwrap_or("");
+    let address = address.trim_start_matches('[').trim_end_matches(']');
+    if host.is_empty() || address.is_empty() {
+        return Err(invalid());
+    }
+    Ok(HostOverride {
+        host: host.to_string(),
+        port: Some(port),
+        address: Some(address.to_string()),
+        connect_port: None,
+    })
+}
+
+/// Parse `--connect-to HOST1:PORT1:HOST2:PORT2`, where any part may be empty
+fn parse_connect_to(s: &str) -> Result<HostOverride> {
+    let invalid = || anyhow!("Invalid --connect-to value: {}", s);
+    let port = |p: &str| -> Result<Option<u16>> {
+        if p.is_empty() {
+            Ok(None)
+        } else {
+            p.parse().map(Some).map_err(|_| invalid())
+        }
+    };
+    let mut parts = s.splitn(3, ':');
+    let host = parts.next().ok_or_else(invalid)?;
+    let listen_port = port(parts.next().ok_or_else(invalid)?)?;
+    let (address, connect_port) = parts.next().and_then(|rest| rest.rsplit_once(':')).ok_or_else(invalid)?;
+    let address = address.trim_start_matches('[').trim_end_matches(']');
+    Ok(HostOverride {
+        host: if host.is_empty() { "*".to_string() } else { host.to_string() },
+        port: listen_port,
+        address: (!address.is_empty()).then(|| address.to_string()),
+        connect_port: port(connect_port)?,
+    })
+}
+
+/// Format a host override as `--resolve` when it only swaps the address,
+/// `--connect-to` otherwise
+fn format_host_override(host_override: &HostOverride) -> String {
+    let bracket = |address: &str| {
+        if address.contains(':') {
+            format!("[{}]", address)
+        } else {
+            address.to_string()
+        }
+    };
+    match (&host_override.address, host_override.port, host_override.connect_port) {
+        (Some(address), Some(port), None) if host_override.host != "*" => {
+            format!("--resolve '{}:{}:{}'", host_override.host, port, bracket(address))
+        }
+        (address, port, connect_port) => format!(
+            "--connect-to '{}:{}:{}:{}'",
+            if host_override.host == "*" { "" } else { &host_override.host },
+            port.map(|p| p.to_string()).unwrap_or_default(),
+            address.as_deref().map(bracket).unwrap_or_default(),
+            connect_port.map(|p| p.to_string()).unwrap_or_default()
+        ),
     }
 }
 
@@ -111,88 +620,224 @@ fn parse_basic_auth(s: &str) -> (String, String) {
     }
 }
 
-/// Tokenize a curl command, respecting quotes
+/// Tokenize a curl command following shell quoting rules, including the
+/// `$'...'` ANSI-C quoting that browsers use in "copy as cURL"
 fn tokenize(input: &str) -> Result<Vec<String>> {
     let mut tokens = Vec::new();
     let mut current = String::new();
-    let mut in_single_quote = false;
-    let mut in_double_quote = false;
-    let mut escape_next = false;
-    
-    for c in input.chars() {
-        if escape_next {
-            current.push(c);
-            escape_next = false;
-            continue;
-        }
-        
+    // Distinguishes an empty quoted argument from no argument
+    let mut in_token = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
         match c {
-            '\\' if !in_single_quote => {
-                escape_next = true;
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+                in_token = true;
             }
-            '\'' if !in_double_quote => {
-                in_single_quote = !in_single_quote;
+            '\'' => {
+                in_token = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err(anyhow!("Unterminated single quote")),
+                    }
+                }
             }
-            '"' if !in_single_quote => {
-                in_double_quote = !in_double_quote;
+            '"' => {
+                in_token = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => current.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => return Err(anyhow!("Unterminated double quote")),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err(anyhow!("Unterminated double quote")),
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                in_token = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some('\\') => current.push(ansi_c_escape(&mut chars)?),
+                        Some(c) => current.push(c),
+                        None => return Err(anyhow!("Unterminated $'...' quote")),
+                    }
+                }
             }
-            ' ' | '\t' | '\n' if !in_single_quote && !in_double_quote => {
-                if !current.is_empty() {
-                    tokens.push(current.clone());
-                    current.clear();
+            c if c.is_whitespace() => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
                 }
             }
-            _ => {
+            c => {
                 current.push(c);
+                in_token = true;
             }
         }
     }
-    
-    if !current.is_empty() {
+
+    if in_token {
         tokens.push(current);
     }
-    
+
     Ok(tokens)
 }
 
+/// Decode the escape sequence after a backslash inside `$'...'`
+fn ansi_c_escape(chars: &mut Peekable<Chars>) -> Result<char> {
+    let c = chars
+        .next()
+        .ok_or_else(|| anyhow!("Unterminated $'...' quote"))?;
+    Ok(match c {
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        'a' => '\x07',
+        'b' => '\x08',
+        'e' | 'E' => '\x1b',
+        'f' => '\x0c',
+        'v' => '\x0b',
+        'x' => escaped_char(chars, String::new(), 16, 2)?,
+        'u' => escaped_char(chars, String::new(), 16, 4)?,
+        'U' => escaped_char(chars, String::new(), 16, 8)?,
+        '0'..='7' => escaped_char(chars, c.to_string(), 8, 3)?,
+        // \\, \', \" and \? stand for themselves
+        c => c,
+    })
+}
+
+/// Read up to `max` digits in `radix` (after those already in `digits`)
+/// and return the character with that code point
+fn escaped_char(chars: &mut Peekable<Chars>, mut digits: String, radix: u32, max: usize) -> Result<char> {
+    while digits.len() < max {
+        match chars.peek() {
+            Some(c) if c.is_digit(radix) => digits.push(*c),
+            _ => break,
+        }
+        chars.next();
+    }
+    u32::from_str_radix(&digits, radix)
+        .ok()
+        .and_then(char::from_u32)
+        .ok_or_else(|| anyhow!("Invalid escape sequence in $'...' quote"))
+}
+
 /// Format request as cURL command
 pub fn to_curl(request: &Request) -> String {
     let mut parts = vec!["curl".to_string()];
-    
+
     // Method
-    if request.method != HttpMethod::GET {
-        parts.push(format!("-X {}", request.method.as_str()));
+    match request.method {
+        HttpMethod::GET => {}
+        HttpMethod::HEAD => parts.push("-I".to_string()),
+        _ => parts.push(format!("-X {}", request.method.as_str())),
     }
-    
+
     // URL
-    parts.push(format!("'{}'", request.url));
-    
+    parts.push(quote(&request.url));
+
     // Headers
     for header in &request.headers {
         if header.enabled {
-            parts.push(format!("-H '{}: {}'", header.key, header.value));
+            parts.push(format!("-H {}", quote(&format!("{}: {}", header.key, header.value))));
         }
     }
-    
+
     // Auth
     match &request.auth {
         AuthType::Bearer(token) => {
-            parts.push(format!("-H 'Authorization: Bearer {}'", token));
+            parts.push(format!("-H {}", quote(&format!("Authorization: Bearer {}", token))));
         }
         AuthType::Basic { username, password } => {
-            parts.push(format!("-u '{}:{}'", username, password));
+            parts.push(format!("-u {}", quote(&format!("{}:{}", username, password))));
         }
         AuthType::None => {}
     }
-    
-    // Body
-    if !request.body.is_empty() {
-        parts.push(format!("-d '{}'", request.body.replace('\'', "'\\''")));
+
+    // TLS
+    if request.ignore_ssl_errors {
+        parts.push("-k".to_string());
+    }
+    if let Some(tls) = &request.tls {
+        for ca in &tls.ca_certs {
+            parts.push(format!("--cacert {}", quote(&ca.to_string_lossy())));
+        }
+        match &tls.client_cert {
+            Some(ClientCertificate::Pem { cert, key }) => {
+                parts.push(format!("--cert {}", quote(&cert.to_string_lossy())));
+                if key != cert {
+                    parts.push(format!("--key {}", quote(&key.to_string_lossy())));
+                }
+            }
+            Some(ClientCertificate::Pkcs12 { path, password }) => {
+                parts.push("--cert-type P12".to_string());
+                let mut cert = path.to_string_lossy().into_owned();
+                if !password.is_empty() {
+                    cert = format!("{}:{}", cert, password);
+                }
+                parts.push(format!("--cert {}", quote(&cert)));
+            }
+            None => {}
+        }
+    }
+
+    // Host overrides
+    for host_override in &request.host_overrides {
+        parts.push(format_host_override(host_override));
     }
-    
+
+    // Unix sockets bypass any proxy
+    if let Some(socket) = &request.unix_socket {
+        parts.push(format!("--unix-socket {}", quote(socket)));
+    }
+
+    // Proxy
+    match request.proxy.as_ref().filter(|_| request.unix_socket.is_none()) {
+        Some(ProxySetting::Manual(proxy)) => {
+            parts.push(format!("-x {}", quote(&proxy.url)));
+            if let Some(username) = &proxy.username {
+                parts.push(format!(
+                    "-U {}",
+                    quote(&format!("{}:{}", username, proxy.password.as_deref().unwrap_or("")))
+                ));
+            }
+            if !proxy.no_proxy.is_empty() {
+                parts.push(format!("--noproxy {}", quote(&proxy.no_proxy.join(","))));
+            }
+        }
+        Some(ProxySetting::Direct) => parts.push("--noproxy '*'".to_string()),
+        Some(ProxySetting::System) | None => {}
+    }
+
+    // Body; --data-raw keeps a leading '@' from naming a file
+    if request.method.has_body() && !request.body.is_empty() {
+        let flag = if request.body.starts_with('@') { "--data-raw" } else { "-d" };
+        parts.push(format!("{} {}", flag, quote(&request.body)));
+    }
+
     parts.join(" \\\n  ")
 }
 
+/// Single-quote a shell argument
+pub(crate) fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -212,4 +857,178 @@ mod tests {
         assert_eq!(req.method, HttpMethod::POST);
         assert_eq!(req.body, r#"{"name":"test"}"#);
     }
+
+    #[test]
+    fn test_parse_proxy_roundtrip() {
+        let curl = "curl -x proxy.internal:3128 -U bob:secret --noproxy localhost,.corp https://api.example.com";
+        let req = parse_curl(curl).unwrap();
+        let Some(ProxySetting::Manual(proxy)) = &req.proxy else {
+            panic!("expected manual proxy, got {:?}", req.proxy);
+        };
+        assert_eq!(proxy.url, "http://proxy.internal:3128");
+        assert_eq!(proxy.username.as_deref(), Some("bob"));
+        assert_eq!(proxy.no_proxy, vec!["localhost", ".corp"]);
+
+        let exported = to_curl(&req);
+        assert!(exported.contains("-x 'http://proxy.internal:3128'"));
+        assert!(exported.contains("-U 'bob:secret'"));
+        assert!(exported.contains("--noproxy 'localhost,.corp'"));
+    }
+
+    #[test]
+    fn test_parse_host_overrides_roundtrip() {
+        let curl = "curl --resolve api.example.com:443:10.0.0.12 --connect-to ::[::1]:8443 https://api.example.com/health";
+        let req = parse_curl(curl).unwrap();
+        assert_eq!(
+            req.host_overrides,
+            vec![
+                HostOverride {
+                    host: "api.example.com".to_string(),
+                    port: Some(443),
+                    address: Some("10.0.0.12".to_string()),
+                    connect_port: None,
+                },
+                HostOverride {
+                    host: "*".to_string(),
+                    port: None,
+                    address: Some("::1".to_string()),
+                    connect_port: Some(8443),
+                },
+            ]
+        );
+
+        let exported = to_curl(&req);
+        assert!(exported.contains("--resolve 'api.example.com:443:10.0.0.12'"));
+        assert!(exported.contains("--connect-to '::[::1]:8443'"));
+        assert!(parse_curl("curl --resolve api.example.com:https:1.2.3.4 https://x").is_err());
+    }
+
+    #[test]
+    fn test_parse_unix_socket_roundtrip() {
+        let curl = "curl --unix-socket /var/run/docker.sock http://localhost/v1.43/containers/json";
+        let req = parse_curl(curl).unwrap();
+        assert_eq!(req.unix_socket.as_deref(), Some("/var/run/docker.sock"));
+        assert_eq!(req.url, "http://localhost/v1.43/containers/json");
+
+        let exported = to_curl(&req);
+        assert!(exported.contains("--unix-socket '/var/run/docker.sock'"));
+        assert!(exported.contains("'http://localhost/v1.43/containers/json'"));
+    }
+
+    #[test]
+    fn test_import_browser_copy_as_curl() {
+        let curl = r#"curl 'https://api.example.com/v1/items?page=2' \
+  -H 'accept: */*' \
+  -H 'authorization: Bearer abc123' \
+  -b 'sid=42; theme=dark' \
+  -H 'content-type: application/json' \
+  --data-raw $'{"note":"it\'s\\nfine é"}' \
+  --compressed"#;
+        let import = import_curl(curl).unwrap();
+        let req = import.request;
+        assert!(import.unsupported.is_empty(), "{:?}", import.unsupported);
+        assert_eq!(req.method, HttpMethod::POST);
+        assert_eq!(req.url, "https://api.example.com/v1/items?page=2");
+        assert_eq!(req.auth, AuthType::Bearer("abc123".to_string()));
+        assert_eq!(req.body, "{\"note\":\"it's\\nfine \u{e9}\"}");
+        let headers: Vec<(&str, &str)> =
+            req.headers.iter().map(|h| (h.key.as_str(), h.value.as_str())).collect();
+        assert_eq!(
+            headers,
+            vec![
+                ("accept", "*/*"),
+                ("content-type", "application/json"),
+                ("Cookie", "sid=42; theme=dark"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_data_variants() {
+        let req = parse_curl("curl -d a=1 -d b=2 --data-urlencode 'q=hello world&more' example.com/search").unwrap();
+        assert_eq!(req.url, "http://example.com/search");
+        assert_eq!(req.method, HttpMethod::POST);
+        assert_eq!(req.body, "a=1&b=2&q=hello%20world%26more");
+        assert!(req.headers.iter().any(|h| h.value == "application/x-www-form-urlencoded"));
+
+        let req = parse_curl("curl -G -d a=1 --data-urlencode 'b=x y' 'https://example.com/?z=0'").unwrap();
+        assert_eq!(req.method, HttpMethod::GET);
+        assert_eq!(req.url, "https://example.com/?z=0&a=1&b=x%20y");
+        assert!(req.body.is_empty());
+
+        let req = parse_curl(r#"curl --json '{"a":1}' --url https://example.com -X PUT"#).unwrap();
+        assert_eq!(req.method, HttpMethod::PUT);
+        assert_eq!(req.body, r#"{"a":1}"#);
+        assert!(req.headers.iter().any(|h| h.key == "Content-Type" && h.value == "application/json"));
+
+        let req = parse_curl("curl -I -A freeman/1.0 -e https://ref.example https://example.com").unwrap();
+        assert_eq!(req.method, HttpMethod::HEAD);
+        assert!(req.headers.iter().any(|h| h.key == "User-Agent" && h.value == "freeman/1.0"));
+        assert!(req.headers.iter().any(|h| h.key == "Referer"));
+    }
+
+    #[test]
+    fn test_import_multipart_form() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "hello").unwrap();
+        let curl = format!("curl -F name=freeman -F 'doc=@{};type=text/plain' https://example.com/upload", path.display());
+        let req = parse_curl(&curl).unwrap();
+        assert_eq!(req.method, HttpMethod::POST);
+        assert!(req.headers.iter().any(|h| h.value == format!("multipart/form-data; boundary={}", FORM_BOUNDARY)));
+        assert!(req.body.contains("name=\"name\"\r\n\r\nfreeman\r\n"));
+        assert!(req.body.contains("name=\"doc\"; filename=\"notes.txt\"\r\nContent-Type: text/plain\r\n\r\nhello\r\n"));
+        assert!(req.body.ends_with(&format!("--{}--\r\n", FORM_BOUNDARY)));
+    }
+
+    #[test]
+    fn test_import_unknown_options() {
+        let import = import_curl("curl --retry-delay 2 https://api.test").unwrap();
+        assert_eq!(import.request.url, "https://api.test");
+        assert_eq!(import.unsupported, vec!["--retry-delay 2"]);
+
+        // The only word left is the URL, not the option's value
+        let import = import_curl("curl --tcp-nodelay https://api.test --retry-all-errors").unwrap();
+        assert_eq!(import.request.url, "https://api.test");
+        assert_eq!(import.unsupported, vec!["--tcp-nodelay", "--retry-all-errors"]);
+
+        let import = import_curl("curl https://api.test --expect100-timeout 1.5 -H 'A: b'").unwrap();
+        assert_eq!(import.request.url, "https://api.test");
+        assert_eq!(import.unsupported, vec!["--expect100-timeout 1.5"]);
+        assert_eq!(import.request.headers[0].key, "A");
+    }
+
+    #[test]
+    fn test_import_tls_and_unsupported_options() {
+        let curl = "curl -sSLk -m 10 --digest --cacert ca.pem --cert-type P12 --cert client.p12:pw https://example.com";
+        let import = import_curl(curl).unwrap();
+        let req = import.request;
+        assert!(req.ignore_ssl_errors);
+        assert_eq!(import.unsupported, vec!["--max-time 10", "--digest"]);
+        let tls = req.tls.clone().unwrap();
+        assert_eq!(tls.ca_certs, vec![PathBuf::from("ca.pem")]);
+        assert_eq!(
+            tls.client_cert,
+            Some(ClientCertificate::Pkcs12 {
+                path: PathBuf::from("client.p12"),
+                password: "pw".to_string()
+            })
+        );
+
+        let exported = to_curl(&req);
+        assert!(exported.contains("-k"));
+        assert!(exported.contains("--cacert 'ca.pem'"));
+        assert!(exported.contains("--cert-type P12"));
+        assert!(exported.contains("--cert 'client.p12:pw'"));
+
+        let req = parse_curl("curl -XDELETE -E client.pem --key client.key https://example.com/1").unwrap();
+        assert_eq!(req.method, HttpMethod::DELETE);
+        assert_eq!(
+            req.tls.unwrap().client_cert,
+            Some(ClientCertificate::Pem {
+                cert: PathBuf::from("client.pem"),
+                key: PathBuf::from("client.key")
+            })
+        );
+    }
 }
@@ -0,0 +1,283 @@
+//! Example values synthesized from OpenAPI schemas
+//!
+//! Used for request bodies the spec gives no literal example for. Values
+//! come from the schema's own `example`, `default`, `const` or first
+//! `enum` entry where there is one, else from the type and format.
+//! `readOnly` properties are left out since they belong in responses.
+
+use serde_json::{Map, Value};
+
+use crate::discovery::openapi;
+
+/// Nesting below which objects and arrays are left empty
+const MAX_DEPTH: usize = 12;
+
+/// An example for `schema`, resolving `$ref`s against `spec`; `None` when
+/// the schema only refers back to itself
+pub fn example_for(schema: &Value, spec: &Value) -> Option<Value> {
+    Generator { spec, refs: Vec::new() }.example(schema, None, 0)
+}
+
+struct Generator<'a> {
+    spec: &'a Value,
+    /// `$ref`s being expanded, to stop at cycles
+    refs: Vec<&'a str>,
+}
+
+impl<'a> Generator<'a> {
+    /// `name` is the property the value is for, used as a hint for strings
+    fn example(&mut self, schema: &'a Value, name: Option<&str>, depth: usize) -> Option<Value> {
+        if let Some(reference) = schema.get("$ref").and_then(|r| r.as_str()) {
+            if self.refs.contains(&reference) {
+                return None;
+            }
+            let target = openapi::resolve_ref(self.spec, reference)?;
+            self.refs.push(reference);
+            let example = self.example(target, name, depth);
+            self.refs.pop();
+            return example;
+        }
+
+        let literal = schema
+            .get("example")
+            .or_else(|| schema.get("examples").and_then(|e| e.as_array()).and_then(|e| e.first()))
+            .or_else(|| schema.get("default"))
+            .or_else(|| schema.get("const"))
+            .or_else(|| schema.get("enum").and_then(|e| e.as_array()).and_then(|e| e.first()));
+        if let Some(literal) = literal {
+            return Some(literal.clone());
+        }
+
+        if let Some(parts) = schema.get("allOf").and_then(|a| a.as_array()) {
+            // Properties next to the `allOf` extend the composed object
+            let mut merged = self.merge(parts, name, depth);
+            if let Value::Object(into) = &mut merged {
+                into.extend(self.object(schema, depth));
+            }
+            return Some(merged);
+        }
+        if let Some(first) = ["oneOf", "anyOf"]
+            .iter()
+            .find_map(|key| schema.get(*key).and_then(|o| o.as_array()).and_then(|o| o.first()))
+        {
+            return self.example(first, name, depth);
+        }
+
+        match schema_type(schema) {
+            "object" => Some(Value::Object(self.object(schema, depth))),
+            "array" => {
+                let item = schema
+                    .get("items")
+                    .filter(|_| depth < MAX_DEPTH)
+                    .and_then(|items| self.example(items, name, depth + 1));
+                Some(Value::Array(item.into_iter().collect()))
+            }
+            "integer" => Some(Value::from(schema.get("minimum").and_then(|m| m.as_i64()).unwrap_or(0))),
+            "number" => Some(Value::from(schema.get("minimum").and_then(|m| m.as_f64()).unwrap_or(0.0))),
+            "boolean" => Some(Value::Bool(true)),
+            "null" => Some(Value::Null),
+            _ => Some(Value::String(string_example(schema, name))),
+        }
+    }
+
+    fn object(&mut self, schema: &'a Value, depth: usize) -> Map<String, Value> {
+        let mut object = Map::new();
+        if depth >= MAX_DEPTH {
+            return object;
+        }
+        if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+            for (name, property) in properties {
+                if self.is_read_only(property) {
+                    continue;
+                }
+                if let Some(value) = self.example(property, Some(name), depth + 1) {
+                    object.insert(name.clone(), value);
+                }
+            }
+        }
+        if object.is_empty() {
+            if let Some(extra @ Value::Object(_)) = schema.get("additionalProperties") {
+                if let Some(value) = self.example(extra, None, depth + 1) {
+                    object.insert("key".to_string(), value);
+                }
+            }
+        }
+        object
+    }
+
+    /// The parts of an `allOf`, with their object properties combined
+    fn merge(&mut self, parts: &'a [Value], name: Option<&str>, depth: usize) -> Value {
+        let mut merged: Option<Value> = None;
+        for part in parts {
+            match (merged.as_mut(), self.example(part, name, depth)) {
+                (Some(Value::Object(into)), Some(Value::Object(from))) => into.extend(from),
+                (None, Some(value)) => merged = Some(value),
+                _ => {}
+            }
+        }
+        merged.unwrap_or_else(|| Value::Object(Map::new()))
+    }
+
+    fn is_read_only(&self, property: &Value) -> bool {
+        let resolved = property
+            .get("$ref")
+            .and_then(|r| r.as_str())
+            .and_then(|r| openapi::resolve_ref(self.spec, r))
+            .unwrap_or(property);
+        property.get("readOnly").or_else(|| resolved.get("readOnly")).and_then(|r| r.as_bool()) == Some(true)
+    }
+}
+
+/// The declared type, or the one implied by the keywords present
+fn schema_type(schema: &Value) -> &str {
+    match schema.get("type") {
+        Some(Value::String(name)) => name,
+        // OpenAPI 3.1 type lists: the first that is not null
+        Some(Value::Array(names)) => names
+            .iter()
+            .filter_map(|n| n.as_str())
+            .find(|n| *n != "null")
+            .unwrap_or("null"),
+        _ if schema.get("properties").is_some() || schema.get("additionalProperties").is_some() => "object",
+        _ if schema.get("items").is_some() => "array",
+        _ => "string",
+    }
+}
+
+fn string_example(schema: &Value, name: Option<&str>) -> String {
+    let example = match schema.get("format").and_then(|f| f.as_str()) {
+        Some("date-time") => "2024-01-15T09:30:00Z",
+        Some("date") => "2024-01-15",
+        Some("time") => "09:30:00",
+        Some("email") => "user@example.com",
+        Some("uri" | "url" | "uri-reference") => "https://example.com",
+        Some("hostname") => "example.com",
+        Some("ipv4") => "192.0.2.1",
+        Some("ipv6") => "2001:db8::1",
+        Some("uuid") => "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+        Some("byte") => "ZXhhbXBsZQ==",
+        Some("password") => "password",
+        _ => {
+            let name = name.unwrap_or("").to_ascii_lowercase();
+            if name.contains("email") {
+                "user@example.com"
+            } else if name.ends_with("url") || name.ends_with("uri") {
+                "https://example.com"
+            } else {
+                "string"
+            }
+        }
+    };
+    let min = schema.get("minLength").and_then(|m| m.as_u64()).unwrap_or(0) as usize;
+    format!("{:x<min$}", example, min = min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_example_from_schema() {
+        let spec = json!({
+            "components": { "schemas": {
+                "Base": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "integer", "readOnly": true },
+                        "created": { "type": "string", "format": "date-time" }
+                    }
+                },
+                "Pet": {
+                    "allOf": [
+                        { "$ref": "#/components/schemas/Base" },
+                        {
+                            "type": "object",
+                            "required": ["name"],
+                            "properties": {
+                                "name": { "type": "string", "example": "Rex" },
+                                "status": { "type": "string", "enum": ["available", "sold"] },
+                                "age": { "type": "integer", "minimum": 1 },
+                                "owner_email": { "type": "string" },
+                                "tags": { "type": "array", "items": { "type": "string" } },
+                                "vaccinated": { "type": "boolean", "default": false },
+                                "kind": { "oneOf": [{ "type": "string", "const": "dog" }, { "type": "integer" }] },
+                                "parent": { "$ref": "#/components/schemas/Pet" }
+                            }
+                        }
+                    ]
+                }
+            }}
+        });
+        let example = example_for(&json!({ "$ref": "#/components/schemas/Pet" }), &spec).unwrap();
+        assert_eq!(
+            example,
+            json!({
+                "created": "2024-01-15T09:30:00Z",
+                "name": "Rex",
+                "status": "available",
+                "age": 1,
+                "owner_email": "user@example.com",
+                "tags": ["string"],
+                "vaccinated": false,
+                "kind": "dog"
+            })
+        );
+    }
+
+    #[test]
+    fn test_example_all_of_with_sibling_properties() {
+        let spec = json!({
+            "components": { "schemas": {
+                "Base": {
+                    "type": "object",
+                    "properties": { "id": { "type": "integer", "readOnly": true }, "name": { "type": "string" } }
+                }
+            }}
+        });
+        let schema = json!({
+            "allOf": [{ "$ref": "#/components/schemas/Base" }],
+            "properties": {
+                "name": { "type": "string", "example": "Rex" },
+                "extra": { "type": "boolean" }
+            }
+        });
+        assert_eq!(example_for(&schema, &spec), Some(json!({ "name": "Rex", "extra": true })));
+    }
+
+    #[test]
+    fn test_example_stops_at_ref_recursion() {
+        let mut schemas = Map::new();
+        for level in 0..20 {
+            schemas.insert(
+                format!("Level{}", level),
+                json!({
+                    "type": "object",
+                    "properties": { "next": { "$ref": format!("#/components/schemas/Level{}", level + 1) } }
+                }),
+            );
+        }
+        schemas.insert(
+            "Node".to_string(),
+            json!({
+                "type": "object",
+                "properties": {
+                    "id": { "type": "integer" },
+                    "children": { "type": "array", "items": { "$ref": "#/components/schemas/Node" } }
+                }
+            }),
+        );
+        schemas.insert("Loop".to_string(), json!({ "$ref": "#/components/schemas/Loop" }));
+        let spec = json!({ "components": { "schemas": schemas } });
+
+        let chain = example_for(&json!({ "$ref": "#/components/schemas/Level0" }), &spec).unwrap();
+        let deepest = "/next".repeat(MAX_DEPTH);
+        assert_eq!(chain.pointer(&deepest), Some(&json!({})));
+        assert_eq!(chain.pointer(&"/next".repeat(MAX_DEPTH - 1)), Some(&json!({ "next": {} })));
+
+        let node = example_for(&json!({ "$ref": "#/components/schemas/Node" }), &spec).unwrap();
+        assert_eq!(node, json!({ "id": 0, "children": [] }));
+
+        assert_eq!(example_for(&json!({ "$ref": "#/components/schemas/Loop" }), &spec), None);
+    }
+}
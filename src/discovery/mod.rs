@@ -2,6 +2,7 @@
 
 pub mod detector;
 pub mod django;
+pub mod example;
 pub mod express;
 pub mod java;
 pub mod models;
@@ -9,6 +10,8 @@ pub mod nestjs;
 pub mod openapi;
 pub mod php;
 pub mod python;
+pub mod spec_refs;
+pub mod validate;
 
 pub use django::load_django_project;
 pub use express::load_express_project;
@@ -1,7 +1,9 @@
 //! Data models for discovered endpoints and workspace projects
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 /// Detected API framework
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -90,6 +92,57 @@ pub struct BodySchema {
     pub schema_name: Option<String>,
     pub required: bool,
     pub example: Option<String>,
+    /// JSON Schema of the body, `$ref`s left as they are
+    pub schema: Option<Value>,
+}
+
+/// A server the API is served from, from a spec's `servers`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ServerSpec {
+    /// URL, possibly with `{variable}` placeholders
+    pub url: String,
+    pub description: Option<String>,
+    pub variables: Vec<ServerVariable>,
+}
+
+/// A `{variable}` of a server URL
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ServerVariable {
+    pub name: String,
+    pub default: String,
+    /// Allowed values from `enum`; empty when any value goes
+    pub choices: Vec<String>,
+    pub description: Option<String>,
+}
+
+impl ServerVariable {
+    /// The chosen value when it is allowed, else the default
+    pub fn value<'a>(&'a self, chosen: &'a HashMap<String, String>) -> &'a str {
+        match chosen.get(&self.name) {
+            Some(value) if self.choices.is_empty() || self.choices.contains(value) => value,
+            _ => &self.default,
+        }
+    }
+}
+
+impl ServerSpec {
+    /// The URL with its variables replaced by the chosen values or defaults
+    pub fn resolve(&self, chosen: &HashMap<String, String>) -> String {
+        self.variables.iter().fold(self.url.clone(), |url, variable| {
+            url.replace(&format!("{{{}}}", variable.name), variable.value(chosen))
+        })
+    }
+}
+
+/// A documented response of an endpoint
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ResponseSpec {
+    /// Status code, range (`2XX`) or `default`
+    pub status: String,
+    pub description: Option<String>,
+    pub content_type: Option<String>,
+    /// JSON Schema of the body, `$ref`s left as they are
+    pub schema: Option<Value>,
 }
 
 /// A discovered API endpoint
@@ -113,6 +166,10 @@ pub struct DiscoveredEndpoint {
     pub parameters: Vec<Parameter>,
     /// Request body schema
     pub body: Option<BodySchema>,
+    /// Documented responses, in spec order
+    pub responses: Vec<ResponseSpec>,
+    /// Servers of the operation or its path, overriding the spec's
+    pub servers: Vec<ServerSpec>,
     /// Authentication requirement
     pub auth: AuthRequirement,
     /// Tags for grouping
@@ -133,12 +190,26 @@ impl DiscoveredEndpoint {
             line_number: None,
             parameters: Vec::new(),
             body: None,
+            responses: Vec::new(),
+            servers: Vec::new(),
             auth: AuthRequirement::None,
             tags: Vec::new(),
             deprecated: false,
         }
     }
 
+    /// The documented response for a status: the exact code, then its
+    /// range (`4XX`), then `default`
+    pub fn response_for(&self, status: u16) -> Option<&ResponseSpec> {
+        let code = status.to_string();
+        let range = format!("{}XX", status / 100);
+        self.responses
+            .iter()
+            .find(|r| r.status == code)
+            .or_else(|| self.responses.iter().find(|r| r.status.eq_ignore_ascii_case(&range)))
+            .or_else(|| self.responses.iter().find(|r| r.status == "default"))
+    }
+
     /// Returns display title for the endpoint
     #[allow(dead_code)]  // Prepared for future endpoint display feature
     pub fn display_title(&self) -> String {
@@ -158,12 +229,18 @@ pub struct WorkspaceProject {
     pub framework: Framework,
     /// Base URL (from config or spec)
     pub base_url: Option<String>,
+    /// Servers of the spec, selectable in the workspace panel
+    pub servers: Vec<ServerSpec>,
     /// API title/name
     pub title: Option<String>,
     /// API version
     pub version: Option<String>,
     /// All discovered endpoints
     pub endpoints: Vec<DiscoveredEndpoint>,
+    /// The spec document the endpoints came from, for resolving `$ref`s
+    pub spec: Option<Value>,
+    /// `$ref`s of the spec that were left unresolved, and why
+    pub unresolved_refs: Vec<String>,
 }
 
 impl WorkspaceProject {
@@ -172,9 +249,12 @@ impl WorkspaceProject {
             root,
             framework: Framework::Unknown,
             base_url: None,
+            servers: Vec::new(),
             title: None,
             version: None,
             endpoints: Vec::new(),
+            spec: None,
+            unresolved_refs: Vec::new(),
         }
     }
 
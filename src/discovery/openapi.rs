@@ -1,29 +1,27 @@
 //! OpenAPI/Swagger specification parser
 
 use anyhow::Result;
-use serde_json::Value;
-use std::fs;
+use serde_json::{json, Value};
 use std::path::Path;
 
+use crate::curl::percent_encode;
+use crate::discovery::{example, spec_refs};
 use crate::discovery::models::{
     AuthRequirement, BodySchema, DiscoveredEndpoint, Framework, Parameter, ParameterLocation,
-    WorkspaceProject,
+    ResponseSpec, ServerSpec, ServerVariable, WorkspaceProject,
 };
 
-/// Parse an OpenAPI spec file and return a WorkspaceProject
-pub fn parse_openapi(spec_path: &Path) -> Result<WorkspaceProject> {
-    let content = fs::read_to_string(spec_path)?;
-
-    // Determine if JSON or YAML
-    let spec: Value = if spec_path.extension().map(|e| e == "json").unwrap_or(false) {
-        serde_json::from_str(&content)?
-    } else {
-        serde_yaml::from_str(&content)?
-    };
+/// Parse an OpenAPI spec file found in `workspace_root` and return a
+/// WorkspaceProject
+pub fn parse_openapi(spec_path: &Path, workspace_root: &Path) -> Result<WorkspaceProject> {
+    // Refs into other files of the workspace become local, so one document
+    // has everything
+    let (spec, unresolved_refs) = spec_refs::load_bundled(spec_path, workspace_root)?;
 
     let root = spec_path.parent().unwrap_or(Path::new(".")).to_path_buf();
     let mut project = WorkspaceProject::new(root);
     project.framework = Framework::OpenAPI;
+    project.unresolved_refs = unresolved_refs;
 
     // Extract info
     if let Some(info) = spec.get("info") {
@@ -34,12 +32,30 @@ pub fn parse_openapi(spec_path: &Path) -> Result<WorkspaceProject> {
             .map(String::from);
     }
 
-    // Extract base URL from servers
-    if let Some(servers) = spec.get("servers").and_then(|s| s.as_array()) {
-        if let Some(first) = servers.first() {
-            project.base_url = first.get("url").and_then(|v| v.as_str()).map(String::from);
-        }
-    }
+    // Swagger 2.0 differs in the base URL, bodies, media types and auth
+    let swagger2 = spec
+        .get("swagger")
+        .and_then(|v| v.as_str())
+        .is_some_and(|v| v.starts_with('2'));
+
+    // Extract servers, or the one made of host and basePath; the base URL
+    // is the first with its variables at their defaults
+    project.servers = if swagger2 {
+        swagger_base_url(&spec)
+            .map(|url| ServerSpec {
+                url,
+                description: None,
+                variables: Vec::new(),
+            })
+            .into_iter()
+            .collect()
+    } else {
+        parse_servers(spec.get("servers"))
+    };
+    project.base_url = project
+        .servers
+        .first()
+        .map(|server| server.resolve(&Default::default()));
 
     // Detect global security schemes
     let security_schemes = extract_security_schemes(&spec);
@@ -48,14 +64,19 @@ pub fn parse_openapi(spec_path: &Path) -> Result<WorkspaceProject> {
     // Parse paths
     if let Some(paths) = spec.get("paths").and_then(|p| p.as_object()) {
         for (path, methods) in paths {
+            let methods = resolve_local(&spec, methods);
             if let Some(methods_obj) = methods.as_object() {
                 for (method, operation) in methods_obj {
                     // Skip non-HTTP method keys like "parameters"
                     if !is_http_method(method) {
                         continue;
                     }
+                    let operation = resolve_local(&spec, operation);
 
                     let mut endpoint = DiscoveredEndpoint::new(method, path);
+                    endpoint.servers = parse_servers(
+                        operation.get("servers").or_else(|| methods.get("servers")),
+                    );
 
                     // Extract operation details
                     if let Some(op) = operation.as_object() {
@@ -88,7 +109,7 @@ pub fn parse_openapi(spec_path: &Path) -> Result<WorkspaceProject> {
                         // Parameters
                         if let Some(params) = op.get("parameters").and_then(|p| p.as_array()) {
                             for param in params {
-                                if let Some(p) = parse_parameter(param) {
+                                if let Some(p) = parse_parameter(&spec, param) {
                                     endpoint.parameters.push(p);
                                 }
                             }
@@ -97,7 +118,7 @@ pub fn parse_openapi(spec_path: &Path) -> Result<WorkspaceProject> {
                         // Also check path-level parameters
                         if let Some(params) = methods.get("parameters").and_then(|p| p.as_array()) {
                             for param in params {
-                                if let Some(p) = parse_parameter(param) {
+                                if let Some(p) = parse_parameter(&spec, param) {
                                     // Don't duplicate
                                     if !endpoint.parameters.iter().any(|ep| ep.name == p.name) {
                                         endpoint.parameters.push(p);
@@ -107,8 +128,30 @@ pub fn parse_openapi(spec_path: &Path) -> Result<WorkspaceProject> {
                         }
 
                         // Request body
-                        if let Some(body) = op.get("requestBody") {
-                            endpoint.body = parse_request_body(body);
+                        if swagger2 {
+                            let params = op
+                                .get("parameters")
+                                .into_iter()
+                                .chain(methods.get("parameters"))
+                                .filter_map(|p| p.as_array())
+                                .flatten()
+                                .map(|p| resolve_local(&spec, p))
+                                .collect::<Vec<_>>();
+                            let consumes = media_types(op.get("consumes").or(spec.get("consumes")));
+                            endpoint.body = swagger_body(&spec, &params, &consumes);
+                        } else if let Some(body) = op.get("requestBody") {
+                            endpoint.body = parse_request_body(&spec, resolve_local(&spec, body));
+                        }
+
+                        // Responses
+                        let produces = media_types(op.get("produces").or(spec.get("produces")));
+                        if let Some(responses) = op.get("responses").and_then(|r| r.as_object()) {
+                            endpoint.responses = responses
+                                .iter()
+                                .map(|(status, response)| {
+                                    parse_response(status, resolve_local(&spec, response), &produces)
+                                })
+                                .collect();
                         }
 
                         // Security (operation-level overrides global)
@@ -126,9 +169,192 @@ pub fn parse_openapi(spec_path: &Path) -> Result<WorkspaceProject> {
         }
     }
 
+    project.spec = Some(spec);
     Ok(project)
 }
 
+/// Follow `$ref`s to a path item, parameter, request body, response or
+/// security scheme within the spec, stopping at a cycle or a ref that
+/// cannot be resolved; schemas keep their `$ref`s and are resolved when used
+fn resolve_local<'a>(spec: &'a Value, value: &'a Value) -> &'a Value {
+    let mut current = value;
+    let mut followed: Vec<&str> = Vec::new();
+    while let Some(reference) = current.get("$ref").and_then(|r| r.as_str()) {
+        if followed.contains(&reference) {
+            break;
+        }
+        followed.push(reference);
+        match resolve_ref(spec, reference) {
+            Some(target) => current = target,
+            None => break,
+        }
+    }
+    current
+}
+
+/// The target of a local `$ref` such as `#/components/schemas/User`
+pub fn resolve_ref<'a>(spec: &'a Value, reference: &str) -> Option<&'a Value> {
+    spec.pointer(reference.strip_prefix('#')?)
+}
+
+/// The JSON media type of a `content` map, or else the first one
+fn preferred_content(content: &serde_json::Map<String, Value>) -> Option<(&str, &Value)> {
+    let types: Vec<&str> = content.keys().map(String::as_str).collect();
+    let preferred = preferred_type(&types)?;
+    content.get_key_value(preferred).map(|(ct, media)| (ct.as_str(), media))
+}
+
+/// `application/json`, else a `+json` type, else the first one
+fn preferred_type<'a>(types: &[&'a str]) -> Option<&'a str> {
+    types
+        .iter()
+        .find(|ct| **ct == "application/json")
+        .or_else(|| types.iter().find(|ct| ct.ends_with("+json")))
+        .or_else(|| types.first())
+        .copied()
+}
+
+/// A Swagger 2.0 `consumes` or `produces` list
+fn media_types(list: Option<&Value>) -> Vec<&str> {
+    list.and_then(|l| l.as_array())
+        .map(|l| l.iter().filter_map(|ct| ct.as_str()).collect())
+        .unwrap_or_default()
+}
+
+/// A response with its schema under `content` (OpenAPI 3) or directly,
+/// with the media types from `produces` (Swagger 2.0)
+fn parse_response(status: &str, response: &Value, produces: &[&str]) -> ResponseSpec {
+    let (content_type, schema) = match response.get("schema") {
+        Some(schema) => (preferred_type(produces), Some(schema)),
+        None => {
+            let media = response
+                .get("content")
+                .and_then(|c| c.as_object())
+                .and_then(preferred_content);
+            (media.map(|(ct, _)| ct), media.and_then(|(_, media)| media.get("schema")))
+        }
+    };
+    ResponseSpec {
+        status: status.to_string(),
+        description: response
+            .get("description")
+            .and_then(|d| d.as_str())
+            .map(String::from),
+        content_type: content_type.map(String::from),
+        schema: schema.cloned(),
+    }
+}
+
+/// A `servers` list, skipping entries without a URL
+fn parse_servers(servers: Option<&Value>) -> Vec<ServerSpec> {
+    let text = |value: &Value, key: &str| value.get(key).and_then(|v| v.as_str()).map(String::from);
+    servers
+        .and_then(|s| s.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|server| {
+            let variables = server
+                .get("variables")
+                .and_then(|v| v.as_object())
+                .into_iter()
+                .flatten()
+                .map(|(name, variable)| {
+                    let choices: Vec<String> = variable
+                        .get("enum")
+                        .and_then(|e| e.as_array())
+                        .into_iter()
+                        .flatten()
+                        .map(|choice| match choice {
+                            Value::String(s) => s.clone(),
+                            other => other.to_string(),
+                        })
+                        .collect();
+                    ServerVariable {
+                        name: name.clone(),
+                        default: text(variable, "default")
+                            .or_else(|| choices.first().cloned())
+                            .unwrap_or_default(),
+                        choices,
+                        description: text(variable, "description"),
+                    }
+                })
+                .collect();
+            Some(ServerSpec {
+                url: text(server, "url")?,
+                description: text(server, "description"),
+                variables,
+            })
+        })
+        .collect()
+}
+
+/// `scheme://host/basePath` of a Swagger 2.0 spec, preferring HTTPS.
+/// Without a host the API is served from wherever the spec is, so only
+/// `basePath` is kept, as a relative server; `None` without either.
+fn swagger_base_url(spec: &Value) -> Option<String> {
+    let base_path = spec.get("basePath").and_then(|b| b.as_str()).unwrap_or("");
+    let base_path = base_path.trim_end_matches('/');
+    let Some(host) = spec.get("host").and_then(|h| h.as_str()) else {
+        return Some(base_path.to_string()).filter(|path| !path.is_empty());
+    };
+    let schemes = media_types(spec.get("schemes"));
+    let scheme = if schemes.contains(&"https") {
+        "https"
+    } else {
+        schemes.first().copied().unwrap_or("https")
+    };
+    Some(format!("{}://{}{}", scheme, host, base_path))
+}
+
+/// The request body of a Swagger 2.0 operation: its `in: body` parameter,
+/// or its `formData` parameters as a form
+fn swagger_body(spec: &Value, params: &[&Value], consumes: &[&str]) -> Option<BodySchema> {
+    let located = |location: &'static str| {
+        params
+            .iter()
+            .filter(move |p| p.get("in").and_then(|i| i.as_str()) == Some(location))
+    };
+    let is_required = |p: &Value| p.get("required").and_then(|r| r.as_bool()).unwrap_or(false);
+
+    if let Some(body) = located("body").next() {
+        let media = json!({ "schema": body.get("schema").cloned().unwrap_or_else(|| json!({})) });
+        let content_type = preferred_type(consumes).unwrap_or("application/json");
+        return Some(request_body(spec, content_type, &media, is_required(body)));
+    }
+
+    let fields: Vec<&&Value> = located("formData").collect();
+    if fields.is_empty() {
+        return None;
+    }
+    let multipart = consumes.contains(&"multipart/form-data")
+        || fields.iter().any(|p| p.get("type").and_then(|t| t.as_str()) == Some("file"));
+    let mut properties = serde_json::Map::new();
+    for field in &fields {
+        let Some(name) = field.get("name").and_then(|n| n.as_str()) else {
+            continue;
+        };
+        let schema: serde_json::Map<String, Value> = ["type", "format", "enum", "default", "items"]
+            .iter()
+            .filter_map(|key| field.get(*key).map(|v| (key.to_string(), v.clone())))
+            .collect();
+        properties.insert(name.to_string(), Value::Object(schema));
+    }
+    let required: Vec<&str> = fields
+        .iter()
+        .filter(|p| is_required(p))
+        .filter_map(|p| p.get("name").and_then(|n| n.as_str()))
+        .collect();
+    let media = json!({
+        "schema": { "type": "object", "properties": properties, "required": required }
+    });
+    let content_type = if multipart {
+        "multipart/form-data"
+    } else {
+        "application/x-www-form-urlencoded"
+    };
+    Some(request_body(spec, content_type, &media, !required.is_empty()))
+}
+
 fn is_http_method(s: &str) -> bool {
     matches!(
         s.to_lowercase().as_str(),
@@ -149,6 +375,7 @@ fn extract_security_schemes(spec: &Value) -> Vec<(String, AuthRequirement)> {
         .and_then(|s| s.as_object())
     {
         for (name, scheme) in sec_schemes {
+            let scheme = resolve_local(spec, scheme);
             let scheme_type = scheme.get("type").and_then(|t| t.as_str()).unwrap_or("");
             let _scheme_in = scheme.get("in").and_then(|i| i.as_str()).unwrap_or("");
             let scheme_name = scheme.get("name").and_then(|n| n.as_str()).unwrap_or("");
@@ -165,6 +392,8 @@ fn extract_security_schemes(spec: &Value) -> Vec<(String, AuthRequirement)> {
                 "apiKey" => AuthRequirement::ApiKey {
                     header: scheme_name.to_string(),
                 },
+                // Swagger 2.0
+                "basic" => AuthRequirement::Basic,
                 "oauth2" => AuthRequirement::OAuth2,
                 "openIdConnect" => AuthRequirement::OAuth2,
                 _ => AuthRequirement::Custom(scheme_type.to_string()),
@@ -212,7 +441,8 @@ fn extract_security_requirement_from(
     AuthRequirement::None
 }
 
-fn parse_parameter(param: &Value) -> Option<Parameter> {
+fn parse_parameter(spec: &Value, param: &Value) -> Option<Parameter> {
+    let param = resolve_local(spec, param);
     let name = param.get("name")?.as_str()?.to_string();
     let location = match param.get("in")?.as_str()? {
         "path" => ParameterLocation::Path,
@@ -227,9 +457,11 @@ fn parse_parameter(param: &Value) -> Option<Parameter> {
         .and_then(|r| r.as_bool())
         .unwrap_or(false);
 
-    let param_type = param
-        .get("schema")
-        .and_then(|s| s.get("type"))
+    let schema = param.get("schema").map(|s| resolve_local(spec, s));
+    // Swagger 2.0 has the type and default on the parameter itself
+    let param_type = schema
+        .unwrap_or(param)
+        .get("type")
         .and_then(|t| t.as_str())
         .unwrap_or("string")
         .to_string();
@@ -239,9 +471,9 @@ fn parse_parameter(param: &Value) -> Option<Parameter> {
         .and_then(|d| d.as_str())
         .map(String::from);
 
-    let default = param
-        .get("schema")
-        .and_then(|s| s.get("default"))
+    let default = schema
+        .unwrap_or(param)
+        .get("default")
         .map(|d| d.to_string());
 
     Some(Parameter {
@@ -254,43 +486,72 @@ fn parse_parameter(param: &Value) -> Option<Parameter> {
     })
 }
 
-fn parse_request_body(body: &Value) -> Option<BodySchema> {
+fn parse_request_body(spec: &Value, body: &Value) -> Option<BodySchema> {
     let required = body
         .get("required")
         .and_then(|r| r.as_bool())
         .unwrap_or(false);
 
-    // Get content types
-    if let Some(content) = body.get("content").and_then(|c| c.as_object()) {
-        // Prefer application/json
-        let (content_type, schema_obj) = if let Some(json) = content.get("application/json") {
-            ("application/json", json)
-        } else if let Some((ct, obj)) = content.iter().next() {
-            (ct.as_str(), obj)
-        } else {
-            return None;
-        };
+    // Get content types, preferring JSON
+    let content = body.get("content").and_then(|c| c.as_object())?;
+    let (content_type, media) = preferred_content(content)?;
+    Some(request_body(spec, content_type, media, required))
+}
 
-        let schema_name = schema_obj
-            .get("schema")
-            .and_then(|s| s.get("$ref"))
-            .and_then(|r| r.as_str())
-            .map(|r| r.split('/').next_back().unwrap_or("").to_string());
-
-        let example = schema_obj
-            .get("example")
-            .or_else(|| schema_obj.get("schema").and_then(|s| s.get("example")))
-            .map(|e| serde_json::to_string_pretty(e).unwrap_or_default());
-
-        return Some(BodySchema {
-            content_type: content_type.to_string(),
-            schema_name,
-            required,
-            example,
-        });
+/// A request body from its media type object (`schema`, `example`,
+/// `examples`)
+fn request_body(spec: &Value, content_type: &str, media: &Value, required: bool) -> BodySchema {
+    let schema = media.get("schema");
+
+    let schema_name = schema
+        .and_then(|s| s.get("$ref"))
+        .and_then(|r| r.as_str())
+        .map(|r| r.split('/').next_back().unwrap_or("").to_string());
+
+    // A literal example, else one synthesized from the schema
+    let example = media
+        .get("example")
+        .or_else(|| {
+            media
+                .get("examples")
+                .and_then(|e| e.as_object())
+                .and_then(|e| e.values().next())
+                .map(|e| resolve_local(spec, e))
+                .and_then(|e| e.get("value"))
+        })
+        .cloned()
+        .or_else(|| schema.and_then(|s| example::example_for(s, spec)))
+        .and_then(|e| example_body(content_type, &e));
+
+    BodySchema {
+        content_type: content_type.to_string(),
+        schema_name,
+        required,
+        example,
+        schema: schema.cloned(),
     }
+}
 
-    None
+/// An example value as a body of the given content type; `None` for
+/// multipart bodies, which the editor cannot hold
+fn example_body(content_type: &str, example: &Value) -> Option<String> {
+    let body = match example {
+        _ if content_type.starts_with("multipart/") => return None,
+        Value::Object(fields) if content_type == "application/x-www-form-urlencoded" => fields
+            .iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                format!("{}={}", percent_encode(name), percent_encode(&value))
+            })
+            .collect::<Vec<_>>()
+            .join("&"),
+        Value::String(text) if !content_type.contains("json") => text.clone(),
+        other => serde_json::to_string_pretty(other).unwrap_or_default(),
+    };
+    Some(body)
 }
 
 #[cfg(test)]
@@ -313,17 +574,254 @@ paths:
           description: OK
     post:
       summary: Create user
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: '#/components/schemas/User'
       responses:
         201:
           description: Created
+          content:
+            application/json:
+              schema:
+                $ref: '#/components/schemas/User'
+        default:
+          $ref: '#/components/responses/Error'
+components:
+  schemas:
+    User:
+      type: object
+      properties:
+        email:
+          type: string
+          format: email
+        role:
+          type: string
+          enum: [admin, member]
+  responses:
+    Error:
+      description: Failure
 "#;
 
         let temp_dir = tempfile::tempdir().unwrap();
         let spec_path = temp_dir.path().join("openapi.yaml");
         std::fs::write(&spec_path, yaml).unwrap();
 
-        let project = parse_openapi(&spec_path).unwrap();
+        let project = parse_openapi(&spec_path, temp_dir.path()).unwrap();
         assert_eq!(project.title, Some("Test API".to_string()));
         assert_eq!(project.endpoints.len(), 2);
+
+        let create = &project.endpoints[1];
+        let body = create.body.as_ref().unwrap();
+        assert_eq!(body.schema_name.as_deref(), Some("User"));
+        let example: Value = serde_json::from_str(body.example.as_deref().unwrap()).unwrap();
+        assert_eq!(example, serde_json::json!({ "email": "user@example.com", "role": "admin" }));
+        assert_eq!(create.responses.len(), 2);
+        let created = create.response_for(201).unwrap();
+        assert_eq!(created.content_type.as_deref(), Some("application/json"));
+        assert_eq!(created.schema, Some(serde_json::json!({ "$ref": "#/components/schemas/User" })));
+        assert_eq!(create.response_for(500).unwrap().description.as_deref(), Some("Failure"));
+        assert!(project.spec.is_some());
+    }
+
+    #[test]
+    fn test_parse_split_openapi() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, content: &str| {
+            let path = temp_dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write(
+            "openapi.yaml",
+            r#"
+openapi: 3.0.0
+paths:
+  /pets:
+    $ref: 'paths/pets.yaml'
+components:
+  securitySchemes:
+    token:
+      $ref: 'components/security.yaml#/token'
+security:
+  - token: []
+"#,
+        );
+        write(
+            "paths/pets.yaml",
+            r#"
+post:
+  parameters:
+    - $ref: '../components/parameters.yaml#/dryRun'
+  requestBody:
+    content:
+      application/json:
+        schema:
+          $ref: '../components/schemas/Pet.yaml'
+"#,
+        );
+        write(
+            "components/parameters.yaml",
+            "dryRun:\n  name: dry_run\n  in: query\n  schema:\n    type: boolean\n",
+        );
+        write("components/security.yaml", "token:\n  type: http\n  scheme: bearer\n");
+        write(
+            "components/schemas/Pet.yaml",
+            "type: object\nproperties:\n  name:\n    type: string\n",
+        );
+
+        let project = parse_openapi(&temp_dir.path().join("openapi.yaml"), temp_dir.path()).unwrap();
+        let endpoint = &project.endpoints[0];
+        assert_eq!(endpoint.parameters[0].name, "dry_run");
+        assert_eq!(endpoint.parameters[0].param_type, "boolean");
+        assert_eq!(endpoint.auth, AuthRequirement::Bearer);
+        let body = endpoint.body.as_ref().unwrap();
+        assert_eq!(body.schema_name.as_deref(), Some("Pet"));
+        assert_eq!(body.example.as_deref(), Some("{\n  \"name\": \"string\"\n}"));
+    }
+
+    #[test]
+    fn test_parse_swagger2() {
+        let json = r##"{
+  "swagger": "2.0",
+  "host": "legacy.example.com",
+  "basePath": "/v1/",
+  "schemes": ["http", "https"],
+  "consumes": ["application/json"],
+  "produces": ["application/json"],
+  "securityDefinitions": { "basicAuth": { "type": "basic" } },
+  "security": [{ "basicAuth": [] }],
+  "paths": {
+    "/pets": {
+      "post": {
+        "parameters": [
+          { "name": "pet", "in": "body", "required": true, "schema": { "$ref": "#/definitions/Pet" } },
+          { "name": "limit", "in": "query", "type": "integer", "default": 10 }
+        ],
+        "responses": { "200": { "description": "OK", "schema": { "$ref": "#/definitions/Pet" } } }
+      }
+    },
+    "/pets/{id}/photo": {
+      "put": {
+        "consumes": ["application/x-www-form-urlencoded"],
+        "parameters": [
+          { "name": "id", "in": "path", "required": true, "type": "string" },
+          { "name": "caption", "in": "formData", "type": "string", "required": true },
+          { "name": "public", "in": "formData", "type": "boolean" }
+        ],
+        "responses": { "204": { "description": "Saved" } }
+      }
+    }
+  },
+  "definitions": {
+    "Pet": { "type": "object", "properties": { "name": { "type": "string", "example": "Rex" } } }
+  }
+}"##;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let spec_path = temp_dir.path().join("swagger.json");
+        std::fs::write(&spec_path, json).unwrap();
+
+        let project = parse_openapi(&spec_path, temp_dir.path()).unwrap();
+        assert_eq!(project.base_url.as_deref(), Some("https://legacy.example.com/v1"));
+
+        let create = &project.endpoints[0];
+        assert_eq!(create.auth, AuthRequirement::Basic);
+        assert_eq!(create.parameters.len(), 1);
+        assert_eq!(create.parameters[0].param_type, "integer");
+        assert_eq!(create.parameters[0].default.as_deref(), Some("10"));
+        let body = create.body.as_ref().unwrap();
+        assert_eq!((body.content_type.as_str(), body.required), ("application/json", true));
+        assert_eq!(body.example.as_deref(), Some("{\n  \"name\": \"Rex\"\n}"));
+        let ok = create.response_for(200).unwrap();
+        assert_eq!(ok.content_type.as_deref(), Some("application/json"));
+        assert!(ok.schema.is_some());
+
+        let photo = &project.endpoints[1];
+        let form = photo.body.as_ref().unwrap();
+        assert_eq!(form.content_type, "application/x-www-form-urlencoded");
+        assert_eq!(form.example.as_deref(), Some("caption=string&public=true"));
+    }
+
+    #[test]
+    fn test_parse_swagger2_without_host() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let spec_path = temp_dir.path().join("swagger.yaml");
+        let spec = "swagger: '2.0'\nbasePath: /api/v2/\npaths:\n  /pets:\n    get: {}\n";
+        std::fs::write(&spec_path, spec).unwrap();
+
+        let project = parse_openapi(&spec_path, temp_dir.path()).unwrap();
+        assert_eq!(project.servers.len(), 1);
+        assert_eq!(project.servers[0].url, "/api/v2");
+        assert_eq!(project.base_url.as_deref(), Some("/api/v2"));
+
+        std::fs::write(&spec_path, "swagger: '2.0'\npaths: {}\n").unwrap();
+        assert!(parse_openapi(&spec_path, temp_dir.path()).unwrap().servers.is_empty());
+    }
+
+    #[test]
+    fn test_parse_servers_and_variables() {
+        let yaml = r#"
+openapi: 3.0.0
+info:
+  title: Servers
+  version: 1.0.0
+servers:
+  - url: https://{region}.api.example.com:{port}/v1
+    description: Production
+    variables:
+      region:
+        default: eu
+        enum: [eu, us]
+      port:
+        default: "443"
+  - url: http://localhost:8000
+paths:
+  /files:
+    servers:
+      - url: https://files.example.com
+    get:
+      responses:
+        "200":
+          description: OK
+    post:
+      servers:
+        - url: https://upload.example.com
+      responses:
+        "201":
+          description: Created
+  /users:
+    get:
+      responses:
+        "200":
+          description: OK
+"#;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let spec_path = temp_dir.path().join("openapi.yaml");
+        std::fs::write(&spec_path, yaml).unwrap();
+
+        let project = parse_openapi(&spec_path, temp_dir.path()).unwrap();
+        assert_eq!(project.servers.len(), 2);
+        assert_eq!(project.base_url.as_deref(), Some("https://eu.api.example.com:443/v1"));
+        let production = &project.servers[0];
+        assert_eq!(production.description.as_deref(), Some("Production"));
+        let region = production.variables.iter().find(|v| v.name == "region").unwrap();
+        assert_eq!(region.choices, vec!["eu", "us"]);
+
+        let mut chosen = std::collections::HashMap::new();
+        chosen.insert("region".to_string(), "us".to_string());
+        chosen.insert("port".to_string(), "8443".to_string());
+        assert_eq!(production.resolve(&chosen), "https://us.api.example.com:8443/v1");
+        // Values outside the enum fall back to the default
+        chosen.insert("region".to_string(), "apac".to_string());
+        assert_eq!(production.resolve(&chosen), "https://eu.api.example.com:8443/v1");
+
+        let server_of = |method: &str, path: &str| {
+            let endpoint = project.endpoints.iter().find(|e| e.method == method && e.path == path).unwrap();
+            endpoint.servers.first().map(|s| s.url.clone())
+        };
+        assert_eq!(server_of("GET", "/files").as_deref(), Some("https://files.example.com"));
+        assert_eq!(server_of("POST", "/files").as_deref(), Some("https://upload.example.com"));
+        assert_eq!(server_of("GET", "/users"), None);
     }
 }
@@ -0,0 +1,306 @@
+//! Loading of OpenAPI specs split over several files
+//!
+//! Every `$ref` into another file (`paths/users.yaml`,
+//! `schemas.yaml#/User`) is rewritten into a local ref, with its target
+//! copied under `x-bundled` in the root document. Targets are named before
+//! they are rewritten themselves, so refs that lead back to a document
+//! already being bundled end in the existing copy instead of looping.
+//! Only files inside the workspace are read. Refs to URLs, to files outside
+//! the workspace and to targets that cannot be read are left as they are,
+//! each with a warning.
+
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Key of the root document the targets of external refs are copied under
+pub const BUNDLED_KEY: &str = "x-bundled";
+
+/// Read a spec and bundle the files under `workspace_root` its `$ref`s
+/// point to into it. Also returns a warning for every ref left unresolved.
+pub fn load_bundled(spec_path: &Path, workspace_root: &Path) -> Result<(Value, Vec<String>)> {
+    let mut root = read_document(spec_path)?;
+    let root_path = fs::canonicalize(spec_path).unwrap_or_else(|_| spec_path.to_path_buf());
+    let workspace_root = fs::canonicalize(workspace_root)
+        .ok()
+        .filter(|dir| root_path.starts_with(dir))
+        .unwrap_or_else(|| root_path.parent().unwrap_or(Path::new("/")).to_path_buf());
+    let mut bundler = Bundler {
+        workspace_root,
+        root_path: root_path.clone(),
+        documents: HashMap::new(),
+        names: HashMap::new(),
+        bundled: Map::new(),
+        warnings: Vec::new(),
+    };
+    bundler.rewrite(&mut root, &root_path);
+    if !bundler.bundled.is_empty() {
+        if let Some(object) = root.as_object_mut() {
+            object.insert(BUNDLED_KEY.to_string(), Value::Object(bundler.bundled));
+        }
+    }
+    Ok((root, bundler.warnings))
+}
+
+/// A JSON or YAML document, by file extension
+fn read_document(path: &Path) -> Result<Value> {
+    let content = fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    let document = if path.extension().map(|e| e == "json").unwrap_or(false) {
+        serde_json::from_str(&content)?
+    } else {
+        serde_yaml::from_str(&content)?
+    };
+    Ok(document)
+}
+
+struct Bundler {
+    root_path: PathBuf,
+    /// Files outside this directory are not read
+    workspace_root: PathBuf,
+    /// Documents read so far, by canonical path
+    documents: HashMap<PathBuf, Value>,
+    /// Bundled name of each external target, by file and JSON pointer
+    names: HashMap<(PathBuf, String), String>,
+    bundled: Map<String, Value>,
+    warnings: Vec<String>,
+}
+
+impl Bundler {
+    /// Rewrite the refs in `value`, which comes from the document at `file`
+    fn rewrite(&mut self, value: &mut Value, file: &Path) {
+        match value {
+            Value::Object(object) => {
+                let reference = object.get("$ref").and_then(|r| r.as_str()).map(String::from);
+                if let Some(local) = reference.and_then(|r| self.localize(&r, file)) {
+                    object.insert("$ref".to_string(), Value::String(local));
+                }
+                for (key, child) in object.iter_mut() {
+                    if key != "$ref" {
+                        self.rewrite(child, file);
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.rewrite(item, file);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The local ref that replaces `reference`, or `None` to keep it
+    fn localize(&mut self, reference: &str, file: &Path) -> Option<String> {
+        let (location, pointer) = reference.split_once('#').unwrap_or((reference, ""));
+        if location.contains("://") {
+            self.warn(file, reference, "remote refs are not fetched");
+            return None;
+        }
+        let target_file = if location.is_empty() {
+            file.to_path_buf()
+        } else {
+            let joined = file.parent().unwrap_or(Path::new(".")).join(location);
+            let Ok(path) = fs::canonicalize(joined) else {
+                self.warn(file, reference, "file not found");
+                return None;
+            };
+            if !path.starts_with(&self.workspace_root) {
+                self.warn(file, reference, "file is outside the workspace");
+                return None;
+            }
+            path
+        };
+        if target_file == self.root_path {
+            // Already local, or a path back into the root document
+            return (!location.is_empty()).then(|| format!("#{}", pointer));
+        }
+
+        let key = (target_file.clone(), pointer.to_string());
+        if let Some(name) = self.names.get(&key) {
+            return Some(bundled_ref(name));
+        }
+        if !self.documents.contains_key(&target_file) {
+            let document = match read_document(&target_file) {
+                Ok(document) => document,
+                Err(e) => {
+                    self.warn(file, reference, &format!("{:#}", e));
+                    return None;
+                }
+            };
+            self.documents.insert(target_file.clone(), document);
+        }
+        let Some(target) = self.documents[&target_file].pointer(pointer) else {
+            self.warn(file, reference, "no such location in the file");
+            return None;
+        };
+        let mut target = target.clone();
+
+        let name = self.unique_name(&target_file, pointer);
+        self.names.insert(key, name.clone());
+        self.rewrite(&mut target, &target_file);
+        self.bundled.insert(name.clone(), target);
+        Some(bundled_ref(&name))
+    }
+
+    /// Note a ref in `file` that is left unresolved
+    fn warn(&mut self, file: &Path, reference: &str, reason: &str) {
+        let file = file.strip_prefix(&self.workspace_root).unwrap_or(file);
+        let warning = format!("{}: $ref '{}' not bundled ({})", file.display(), reference, reason);
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+    }
+
+    /// The last pointer segment or the file name, made unique
+    fn unique_name(&self, file: &Path, pointer: &str) -> String {
+        let base = match pointer.rsplit('/').next() {
+            Some(segment) if !segment.is_empty() => segment.replace("~1", "/").replace("~0", "~"),
+            _ => file
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_else(|| "ref".to_string()),
+        };
+        let taken = |name: &str| self.names.values().any(|n| n == name);
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while taken(&name) {
+            name = format!("{}_{}", base, suffix);
+            suffix += 1;
+        }
+        name
+    }
+}
+
+fn bundled_ref(name: &str) -> String {
+    format!("#/{}/{}", BUNDLED_KEY, name.replace('~', "~0").replace('/', "~1"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_bundles_relative_files_with_cycles() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, content: &str| {
+            let path = dir.path().join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write(
+            "openapi.yaml",
+            "paths:\n  /users:\n    $ref: 'paths/users.yaml'\ncomponents:\n  schemas:\n    Id:\n      type: integer\n",
+        );
+        write(
+            "paths/users.yaml",
+            "get:\n  responses:\n    '200':\n      content:\n        application/json:\n          schema:\n            $ref: '../schemas.yaml#/User'\n",
+        );
+        write(
+            "schemas.yaml",
+            "User:\n  properties:\n    id:\n      $ref: 'openapi.yaml#/components/schemas/Id'\n    manager:\n      $ref: '#/User'\n",
+        );
+
+        let (spec, warnings) = load_bundled(&dir.path().join("openapi.yaml"), dir.path()).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(spec.pointer("/paths/~1users/$ref"), Some(&json!("#/x-bundled/users")));
+        assert_eq!(
+            spec.pointer("/x-bundled/users/get/responses/200/content/application~1json/schema/$ref"),
+            Some(&json!("#/x-bundled/User"))
+        );
+        let user = spec.pointer("/x-bundled/User/properties").unwrap();
+        assert_eq!(user["id"]["$ref"], "#/components/schemas/Id");
+        assert_eq!(user["manager"]["$ref"], "#/x-bundled/User");
+    }
+
+    #[test]
+    fn test_nested_refs_and_name_collisions() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, content: &str| {
+            let path = dir.path().join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write(
+            "openapi.yaml",
+            "components:
+  schemas:
+    Staff:
+      $ref: 'v1/people/staff.yaml#/User'
+    Customer:
+      $ref: 'v1/customers.yaml#/User'
+",
+        );
+        write("v1/people/staff.yaml", "User:
+  properties:
+    badge:
+      $ref: '../common/badge.yaml'
+");
+        write("v1/common/badge.yaml", "type: string
+");
+        write("v1/customers.yaml", "User:
+  type: object
+");
+
+        let (spec, warnings) = load_bundled(&dir.path().join("openapi.yaml"), dir.path()).unwrap();
+        assert!(warnings.is_empty());
+        let schemas = spec.pointer("/components/schemas").unwrap();
+        // Keys are walked in order, so Customer's User is bundled first
+        assert_eq!(schemas["Customer"]["$ref"], "#/x-bundled/User");
+        assert_eq!(schemas["Staff"]["$ref"], "#/x-bundled/User_2");
+        assert_eq!(spec.pointer("/x-bundled/User/type"), Some(&json!("object")));
+        assert_eq!(spec.pointer("/x-bundled/User_2/properties/badge/$ref"), Some(&json!("#/x-bundled/badge")));
+        assert_eq!(spec.pointer("/x-bundled/badge/type"), Some(&json!("string")));
+    }
+
+    #[test]
+    fn test_refs_are_bounded_by_the_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path().join("project");
+        fs::create_dir_all(workspace.join("api")).unwrap();
+        fs::create_dir_all(workspace.join("common")).unwrap();
+        fs::write(dir.path().join("secret.yaml"), "token: hunter2\n").unwrap();
+        fs::write(workspace.join("common/schemas.yaml"), "Error:\n  type: object\n").unwrap();
+        fs::write(workspace.join("common/broken.yaml"), "Error: [unclosed\n").unwrap();
+        fs::write(
+            workspace.join("api/openapi.yaml"),
+            "components:
+  schemas:
+    Error:
+      $ref: '../common/schemas.yaml#/Error'
+    Gone:
+      $ref: '../common/schemas.yaml#/Gone'
+    Broken:
+      $ref: '../common/broken.yaml#/Error'
+    Missing:
+      $ref: 'nowhere.yaml#/Thing'
+    Outside:
+      $ref: '../../secret.yaml'
+    Remote:
+      $ref: 'https://example.com/schemas.yaml#/Error'
+",
+        )
+        .unwrap();
+
+        let (spec, warnings) = load_bundled(&workspace.join("api/openapi.yaml"), &workspace).unwrap();
+        let schemas = spec.pointer("/components/schemas").unwrap();
+        assert_eq!(schemas["Error"]["$ref"], "#/x-bundled/Error");
+        assert_eq!(spec.pointer("/x-bundled/Error/type"), Some(&json!("object")));
+        assert_eq!(schemas["Outside"]["$ref"], "../../secret.yaml");
+        assert_eq!(spec.pointer("/x-bundled").unwrap().as_object().unwrap().len(), 1);
+
+        assert_eq!(warnings.len(), 5);
+        assert!(warnings[0].starts_with("api/openapi.yaml: $ref '../common/broken.yaml#/Error' not bundled ("));
+        assert_eq!(
+            warnings[1..],
+            [
+                "api/openapi.yaml: $ref '../common/schemas.yaml#/Gone' not bundled (no such location in the file)",
+                "api/openapi.yaml: $ref 'nowhere.yaml#/Thing' not bundled (file not found)",
+                "api/openapi.yaml: $ref '../../secret.yaml' not bundled (file is outside the workspace)",
+                "api/openapi.yaml: $ref 'https://example.com/schemas.yaml#/Error' not bundled (remote refs are not fetched)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,403 @@
+//! Response validation against OpenAPI response schemas
+//!
+//! Covers the parts of JSON Schema that specs use to describe payloads:
+//! `type` (with `nullable`), `required`, `properties`,
+//! `additionalProperties`, `items`, `enum`, `const`, `allOf`/`anyOf`/`oneOf`
+//! and the length, size and range keywords. Formats are not checked.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::discovery::models::DiscoveredEndpoint;
+use crate::discovery::openapi;
+use crate::json_tree::field_path;
+
+/// `$ref`s followed in a row without descending into the value; a cycle
+/// that never reaches a value stops here
+const MAX_REF_CHAIN: usize = 32;
+
+/// A way the response differs from the spec
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violation {
+    /// JSONPath of the offending value; empty for the status
+    pub path: String,
+    pub message: String,
+}
+
+impl Violation {
+    fn new(path: &str, message: impl Into<String>) -> Self {
+        Violation {
+            path: path.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// Check a response against the endpoint's documented responses; `None`
+/// when the spec documents none, so there is nothing to check against
+pub fn validate_response(
+    endpoint: &DiscoveredEndpoint,
+    spec: Option<&Value>,
+    status: u16,
+    body: &str,
+) -> Option<Vec<Violation>> {
+    if endpoint.responses.is_empty() {
+        return None;
+    }
+    let Some(response) = endpoint.response_for(status) else {
+        let documented: Vec<&str> = endpoint.responses.iter().map(|r| r.status.as_str()).collect();
+        return Some(vec![Violation::new(
+            "",
+            format!("status {} is not documented (documented: {})", status, documented.join(", ")),
+        )]);
+    };
+    let Some(schema) = &response.schema else {
+        return Some(Vec::new());
+    };
+    if body.trim().is_empty() {
+        return Some(vec![Violation::new("$", "body is empty but the spec documents one")]);
+    }
+    if response.content_type.as_deref().is_some_and(|ct| !is_json(ct)) {
+        return Some(Vec::new());
+    }
+    let value: Value = match serde_json::from_str(body) {
+        Ok(value) => value,
+        Err(e) => return Some(vec![Violation::new("$", format!("body is not valid JSON: {}", e))]),
+    };
+    let mut validator = Validator {
+        root: spec,
+        patterns: HashMap::new(),
+        schema_problems: Vec::new(),
+    };
+    let mut violations = Vec::new();
+    validator.check(schema, &value, "$", 0, &mut violations);
+    violations.extend(validator.schema_problems);
+    Some(violations)
+}
+
+fn is_json(content_type: &str) -> bool {
+    let essence = content_type.split(';').next().unwrap_or("").trim();
+    essence == "application/json" || essence.ends_with("+json") || essence == "*/*"
+}
+
+struct Validator<'a> {
+    /// Document that local `$ref`s point into
+    root: Option<&'a Value>,
+    /// Compiled `pattern`s, `None` for ones the regex crate rejects
+    patterns: HashMap<&'a str, Option<Regex>>,
+    /// Problems with the schema itself, each reported once
+    schema_problems: Vec<Violation>,
+}
+
+impl<'a> Validator<'a> {
+    fn check(&mut self, schema: &'a Value, value: &Value, path: &str, refs: usize, out: &mut Vec<Violation>) {
+        if let Some(reference) = schema.get("$ref").and_then(|r| r.as_str()) {
+            if refs >= MAX_REF_CHAIN {
+                return;
+            }
+            match self.resolve(reference) {
+                Some(target) => self.check(target, value, path, refs + 1, out),
+                None => out.push(Violation::new(path, format!("cannot resolve {}", reference))),
+            }
+            return;
+        }
+
+        if value.is_null() && schema.get("nullable").and_then(|n| n.as_bool()) == Some(true) {
+            return;
+        }
+        if let Some(expected) = schema.get("type") {
+            let types: Vec<&str> = match expected {
+                Value::String(name) => vec![name.as_str()],
+                Value::Array(names) => names.iter().filter_map(|n| n.as_str()).collect(),
+                _ => Vec::new(),
+            };
+            if !types.is_empty() && !types.iter().any(|name| has_type(value, name)) {
+                out.push(Violation::new(
+                    path,
+                    format!("expected {}, got {}", types.join(" or "), type_name(value)),
+                ));
+                return;
+            }
+        }
+        if let Some(options) = schema.get("enum").and_then(|e| e.as_array()) {
+            if !options.contains(value) {
+                let listed: Vec<String> = options.iter().map(|o| o.to_string()).collect();
+                out.push(Violation::new(
+                    path,
+                    format!("{} is not one of {}", value, listed.join(", ")),
+                ));
+            }
+        }
+        if let Some(constant) = schema.get("const") {
+            if constant != value {
+                out.push(Violation::new(path, format!("expected {}, got {}", constant, value)));
+            }
+        }
+
+        self.check_combinators(schema, value, path, refs, out);
+
+        match value {
+            Value::Object(fields) => {
+                if let Some(required) = schema.get("required").and_then(|r| r.as_array()) {
+                    for name in required.iter().filter_map(|n| n.as_str()) {
+                        if !fields.contains_key(name) {
+                            out.push(Violation::new(path, format!("missing required field `{}`", name)));
+                        }
+                    }
+                }
+                let properties = schema.get("properties").and_then(|p| p.as_object());
+                for (name, field) in fields {
+                    let field_schema = properties.and_then(|p| p.get(name));
+                    let child = field_path(path, name);
+                    match (field_schema, schema.get("additionalProperties")) {
+                        (Some(field_schema), _) => self.check(field_schema, field, &child, 0, out),
+                        (None, Some(Value::Bool(false))) => {
+                            out.push(Violation::new(&child, "field is not in the schema"))
+                        }
+                        (None, Some(extra @ Value::Object(_))) => self.check(extra, field, &child, 0, out),
+                        (None, _) => {}
+                    }
+                }
+            }
+            Value::Array(items) => {
+                if let Some(item_schema) = schema.get("items").filter(|i| i.is_object()) {
+                    for (index, item) in items.iter().enumerate() {
+                        self.check(item_schema, item, &format!("{}[{}]", path, index), 0, out);
+                    }
+                }
+                check_bound(schema, "minItems", "maxItems", items.len(), "items", path, out);
+            }
+            Value::String(text) => {
+                check_bound(schema, "minLength", "maxLength", text.chars().count(), "characters", path, out);
+                if let Some(pattern) = schema.get("pattern").and_then(|p| p.as_str()) {
+                    if self.pattern(pattern, path).is_some_and(|regex| !regex.is_match(text)) {
+                        out.push(Violation::new(path, format!("does not match pattern {}", pattern)));
+                    }
+                }
+            }
+            Value::Number(number) => {
+                let Some(number) = number.as_f64() else {
+                    return;
+                };
+                if let Some(minimum) = schema.get("minimum").and_then(|m| m.as_f64()) {
+                    if number < minimum {
+                        out.push(Violation::new(path, format!("{} is less than the minimum {}", number, minimum)));
+                    }
+                }
+                if let Some(maximum) = schema.get("maximum").and_then(|m| m.as_f64()) {
+                    if number > maximum {
+                        out.push(Violation::new(path, format!("{} is more than the maximum {}", number, maximum)));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_combinators(&mut self, schema: &'a Value, value: &Value, path: &str, refs: usize, out: &mut Vec<Violation>) {
+        if let Some(all) = schema.get("allOf").and_then(|a| a.as_array()) {
+            for part in all {
+                self.check(part, value, path, refs, out);
+            }
+        }
+        if let Some(any) = schema.get("anyOf").and_then(|a| a.as_array()) {
+            if self.passing(any, value, path, refs) == 0 {
+                out.push(Violation::new(path, "matches none of the anyOf schemas"));
+            }
+        }
+        if let Some(one) = schema.get("oneOf").and_then(|o| o.as_array()) {
+            match self.passing(one, value, path, refs) {
+                1 => {}
+                0 => out.push(Violation::new(path, "matches none of the oneOf schemas")),
+                n => out.push(Violation::new(path, format!("matches {} oneOf schemas, expected one", n))),
+            }
+        }
+    }
+
+    /// How many of `parts` the value satisfies
+    fn passing(&mut self, parts: &'a [Value], value: &Value, path: &str, refs: usize) -> usize {
+        parts
+            .iter()
+            .filter(|part| {
+                let mut scratch = Vec::new();
+                self.check(part, value, path, refs, &mut scratch);
+                scratch.is_empty()
+            })
+            .count()
+    }
+
+    /// The compiled pattern, compiled on first use. A pattern the regex
+    /// crate rejects is reported once as a schema problem and not checked.
+    fn pattern(&mut self, pattern: &'a str, path: &str) -> Option<&Regex> {
+        let problems = &mut self.schema_problems;
+        self.patterns
+            .entry(pattern)
+            .or_insert_with(|| match Regex::new(pattern) {
+                Ok(regex) => Some(regex),
+                Err(_) => {
+                    problems.push(Violation::new(path, format!("schema pattern {} is invalid, not checked", pattern)));
+                    None
+                }
+            })
+            .as_ref()
+    }
+
+    fn resolve(&self, reference: &str) -> Option<&'a Value> {
+        openapi::resolve_ref(self.root?, reference)
+    }
+}
+
+fn check_bound(schema: &Value, min_key: &str, max_key: &str, len: usize, unit: &str, path: &str, out: &mut Vec<Violation>) {
+    let len = len as u64;
+    if let Some(min) = schema.get(min_key).and_then(|m| m.as_u64()) {
+        if len < min {
+            out.push(Violation::new(path, format!("has {} {}, at least {} expected", len, unit, min)));
+        }
+    }
+    if let Some(max) = schema.get(max_key).and_then(|m| m.as_u64()) {
+        if len > max {
+            out.push(Violation::new(path, format!("has {} {}, at most {} expected", len, unit, max)));
+        }
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::models::ResponseSpec;
+    use serde_json::json;
+
+    fn endpoint() -> (DiscoveredEndpoint, Value) {
+        let spec = json!({
+            "components": { "schemas": {
+                "User": {
+                    "type": "object",
+                    "required": ["id", "name"],
+                    "properties": {
+                        "id": { "type": "integer" },
+                        "name": { "type": "string", "minLength": 1 },
+                        "role": { "enum": ["admin", "member"] },
+                        "manager": { "$ref": "#/components/schemas/User" },
+                        "email": { "type": "string", "nullable": true }
+                    }
+                }
+            }}
+        });
+        let mut endpoint = DiscoveredEndpoint::new("GET", "/users/{id}");
+        endpoint.responses = vec![
+            ResponseSpec {
+                status: "200".to_string(),
+                description: None,
+                content_type: Some("application/json".to_string()),
+                schema: Some(json!({ "$ref": "#/components/schemas/User" })),
+            },
+            ResponseSpec {
+                status: "4XX".to_string(),
+                description: None,
+                content_type: None,
+                schema: None,
+            },
+        ];
+        (endpoint, spec)
+    }
+
+    #[test]
+    fn test_validate_response_body() {
+        let (endpoint, spec) = endpoint();
+        let valid = r#"{"id": 1, "name": "Ann", "email": null, "manager": {"id": 2, "name": "Bo"}}"#;
+        assert_eq!(validate_response(&endpoint, Some(&spec), 200, valid), Some(Vec::new()));
+
+        let invalid = r#"{"id": "1", "role": "owner", "manager": {"id": 2, "name": ""}}"#;
+        let found: Vec<String> = validate_response(&endpoint, Some(&spec), 200, invalid)
+            .unwrap()
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                "$: missing required field `name`",
+                "$.id: expected integer, got string",
+                "$.manager.name: has 0 characters, at least 1 expected",
+                "$.role: \"owner\" is not one of \"admin\", \"member\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_patterns() {
+        let mut endpoint = DiscoveredEndpoint::new("GET", "/tags");
+        endpoint.responses = vec![ResponseSpec {
+            status: "200".to_string(),
+            description: None,
+            content_type: Some("application/json".to_string()),
+            schema: Some(json!({
+                "type": "object",
+                "properties": {
+                    "tags": { "type": "array", "items": { "type": "string", "pattern": "^[a-z]+$" } },
+                    "codes": { "type": "array", "items": { "type": "string", "pattern": "(?<=x)[" } }
+                }
+            })),
+        }];
+        let body = r#"{"tags": ["ok", "Bad", "fine"], "codes": ["a", "b"]}"#;
+        let found: Vec<String> = validate_response(&endpoint, None, 200, body)
+            .unwrap()
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                "$.tags[1]: does not match pattern ^[a-z]+$",
+                "$.codes[0]: schema pattern (?<=x)[ is invalid, not checked",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_response_status() {
+        let (endpoint, spec) = endpoint();
+        assert_eq!(validate_response(&endpoint, Some(&spec), 404, "not found"), Some(Vec::new()));
+        let violations = validate_response(&endpoint, Some(&spec), 500, "").unwrap();
+        assert_eq!(violations[0].message, "status 500 is not documented (documented: 200, 4XX)");
+        assert_eq!(validate_response(&DiscoveredEndpoint::new("GET", "/"), None, 500, ""), None);
+    }
+}
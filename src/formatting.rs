@@ -0,0 +1,451 @@
+//! Content type detection and pretty printing of response bodies
+//!
+//! The network layer hands bodies over as received. They are formatted
+//! here for display, so the raw text stays available for the raw view,
+//! filters, history and exports.
+
+/// Format of a response body, deciding how it is printed and highlighted
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BodyKind {
+    Json,
+    /// XML, including SOAP envelopes
+    Xml,
+    Html,
+    Yaml,
+    Csv,
+    /// `application/x-www-form-urlencoded`
+    Form,
+    #[default]
+    Text,
+}
+
+/// HTML elements that never have a closing tag
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// HTML elements whose content is kept as it is
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "pre", "textarea"];
+
+impl BodyKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            BodyKind::Json => "JSON",
+            BodyKind::Xml => "XML",
+            BodyKind::Html => "HTML",
+            BodyKind::Yaml => "YAML",
+            BodyKind::Csv => "CSV",
+            BodyKind::Form => "Form",
+            BodyKind::Text => "Text",
+        }
+    }
+
+    /// Kind named by the Content-Type header, or sniffed from the body
+    /// when the header is missing or generic
+    pub fn detect(content_type: Option<&str>, body: &str) -> BodyKind {
+        let mime = content_type
+            .and_then(|value| value.split(';').next())
+            .map(|mime| mime.trim().to_ascii_lowercase())
+            .unwrap_or_default();
+        match mime.as_str() {
+            "text/html" | "application/xhtml+xml" => return BodyKind::Html,
+            "text/csv" => return BodyKind::Csv,
+            "application/x-www-form-urlencoded" => return BodyKind::Form,
+            m if m.contains("json") => return BodyKind::Json,
+            m if m.contains("xml") => return BodyKind::Xml,
+            m if m.contains("yaml") || m.ends_with("/yml") => return BodyKind::Yaml,
+            _ => {}
+        }
+
+        let start = body.trim_start();
+        if (start.starts_with('{') || start.starts_with('['))
+            && serde_json::from_str::<serde_json::Value>(body).is_ok()
+        {
+            return BodyKind::Json;
+        }
+        if start.starts_with('<') {
+            let head: String = start.chars().take(64).collect::<String>().to_ascii_lowercase();
+            return if head.starts_with("<!doctype html") || head.starts_with("<html") {
+                BodyKind::Html
+            } else {
+                BodyKind::Xml
+            };
+        }
+        BodyKind::Text
+    }
+}
+
+/// The body pretty printed, or `None` when it is not valid for its kind
+/// or there is nothing to change
+pub fn pretty(kind: BodyKind, body: &str) -> Option<String> {
+    match kind {
+        BodyKind::Json => serde_json::from_str::<serde_json::Value>(body)
+            .ok()
+            .and_then(|json| serde_json::to_string_pretty(&json).ok()),
+        BodyKind::Xml => pretty_markup(body, false),
+        BodyKind::Html => pretty_markup(body, true),
+        BodyKind::Yaml => pretty_yaml(body),
+        BodyKind::Csv => pretty_csv(body),
+        BodyKind::Form => pretty_form(body),
+        BodyKind::Text => None,
+    }
+}
+
+// ========================
+// XML / HTML
+// ========================
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Open { text: &'a str, name: String },
+    Close(&'a str),
+    /// Self-closing and void elements, comments, declarations, CDATA
+    Single(&'a str),
+    Text(&'a str),
+    /// Content of a raw text element, kept verbatim
+    Raw(&'a str),
+}
+
+/// Split markup into tags and text
+fn tokenize(body: &str, html: bool) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = body;
+    while !rest.is_empty() {
+        let Some(start) = rest.find('<') else {
+            tokens.push(Token::Text(rest));
+            break;
+        };
+        if start > 0 {
+            tokens.push(Token::Text(&rest[..start]));
+            rest = &rest[start..];
+        }
+
+        let end = if rest.starts_with("<!--") {
+            rest.find("-->").map(|i| i + 3)
+        } else if rest.starts_with("<![CDATA[") {
+            rest.find("]]>").map(|i| i + 3)
+        } else {
+            tag_end(rest)
+        };
+        let end = end.unwrap_or(rest.len());
+        let text = &rest[..end];
+        rest = &rest[end..];
+
+        let name = tag_name(text);
+        if text.starts_with("</") {
+            tokens.push(Token::Close(text));
+        } else if text.starts_with("<!")
+            || text.starts_with("<?")
+            || text.ends_with("/>")
+            || (html && VOID_ELEMENTS.contains(&name.as_str()))
+        {
+            tokens.push(Token::Single(text));
+        } else if html && RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+            let raw_end = find_close_tag(rest, &name).unwrap_or(rest.len());
+            tokens.push(Token::Open { text, name });
+            if raw_end > 0 {
+                tokens.push(Token::Raw(&rest[..raw_end]));
+            }
+            rest = &rest[raw_end..];
+        } else {
+            tokens.push(Token::Open { text, name });
+        }
+    }
+    tokens
+}
+
+/// Start of the first `</name` in `text`, ignoring ASCII case
+fn find_close_tag(text: &str, name: &str) -> Option<usize> {
+    let mut from = 0;
+    while let Some(pos) = text[from..].find("</") {
+        let start = from + pos;
+        let after = &text.as_bytes()[start + 2..];
+        if after.len() >= name.len() && after[..name.len()].eq_ignore_ascii_case(name.as_bytes()) {
+            return Some(start);
+        }
+        from = start + 2;
+    }
+    None
+}
+
+/// End of the tag at the start of `text`, skipping `>` in quoted values
+fn tag_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Lowercased element name of a tag, empty for comments and declarations
+fn tag_name(tag: &str) -> String {
+    tag.trim_start_matches('<')
+        .trim_start_matches('/')
+        .chars()
+        .take_while(|c| !c.is_whitespace() && *c != '/' && *c != '>')
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// One element per line, children indented by two spaces. Elements
+/// holding only a short text stay on one line.
+fn pretty_markup(body: &str, html: bool) -> Option<String> {
+    let tokens = tokenize(body, html);
+    if !tokens.iter().any(|t| matches!(t, Token::Open { .. } | Token::Single(_))) {
+        return None;
+    }
+
+    let mut out = String::new();
+    let mut depth = 0usize;
+    let push_line = |out: &mut String, depth: usize, text: &str| {
+        out.push_str(&"  ".repeat(depth));
+        out.push_str(text);
+        out.push('\n');
+    };
+
+    let mut i = 0;
+    while i < tokens.len() {
+        match &tokens[i] {
+            Token::Open { text, name } => {
+                // <a>text</a> and <a></a> stay on one line
+                let inline = match (tokens.get(i + 1), tokens.get(i + 2)) {
+                    (Some(Token::Close(close)), _) if tag_name(close) == *name => {
+                        Some((format!("{}{}", text, close), 2))
+                    }
+                    (Some(Token::Text(inner)), Some(Token::Close(close)))
+                        if tag_name(close) == *name && !inner.trim().contains('\n') =>
+                    {
+                        Some((format!("{}{}{}", text, inner.trim(), close), 3))
+                    }
+                    _ => None,
+                };
+                match inline {
+                    Some((line, consumed)) => {
+                        push_line(&mut out, depth, &line);
+                        i += consumed;
+                        continue;
+                    }
+                    None => {
+                        push_line(&mut out, depth, text);
+                        depth += 1;
+                    }
+                }
+            }
+            Token::Close(text) => {
+                depth = depth.saturating_sub(1);
+                push_line(&mut out, depth, text);
+            }
+            Token::Single(text) => push_line(&mut out, depth, text),
+            Token::Text(text) => {
+                for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+                    push_line(&mut out, depth, line);
+                }
+            }
+            Token::Raw(text) => {
+                let text = text.trim_matches(|c| c == '\n' || c == '\r');
+                if !text.trim().is_empty() {
+                    out.push_str(text);
+                    out.push('\n');
+                }
+            }
+        }
+        i += 1;
+    }
+    out.pop();
+    Some(out)
+}
+
+// ========================
+// YAML
+// ========================
+
+/// Normalized block style; comments are only visible in the raw view
+fn pretty_yaml(body: &str) -> Option<String> {
+    let value: serde_yaml::Value = serde_yaml::from_str(body).ok()?;
+    if !value.is_mapping() && !value.is_sequence() {
+        return None;
+    }
+    let text = serde_yaml::to_string(&value).ok()?;
+    Some(text.trim_end().to_string())
+}
+
+// ========================
+// CSV
+// ========================
+
+/// Records of a CSV document, with quoted fields unescaped
+fn parse_csv(body: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (in_quotes, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => in_quotes = false,
+            (true, c) => field.push(c),
+            (false, '"') if field.is_empty() => in_quotes = true,
+            (false, ',') => row.push(std::mem::take(&mut field)),
+            (false, '\r') => {}
+            (false, '\n') => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+/// An aligned table with a rule under the header row
+fn pretty_csv(body: &str) -> Option<String> {
+    let rows = parse_csv(body);
+    if rows.is_empty() {
+        return None;
+    }
+    let columns = rows.iter().map(Vec::len).max().unwrap_or_default();
+    let mut widths = vec![0; columns];
+    for row in &rows {
+        for (width, field) in widths.iter_mut().zip(row) {
+            *width = (*width).max(field.chars().count());
+        }
+    }
+
+    let format_row = |row: &Vec<String>| {
+        let cells: Vec<String> = widths
+            .iter()
+            .enumerate()
+            .map(|(i, width)| {
+                let field = row.get(i).map(String::as_str).unwrap_or_default();
+                format!("{}{}", field, " ".repeat(width - field.chars().count()))
+            })
+            .collect();
+        cells.join(" │ ").trim_end().to_string()
+    };
+    let mut lines = vec![format_row(&rows[0])];
+    lines.push(
+        widths
+            .iter()
+            .map(|width| "─".repeat(*width))
+            .collect::<Vec<_>>()
+            .join("─┼─"),
+    );
+    lines.extend(rows[1..].iter().map(format_row));
+    Some(lines.join("\n"))
+}
+
+// ========================
+// Form
+// ========================
+
+/// Decode `+` and `%XX` escapes of a form field
+fn form_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => {
+                let hex = bytes.get(i + 1..i + 3).and_then(|hex| std::str::from_utf8(hex).ok());
+                match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// One decoded `name = value` pair per line, names aligned
+fn pretty_form(body: &str) -> Option<String> {
+    let pairs: Vec<(String, String)> = body
+        .trim()
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (form_decode(name), form_decode(value))
+        })
+        .collect();
+    if pairs.is_empty() {
+        return None;
+    }
+    let width = pairs.iter().map(|(name, _)| name.chars().count()).max().unwrap_or_default();
+    let lines: Vec<String> = pairs
+        .iter()
+        .map(|(name, value)| {
+            format!("{}{} = {}", name, " ".repeat(width - name.chars().count()), value)
+        })
+        .collect();
+    Some(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(BodyKind::detect(Some("application/problem+json"), "{}"), BodyKind::Json);
+        assert_eq!(BodyKind::detect(Some("application/soap+xml; charset=utf-8"), ""), BodyKind::Xml);
+        assert_eq!(BodyKind::detect(Some("text/html"), ""), BodyKind::Html);
+        assert_eq!(BodyKind::detect(Some("application/x-yaml"), ""), BodyKind::Yaml);
+        assert_eq!(BodyKind::detect(None, " [1, 2]"), BodyKind::Json);
+        assert_eq!(BodyKind::detect(Some("text/plain"), "<!DOCTYPE html><p>"), BodyKind::Html);
+        assert_eq!(BodyKind::detect(None, "<?xml version=\"1.0\"?><a/>"), BodyKind::Xml);
+        assert_eq!(BodyKind::detect(None, "{not json"), BodyKind::Text);
+    }
+
+    #[test]
+    fn test_pretty_markup() {
+        let soap = r#"<?xml version="1.0"?><soap:Envelope><soap:Body><m:Price a="x>y">12</m:Price><m:Empty/></soap:Body></soap:Envelope>"#;
+        assert_eq!(
+            pretty(BodyKind::Xml, soap).unwrap(),
+            "<?xml version=\"1.0\"?>\n<soap:Envelope>\n  <soap:Body>\n    <m:Price a=\"x>y\">12</m:Price>\n    <m:Empty/>\n  </soap:Body>\n</soap:Envelope>"
+        );
+
+        let html = "<html><head><meta charset=utf-8><script>if (a < b) { go() }</script></head><body><p>Hi <b>there</b></p></body></html>";
+        assert_eq!(
+            pretty(BodyKind::Html, html).unwrap(),
+            "<html>\n  <head>\n    <meta charset=utf-8>\n    <script>\nif (a < b) { go() }\n    </script>\n  </head>\n  <body>\n    <p>\n      Hi\n      <b>there</b>\n    </p>\n  </body>\n</html>"
+        );
+        assert_eq!(pretty(BodyKind::Xml, "plain"), None);
+
+        assert_eq!(find_close_tag("a </b> é </SCRIPT>", "script"), Some(10));
+        assert_eq!(find_close_tag("x </scrip", "script"), None);
+    }
+
+    #[test]
+    fn test_pretty_yaml_csv_and_form() {
+        assert_eq!(pretty(BodyKind::Yaml, "a: {b: 1, c: [x]}").unwrap(), "a:\n  b: 1\n  c:\n  - x");
+        assert_eq!(
+            pretty(BodyKind::Csv, "id,name\r\n1,\"Doe, Ann\"\n22,Bo\n").unwrap(),
+            "id │ name\n───┼─────────\n1  │ Doe, Ann\n22 │ Bo"
+        );
+        assert_eq!(
+            pretty(BodyKind::Form, "name=Ann+Lee&city=K%C3%B6ln&empty").unwrap(),
+            "name  = Ann Lee\ncity  = Köln\nempty = "
+        );
+    }
+}
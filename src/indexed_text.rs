@@ -0,0 +1,128 @@
+//! Shared text with a line index, for response bodies
+//!
+//! Cloning only bumps reference counts, so a multi-megabyte body is
+//! stored once however many render states and history entries hold it,
+//! and any line is found without scanning the text.
+
+use std::fmt;
+use std::ops::{Deref, Range};
+use std::sync::Arc;
+
+#[derive(Clone, Default)]
+pub struct IndexedText {
+    text: Arc<str>,
+    /// Byte offset of the start of every line
+    line_starts: Arc<[usize]>,
+}
+
+impl IndexedText {
+    pub fn new(text: impl Into<Arc<str>>) -> Self {
+        let text = text.into();
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .filter(|start| *start < text.len() || *start == 0)
+            .collect();
+        IndexedText { text, line_starts }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Number of lines, counted like `str::lines`
+    pub fn line_count(&self) -> usize {
+        if self.text.is_empty() {
+            0
+        } else {
+            self.line_starts.len()
+        }
+    }
+
+    /// A line without its line ending, or `None` past the end
+    pub fn line(&self, index: usize) -> Option<&str> {
+        if index >= self.line_count() {
+            return None;
+        }
+        let start = self.line_starts[index];
+        let end = self.line_starts.get(index + 1).copied().unwrap_or(self.text.len());
+        let line = &self.text[start..end];
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        Some(line.strip_suffix('\r').unwrap_or(line))
+    }
+
+    /// The lines in `range` that exist
+    pub fn lines(&self, range: Range<usize>) -> impl Iterator<Item = &str> {
+        let end = range.end.min(self.line_count());
+        (range.start.min(end)..end).filter_map(move |index| self.line(index))
+    }
+}
+
+impl Deref for IndexedText {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.text
+    }
+}
+
+impl From<String> for IndexedText {
+    fn from(text: String) -> Self {
+        IndexedText::new(text)
+    }
+}
+
+impl From<&str> for IndexedText {
+    fn from(text: &str) -> Self {
+        IndexedText::new(text)
+    }
+}
+
+/// Texts sharing storage are equal without comparing them
+impl PartialEq for IndexedText {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.text, &other.text) || self.text == other.text
+    }
+}
+
+impl PartialEq<str> for IndexedText {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for IndexedText {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl fmt::Debug for IndexedText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for IndexedText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines_match_str_lines() {
+        for text in ["", "a", "a\n", "a\r\nb\n\nc", "\n\n"] {
+            let indexed = IndexedText::new(text);
+            let expected: Vec<&str> = text.lines().collect();
+            assert_eq!(indexed.line_count(), expected.len(), "{:?}", text);
+            assert_eq!(indexed.lines(0..usize::MAX).collect::<Vec<_>>(), expected);
+        }
+        let indexed = IndexedText::new("zero\none\ntwo");
+        assert_eq!(indexed.lines(1..5).collect::<Vec<_>>(), vec!["one", "two"]);
+        assert_eq!(indexed.line(3), None);
+        assert!(indexed.clone() == "zero\none\ntwo");
+    }
+}
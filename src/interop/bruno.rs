@@ -0,0 +1,597 @@
+//! Bruno collection folder import (`bruno.json` plus `.bru` files)
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+use crate::curl::{form_part, set_default_header, FORM_BOUNDARY};
+use crate::interop::{
+    add_path_variable, encode_form, file_placeholder_part, file_placeholder_warning,
+    path_variables_to_placeholders, Imported,
+};
+use crate::models::{AuthType, Collection, Environment, Header, HttpMethod, Request};
+
+/// Whether a directory is a Bruno collection
+pub fn is_collection(path: &Path) -> bool {
+    path.join("bruno.json").is_file()
+}
+
+/// Convert a Bruno collection folder. Its subfolders become folders,
+/// `environments/*.bru` environments named `<collection> - <name>`.
+pub fn import(root: &Path) -> Result<Imported> {
+    let config = fs::read_to_string(root.join("bruno.json"))
+        .with_context(|| format!("Cannot read {}", root.join("bruno.json").display()))?;
+    let config: serde_json::Value = serde_json::from_str(&config).context("Invalid bruno.json")?;
+    let name = config
+        .get("name")
+        .and_then(serde_json::Value::as_str)
+        .map(str::to_string)
+        .or_else(|| root.file_name().map(|name| name.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "Bruno collection".to_string());
+
+    let mut importer = Importer {
+        variables: Environment::new(&name),
+        warnings: Vec::new(),
+    };
+    let defaults = match read_bru(&root.join("collection.bru"))? {
+        Some(bru) => importer.defaults(&bru, &name, &Defaults::default()),
+        None => Defaults::default(),
+    };
+    let collection = importer.folder(root, &name, &defaults, &name)?;
+
+    let mut imported = Imported::default();
+    imported.collections.push(collection);
+    if !importer.variables.variables.is_empty() {
+        imported.environments.push(importer.variables);
+    }
+
+    let environments = root.join("environments");
+    if environments.is_dir() {
+        for path in sorted_entries(&environments)? {
+            if path.extension().map_or(true, |ext| ext != "bru") {
+                continue;
+            }
+            let Some(bru) = read_bru(&path)? else {
+                continue;
+            };
+            let env_name = path.file_stem().unwrap_or_default().to_string_lossy();
+            let mut environment = Environment::new(format!("{} - {}", name, env_name));
+            for (key, value) in bru.dict("vars") {
+                environment.set(key, value);
+            }
+            let secrets = bru.list("vars:secret");
+            if !secrets.is_empty() {
+                importer.warnings.push(format!(
+                    "Environment {}: secret values are not stored in the collection ({})",
+                    env_name,
+                    secrets.join(", ")
+                ));
+                for secret in secrets {
+                    environment.set(secret, "");
+                }
+            }
+            imported.environments.push(environment);
+        }
+    }
+    imported.warnings = importer.warnings;
+    Ok(imported)
+}
+
+/// Headers and auth a collection or folder passes down to its requests
+#[derive(Clone, Debug, Default)]
+struct Defaults {
+    headers: Vec<Header>,
+    auth: Option<AuthType>,
+}
+
+struct Importer {
+    /// Collection variables and path parameter values
+    variables: Environment,
+    warnings: Vec<String>,
+}
+
+impl Importer {
+    fn folder(&mut self, dir: &Path, name: &str, defaults: &Defaults, path: &str) -> Result<Collection> {
+        let mut collection = Collection::new(name);
+        let mut requests = Vec::new();
+        for entry in sorted_entries(dir)? {
+            let file_name = entry.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            if entry.is_dir() {
+                // Environments are read separately; dot folders are not Bruno's
+                if (file_name == "environments" && dir.join("bruno.json").is_file())
+                    || file_name.starts_with('.')
+                    || file_name == "node_modules"
+                {
+                    continue;
+                }
+                let folder_path = format!("{} / {}", path, file_name);
+                let folder_defaults = match read_bru(&entry.join("folder.bru"))? {
+                    Some(bru) => self.defaults(&bru, &folder_path, defaults),
+                    None => defaults.clone(),
+                };
+                let folder = self.folder(&entry, &file_name, &folder_defaults, &folder_path)?;
+                if !folder.requests.is_empty() || !folder.folders.is_empty() {
+                    collection.folders.push(folder);
+                }
+            } else if entry.extension().is_some_and(|ext| ext == "bru")
+                && file_name != "folder.bru"
+                && file_name != "collection.bru"
+            {
+                let Some(bru) = read_bru(&entry)? else {
+                    continue;
+                };
+                let request_name = bru
+                    .dict("meta")
+                    .into_iter()
+                    .find(|(key, _)| key == "name")
+                    .map(|(_, name)| name)
+                    .unwrap_or_else(|| file_name.trim_end_matches(".bru").to_string());
+                let seq = bru
+                    .dict("meta")
+                    .into_iter()
+                    .find(|(key, _)| key == "seq")
+                    .and_then(|(_, seq)| seq.parse::<f64>().ok())
+                    .unwrap_or(f64::MAX);
+                let request_path = format!("{} / {}", path, request_name);
+                if let Some(request) = self.request(&bru, &request_name, defaults, &request_path) {
+                    requests.push((seq, request));
+                }
+            }
+        }
+        requests.sort_by(|a, b| a.0.total_cmp(&b.0));
+        collection.requests = requests.into_iter().map(|(_, request)| request).collect();
+        Ok(collection)
+    }
+
+    /// Headers and auth of `collection.bru` or `folder.bru`, on top of
+    /// the parent's
+    fn defaults(&mut self, bru: &Bru, path: &str, parent: &Defaults) -> Defaults {
+        self.check_scripts(bru, path);
+        for (key, value) in bru.dict("vars:pre-request") {
+            self.variables.set(key, value);
+        }
+        let mut defaults = parent.clone();
+        for header in bru.headers() {
+            defaults.headers.retain(|h| !h.key.eq_ignore_ascii_case(&header.key));
+            defaults.headers.push(header);
+        }
+        let mode = bru
+            .dict("auth")
+            .into_iter()
+            .find(|(key, _)| key == "mode")
+            .map(|(_, mode)| mode);
+        if let Some(mode) = mode {
+            let mut headers = Vec::new();
+            let mut url = String::new();
+            match self.auth(bru, &mode, &mut headers, &mut url, path) {
+                Some(auth) => defaults.auth = Some(auth),
+                None if mode == "none" => defaults.auth = Some(AuthType::None),
+                None => {}
+            }
+            defaults.headers.extend(headers);
+        }
+        defaults
+    }
+
+    fn request(&mut self, bru: &Bru, name: &str, defaults: &Defaults, path: &str) -> Option<Request> {
+        let (method, settings) = ["get", "post", "put", "patch", "delete", "head", "options"]
+            .iter()
+            .find_map(|method| bru.block(method).map(|_| (*method, bru.dict(method))))
+            .or_else(|| {
+                let kind = bru.dict("meta").into_iter().find(|(key, _)| key == "type").map(|(_, t)| t);
+                self.warnings.push(format!(
+                    "{}: {} request",
+                    path,
+                    kind.unwrap_or_else(|| "unknown".to_string())
+                ));
+                None
+            })?;
+        let setting = |key: &str| {
+            settings
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, value)| value.clone())
+                .unwrap_or_default()
+        };
+
+        let mut request = Request {
+            name: name.to_string(),
+            method: HttpMethod::parse(method).unwrap_or(HttpMethod::GET),
+            headers: bru.headers(),
+            ..Request::default()
+        };
+        for header in &defaults.headers {
+            if !request.headers.iter().any(|h| h.key.eq_ignore_ascii_case(&header.key)) {
+                request.headers.push(header.clone());
+            }
+        }
+
+        for (key, value) in bru.dict("params:path") {
+            if !value.is_empty() {
+                add_path_variable(&mut self.variables, &mut self.warnings, key, value);
+            }
+        }
+        for (key, value) in bru.dict("vars:pre-request") {
+            self.variables.set(key, value);
+        }
+        request.url = path_variables_to_placeholders(&setting("url"));
+
+        self.body(bru, &setting("body"), &mut request, path);
+
+        match setting("auth").as_str() {
+            "inherit" | "" => {
+                if let Some(auth) = &defaults.auth {
+                    request.auth = auth.clone();
+                }
+            }
+            mode => {
+                let (mut headers, mut url) = (Vec::new(), request.url.clone());
+                if let Some(auth) = self.auth(bru, mode, &mut headers, &mut url, path) {
+                    request.auth = auth;
+                }
+                request.headers.extend(headers);
+                request.url = url;
+            }
+        }
+
+        self.check_scripts(bru, path);
+        Some(request)
+    }
+
+    fn body(&mut self, bru: &Bru, mode: &str, request: &mut Request, path: &str) {
+        match mode {
+            "" | "none" => {}
+            "json" | "text" | "xml" | "sparql" => {
+                request.body = bru.text(&format!("body:{}", mode));
+                let content_type = match mode {
+                    "json" => "application/json",
+                    "xml" => "application/xml",
+                    "sparql" => "application/sparql-query",
+                    _ => "text/plain",
+                };
+                set_default_header(request, "Content-Type", content_type);
+            }
+            "formUrlEncoded" => {
+                request.body = bru
+                    .dict("body:form-urlencoded")
+                    .into_iter()
+                    .map(|(key, value)| format!("{}={}", encode_form(&key), encode_form(&value)))
+                    .collect::<Vec<_>>()
+                    .join("&");
+                set_default_header(request, "Content-Type", "application/x-www-form-urlencoded");
+            }
+            "multipartForm" => {
+                let mut parts = Vec::new();
+                for (key, value) in bru.dict("body:multipart-form") {
+                    if let Some(files) = value.strip_prefix("@file(").and_then(|v| v.strip_suffix(')')) {
+                        let file = files.split('|').next().unwrap_or_default();
+                        parts.push(file_placeholder_part(&key, file));
+                        self.warnings.push(file_placeholder_warning(path, &key, file));
+                        continue;
+                    }
+                    match form_part(&format!("{}={}", key, value), true) {
+                        Ok(part) => parts.push(part),
+                        Err(e) => self.warnings.push(format!("{}: form field '{}' ({:#})", path, key, e)),
+                    }
+                }
+                request.body = format!("{}--{}--\r\n", parts.concat(), FORM_BOUNDARY);
+                set_default_header(
+                    request,
+                    "Content-Type",
+                    &format!("multipart/form-data; boundary={}", FORM_BOUNDARY),
+                );
+            }
+            "graphql" => {
+                let variables = bru.text("body:graphql:vars");
+                let variables = serde_json::from_str(&variables)
+                    .unwrap_or_else(|_| serde_json::json!({}));
+                let payload = serde_json::json!({
+                    "query": bru.text("body:graphql"),
+                    "variables": variables,
+                });
+                request.body = serde_json::to_string_pretty(&payload).unwrap_or_default();
+                set_default_header(request, "Content-Type", "application/json");
+            }
+            other => self.warnings.push(format!("{}: {} body", path, other)),
+        }
+    }
+
+    /// The auth for `mode`; API keys are added to `headers` or `url`
+    fn auth(
+        &mut self,
+        bru: &Bru,
+        mode: &str,
+        headers: &mut Vec<Header>,
+        url: &mut String,
+        path: &str,
+    ) -> Option<AuthType> {
+        let params = bru.dict(&format!("auth:{}", mode));
+        let param = |key: &str| {
+            params
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, value)| value.clone())
+                .unwrap_or_default()
+        };
+        match mode {
+            "none" | "inherit" => None,
+            "bearer" => Some(AuthType::Bearer(param("token"))),
+            "basic" => Some(AuthType::Basic {
+                username: param("username"),
+                password: param("password"),
+            }),
+            "apikey" => {
+                if param("placement") == "queryparams" {
+                    let separator = if url.contains('?') { '&' } else { '?' };
+                    *url = format!("{}{}{}={}", url, separator, param("key"), param("value"));
+                } else {
+                    headers.push(Header::new(param("key"), param("value")));
+                }
+                None
+            }
+            other => {
+                self.warnings.push(format!("{}: {} auth", path, other));
+                None
+            }
+        }
+    }
+
+    fn check_scripts(&mut self, bru: &Bru, path: &str) {
+        for (block, label) in [
+            ("script:pre-request", "pre-request script"),
+            ("script:post-response", "post-response script"),
+            ("tests", "tests"),
+            ("vars:post-response", "post-response variables"),
+            ("assert", "assertions"),
+        ] {
+            if bru.block(block).is_some_and(|lines| lines.iter().any(|l| !l.trim().is_empty())) {
+                self.warnings.push(format!("{}: {}", path, label));
+            }
+        }
+    }
+}
+
+/// A parsed `.bru` file: named blocks of indented lines
+struct Bru {
+    blocks: Vec<(String, Vec<String>)>,
+}
+
+impl Bru {
+    fn parse(content: &str) -> Bru {
+        let mut blocks = Vec::new();
+        let mut lines = content.lines();
+        while let Some(line) = lines.next() {
+            let line = line.trim_end();
+            let Some((name, close)) = line
+                .strip_suffix(" {")
+                .map(|name| (name, "}"))
+                .or_else(|| line.strip_suffix(" [").map(|name| (name, "]")))
+            else {
+                continue;
+            };
+            let mut body = Vec::new();
+            for line in lines.by_ref() {
+                if line.trim_end() == close {
+                    break;
+                }
+                body.push(line.strip_prefix("  ").unwrap_or(line).to_string());
+            }
+            blocks.push((name.trim().to_string(), body));
+        }
+        Bru { blocks }
+    }
+
+    fn block(&self, name: &str) -> Option<&[String]> {
+        self.blocks
+            .iter()
+            .find(|(block, _)| block == name)
+            .map(|(_, lines)| lines.as_slice())
+    }
+
+    /// A text block such as `body:json`, indentation removed
+    fn text(&self, name: &str) -> String {
+        self.block(name).map(|lines| lines.join("\n")).unwrap_or_default()
+    }
+
+    /// Enabled `key: value` entries; `~` marks disabled ones
+    fn dict(&self, name: &str) -> Vec<(String, String)> {
+        self.entries(name)
+            .into_iter()
+            .filter(|(_, _, enabled)| *enabled)
+            .map(|(key, value, _)| (key, value))
+            .collect()
+    }
+
+    fn entries(&self, name: &str) -> Vec<(String, String, bool)> {
+        self.block(name)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                let key = key.trim();
+                let (key, enabled) = match key.strip_prefix('~') {
+                    Some(key) => (key, false),
+                    None => (key, true),
+                };
+                Some((key.to_string(), value.trim().to_string(), enabled))
+            })
+            .collect()
+    }
+
+    /// Items of a list block such as `vars:secret [ ... ]`
+    fn list(&self, name: &str) -> Vec<String> {
+        self.block(name)
+            .unwrap_or_default()
+            .iter()
+            .map(|item| item.trim().trim_end_matches(',').to_string())
+            .filter(|item| !item.is_empty())
+            .collect()
+    }
+
+    fn headers(&self) -> Vec<Header> {
+        self.entries("headers")
+            .into_iter()
+            .map(|(key, value, enabled)| Header { key, value, enabled })
+            .collect()
+    }
+}
+
+fn read_bru(path: &Path) -> Result<Option<Bru>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    Ok(Some(Bru::parse(&content)))
+}
+
+fn sorted_entries(dir: &Path) -> Result<Vec<std::path::PathBuf>> {
+    let mut entries = fs::read_dir(dir)
+        .with_context(|| format!("Cannot read {}", dir.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_import_collection_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "bruno.json", r#"{ "version": "1", "name": "Payments", "type": "collection" }"#);
+        write(
+            root,
+            "collection.bru",
+            "headers {\n  X-Client: freeman\n}\n\nauth {\n  mode: bearer\n}\n\nauth:bearer {\n  token: {{token}}\n}\n",
+        );
+        write(
+            root,
+            "Charges/Create charge.bru",
+            r#"meta {
+  name: Create charge
+  type: http
+  seq: 2
+}
+
+post {
+  url: {{baseUrl}}/charges/:id
+  body: json
+  auth: inherit
+}
+
+params:path {
+  id: ch_1
+}
+
+headers {
+  ~X-Debug: 1
+}
+
+body:json {
+  {
+    "amount": 100
+  }
+}
+
+script:pre-request {
+  bru.setVar("ts", Date.now());
+}
+"#,
+        );
+        write(
+            root,
+            "Charges/List charges.bru",
+            "meta {\n  name: List charges\n  seq: 1\n}\n\nget {\n  url: {{baseUrl}}/charges?limit=5\n  auth: basic\n}\n\nauth:basic {\n  username: sk_test\n  password: \n}\n",
+        );
+        write(
+            root,
+            "environments/Local.bru",
+            "vars {\n  baseUrl: http://localhost:8080\n}\nvars:secret [\n  token\n]\n",
+        );
+
+        let imported = import(root).unwrap();
+        let payments = &imported.collections[0];
+        assert_eq!(payments.name, "Payments");
+        let charges = &payments.folders[0];
+        assert_eq!(charges.name, "Charges");
+
+        let list = &charges.requests[0];
+        assert_eq!(list.url, "{{baseUrl}}/charges?limit=5");
+        assert_eq!(
+            list.auth,
+            AuthType::Basic { username: "sk_test".to_string(), password: String::new() }
+        );
+
+        let create = &charges.requests[1];
+        assert_eq!(create.method, HttpMethod::POST);
+        assert_eq!(create.url, "{{baseUrl}}/charges/{{id}}");
+        assert_eq!(create.body, "{\n  \"amount\": 100\n}");
+        assert_eq!(create.auth, AuthType::Bearer("{{token}}".to_string()));
+        assert!(!create.headers[0].enabled);
+        assert!(create.headers.iter().any(|h| h.key == "X-Client"));
+        assert!(create.headers.iter().any(|h| h.value == "application/json"));
+
+        assert_eq!(imported.environments[0].get("id").map(String::as_str), Some("ch_1"));
+        let local = &imported.environments[1];
+        assert_eq!(local.name, "Payments - Local");
+        assert_eq!(local.get("baseUrl").map(String::as_str), Some("http://localhost:8080"));
+        assert_eq!(local.get("token").map(String::as_str), Some(""));
+
+        assert_eq!(imported.warnings.len(), 2);
+        assert!(imported.warnings[0].contains("Create charge: pre-request script"));
+        assert!(imported.warnings[1].contains("secret values"));
+    }
+
+    #[test]
+    fn test_import_malformed_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "bruno.json", r#"{ "name": "Broken" }"#);
+        write(
+            root,
+            "Ping.bru",
+            "stray text\n\nget {\n  url: https://ping.test\n  body: json\n}\n\nheaders {\n  not a header\n  X-Ok: 1\n}\n\nbody:json {\n  {\"open\": true}\n",
+        );
+        write(root, "Notes.bru", "meta {\n  name: Notes\n}\n\ndocs {\n  unfinished");
+
+        let imported = import(root).unwrap();
+        let broken = &imported.collections[0];
+        assert_eq!(broken.requests.len(), 1);
+        let ping = &broken.requests[0];
+        assert_eq!(ping.url, "https://ping.test");
+        assert_eq!(ping.headers[0].key, "X-Ok");
+        assert_eq!(ping.body, "{\"open\": true}");
+        assert_eq!(imported.warnings, vec!["Broken / Notes: unknown request".to_string()]);
+    }
+
+    #[test]
+    fn test_import_form_file_is_not_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "bruno.json", r#"{ "name": "Uploads" }"#);
+        write(root, "avatar.png", "PIXELS");
+        write(
+            root,
+            "Upload.bru",
+            "post {\n  url: https://files.test\n  body: multipartForm\n}\n\nbody:multipart-form {\n  caption: hi\n  file: @file(avatar.png)\n}\n",
+        );
+
+        let imported = import(root).unwrap();
+        let body = &imported.collections[0].requests[0].body;
+        assert!(!body.contains("PIXELS"));
+        assert!(body.contains("name=\"file\"; filename=\"avatar.png\""));
+        assert!(body.contains("\r\n\r\nhi\r\n"));
+        assert_eq!(imported.warnings.len(), 1);
+        assert!(imported.warnings[0].contains("Uploads / Upload: form file 'file' (avatar.png)"));
+    }
+}
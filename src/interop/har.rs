@@ -0,0 +1,431 @@
+//! HAR 1.2 import (browser captures) and export (request history)
+
+use anyhow::{anyhow, bail, Result};
+use base64::Engine;
+use serde_json::{json, Value};
+
+use crate::curl::{add_header, percent_encode};
+use crate::interop::{array, text, Imported};
+use crate::models::{AuthType, Collection, Header, HistoryEntry, HttpMethod, Request};
+
+/// Headers the HTTP client sets itself; replaying the captured values
+/// would be wrong (`Content-Length`) or undecodable (`Accept-Encoding`)
+const CLIENT_HEADERS: &[&str] = &[
+    "host",
+    "content-length",
+    "connection",
+    "keep-alive",
+    "transfer-encoding",
+    "accept-encoding",
+];
+
+/// Which HAR entries to import. Empty lists match everything.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HarFilter {
+    /// Hosts, matching their subdomains too
+    pub domains: Vec<String>,
+    pub methods: Vec<HttpMethod>,
+    /// Substrings of the response content type, e.g. `json`
+    pub content_types: Vec<String>,
+}
+
+impl HarFilter {
+    /// Split trailing `domain:`, `method:` and `type:` filters (values
+    /// separated by commas) off a path typed in the open popup, e.g.
+    /// `capture.har domain:api.example.com method:POST,PUT type:json`
+    pub fn split(input: &str) -> Result<(&str, HarFilter)> {
+        let mut filter = HarFilter::default();
+        let mut path = input.trim_end();
+        while let Some((rest, token)) = path.rsplit_once(' ') {
+            let Some((kind, values)) = token.split_once(':') else {
+                break;
+            };
+            let values = values.split(',').filter(|v| !v.is_empty());
+            match kind {
+                "domain" => filter.domains.extend(values.map(|v| v.to_ascii_lowercase())),
+                "method" => {
+                    for value in values {
+                        let method = HttpMethod::parse(value)
+                            .ok_or_else(|| anyhow!("Unknown method in filter: {}", value))?;
+                        filter.methods.push(method);
+                    }
+                }
+                "type" => filter.content_types.extend(values.map(|v| v.to_ascii_lowercase())),
+                _ => break,
+            }
+            path = rest.trim_end();
+        }
+        Ok((path, filter))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == HarFilter::default()
+    }
+
+    fn matches(&self, host: &str, method: &HttpMethod, content_type: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        let content_type = content_type.to_ascii_lowercase();
+        (self.domains.is_empty()
+            || self.domains.iter().any(|domain| {
+                host == *domain || host.strip_suffix(domain.as_str()).is_some_and(|sub| sub.ends_with('.'))
+            }))
+            && (self.methods.is_empty() || self.methods.contains(method))
+            && (self.content_types.is_empty()
+                || self.content_types.iter().any(|t| content_type.contains(t.as_str())))
+    }
+}
+
+/// Whether a document is a HAR log
+pub fn is_har(value: &Value) -> bool {
+    value.pointer("/log/entries").is_some_and(Value::is_array)
+}
+
+/// Convert the entries of a HAR log matching `filter` into a collection
+/// named `name`, one request per entry, in a folder per host when the
+/// capture spans several hosts
+pub fn import(value: &Value, filter: &HarFilter, name: &str) -> Result<Imported> {
+    let Some(entries) = value.pointer("/log/entries").and_then(Value::as_array) else {
+        bail!("HAR file has no log entries");
+    };
+
+    let mut warnings = Vec::new();
+    let mut hosts: Vec<Collection> = Vec::new();
+    let (mut not_http, mut filtered) = (0, 0);
+    for (i, entry) in entries.iter().enumerate() {
+        let captured = &entry["request"];
+        let url = text(captured.get("url"));
+        let parsed = match reqwest::Url::parse(&url) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => parsed,
+            _ => {
+                not_http += 1;
+                continue;
+            }
+        };
+        let host = parsed.host_str().unwrap_or_default().to_string();
+        let method_name = text(captured.get("method"));
+        let Some(method) = HttpMethod::parse(&method_name) else {
+            warnings.push(format!("Entry {} ({}): {} method", i + 1, url, method_name));
+            continue;
+        };
+        let content_type = text(entry.pointer("/response/content/mimeType"));
+        if !filter.matches(&host, &method, &content_type) {
+            filtered += 1;
+            continue;
+        }
+
+        let mut request = Request {
+            name: format!("{} {}", method.as_str(), parsed.path()),
+            method,
+            url,
+            headers: Vec::new(),
+            ..Request::default()
+        };
+        for header in array(captured.get("headers")) {
+            let key = text(header.get("name"));
+            if key.starts_with(':') || CLIENT_HEADERS.contains(&key.to_ascii_lowercase().as_str()) {
+                continue;
+            }
+            add_header(&mut request, Header::new(key, text(header.get("value"))));
+        }
+        if let Some(post) = captured.get("postData") {
+            match post.get("text").and_then(Value::as_str) {
+                Some(body) => request.body = body.to_string(),
+                None if !array(post.get("params")).is_empty() => {
+                    request.body = array(post.get("params"))
+                        .iter()
+                        .map(|param| {
+                            format!(
+                                "{}={}",
+                                percent_encode(&text(param.get("name"))),
+                                percent_encode(&text(param.get("value")))
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join("&");
+                }
+                None => warnings.push(format!("Entry {} ({}): body was not captured", i + 1, request.name)),
+            }
+        }
+
+        match hosts.iter_mut().find(|folder| folder.name == host) {
+            Some(folder) => folder.requests.push(request),
+            None => {
+                let mut folder = Collection::new(host);
+                folder.requests.push(request);
+                hosts.push(folder);
+            }
+        }
+    }
+
+    if not_http > 0 {
+        warnings.push(format!("{} non-HTTP entries (data:, blob:, ws: ...)", not_http));
+    }
+    if filtered > 0 {
+        warnings.push(format!("{} entries did not match the filter", filtered));
+    }
+
+    let mut collection = Collection::new(name);
+    if hosts.len() == 1 {
+        collection.requests = hosts.remove(0).requests;
+    } else {
+        collection.folders = hosts;
+    }
+    Ok(Imported {
+        collections: vec![collection],
+        environments: Vec::new(),
+        warnings,
+    })
+}
+
+/// Write history entries, oldest first, as a HAR 1.2 log. Requests
+/// should already have their variables substituted.
+pub fn export(entries: &[HistoryEntry]) -> Result<String> {
+    let entries: Vec<Value> = entries.iter().map(export_entry).collect();
+    let log = json!({
+        "log": {
+            "version": "1.2",
+            "creator": { "name": "freeman", "version": env!("CARGO_PKG_VERSION") },
+            "entries": entries,
+        }
+    });
+    Ok(serde_json::to_string_pretty(&log)?)
+}
+
+fn export_entry(entry: &HistoryEntry) -> Value {
+    let (request, response) = (&entry.sent, &entry.response);
+    let http_version = response.http_version.as_deref().unwrap_or("HTTP/1.1");
+
+    let mut headers: Vec<&Header> = request.headers.iter().filter(|h| h.enabled).collect();
+    let authorization = match &request.auth {
+        AuthType::None => None,
+        AuthType::Bearer(token) => Some(format!("Bearer {}", token)),
+        AuthType::Basic { username, password } => Some(format!(
+            "Basic {}",
+            base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password))
+        )),
+    };
+    let authorization = authorization.map(|value| Header::new("Authorization", value));
+    headers.extend(authorization.as_ref());
+
+    let query: Vec<Value> = reqwest::Url::parse(&request.url)
+        .map(|url| {
+            url.query_pairs()
+                .map(|(name, value)| json!({ "name": name, "value": value }))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let sends_body = request.method.has_body() && !request.body.is_empty();
+    let mut har_request = json!({
+        "method": request.method.as_str(),
+        "url": request.url,
+        "httpVersion": http_version,
+        "cookies": [],
+        "headers": name_values(headers),
+        "queryString": query,
+        "headersSize": -1,
+        "bodySize": if sends_body { request.body.len() } else { 0 },
+    });
+    if sends_body {
+        har_request["postData"] = json!({
+            "mimeType": header_value(&request.headers, "content-type").unwrap_or_default(),
+            "text": request.body,
+        });
+    }
+
+    let mime_type = header_value(&response.headers, "content-type").unwrap_or_default();
+    let status = response.status_code.unwrap_or(0);
+    let mut har_response = json!({
+        "status": status,
+        "statusText": reqwest::StatusCode::from_u16(status)
+            .ok()
+            .and_then(|code| code.canonical_reason())
+            .unwrap_or_default(),
+        "httpVersion": http_version,
+        "cookies": [],
+        "headers": name_values(response.headers.iter()),
+        "content": { "size": response.body.len(), "mimeType": mime_type, "text": response.body.as_str() },
+        "redirectURL": header_value(&response.headers, "location").unwrap_or_default(),
+        "headersSize": -1,
+        "bodySize": -1,
+    });
+    if response.status_code.is_none() {
+        // No response: the body holds the error, as browsers record it
+        har_response["_error"] = json!(response.body.as_str());
+    }
+
+    // HAR counts the TLS handshake in `connect` as well as in `ssl`
+    let ms = |phase: Option<u64>| phase.map_or(-1, |ms| ms as i64);
+    let timings = match &response.timing {
+        Some(timing) => json!({
+            "blocked": -1,
+            "dns": ms(timing.dns_ms),
+            "connect": ms(timing.connect_ms.map(|connect| connect + timing.tls_ms.unwrap_or(0))),
+            "ssl": ms(timing.tls_ms),
+            "send": 0,
+            "wait": timing.ttfb_ms,
+            "receive": timing.download_ms,
+        }),
+        None => json!({ "send": 0, "wait": response.time_ms, "receive": 0 }),
+    };
+    let total: i64 = ["dns", "connect", "send", "wait", "receive"]
+        .iter()
+        .filter_map(|phase| timings.get(*phase).and_then(Value::as_i64))
+        .filter(|ms| *ms > 0)
+        .sum();
+
+    let started = entry.timestamp - chrono::Duration::milliseconds(response.time_ms as i64);
+    json!({
+        "startedDateTime": started.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        "time": total,
+        "request": har_request,
+        "response": har_response,
+        "cache": {},
+        "timings": timings,
+    })
+}
+
+fn name_values<'a>(headers: impl IntoIterator<Item = &'a Header>) -> Vec<Value> {
+    headers
+        .into_iter()
+        .map(|header| json!({ "name": header.key, "value": header.value }))
+        .collect()
+}
+
+fn header_value<'a>(headers: &'a [Header], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|header| header.enabled && header.key.eq_ignore_ascii_case(name))
+        .map(|header| header.value.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Response, ResponseTiming};
+
+    const CAPTURE: &str = r#"{
+        "log": {
+            "version": "1.2",
+            "entries": [
+                {
+                    "request": {
+                        "method": "POST",
+                        "url": "https://api.shop.test/v1/cart?x=1",
+                        "headers": [
+                            { "name": ":authority", "value": "api.shop.test" },
+                            { "name": "content-type", "value": "application/json" },
+                            { "name": "authorization", "value": "Bearer abc" },
+                            { "name": "accept-encoding", "value": "gzip, br" },
+                            { "name": "cookie", "value": "sid=1" }
+                        ],
+                        "postData": { "mimeType": "application/json", "text": "{\"sku\":1}" }
+                    },
+                    "response": { "status": 200, "content": { "mimeType": "application/json" } }
+                },
+                {
+                    "request": { "method": "GET", "url": "https://cdn.shop.test/app.js", "headers": [] },
+                    "response": { "status": 200, "content": { "mimeType": "application/javascript" } }
+                },
+                {
+                    "request": { "method": "GET", "url": "https://shop.test/api/me", "headers": [] },
+                    "response": { "status": 200, "content": { "mimeType": "application/json; charset=utf-8" } }
+                },
+                {
+                    "request": { "method": "GET", "url": "data:image/png;base64,AAA", "headers": [] },
+                    "response": { "status": 200, "content": { "mimeType": "image/png" } }
+                }
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn test_filter_split() {
+        let (path, filter) =
+            HarFilter::split("~/captures/my shop.har domain:Shop.test method:get,post type:json").unwrap();
+        assert_eq!(path, "~/captures/my shop.har");
+        assert_eq!(filter.domains, vec!["shop.test"]);
+        assert_eq!(filter.methods, vec![HttpMethod::GET, HttpMethod::POST]);
+        assert_eq!(filter.content_types, vec!["json"]);
+
+        let (path, filter) = HarFilter::split("/tmp/plain.har").unwrap();
+        assert_eq!(path, "/tmp/plain.har");
+        assert!(filter.is_empty());
+        assert!(HarFilter::split("x.har method:FETCH").is_err());
+    }
+
+    #[test]
+    fn test_import_filtered() {
+        let value: Value = serde_json::from_str(CAPTURE).unwrap();
+        let (_, filter) = HarFilter::split("x.har domain:shop.test type:json").unwrap();
+        let imported = import(&value, &filter, "capture").unwrap();
+        let capture = &imported.collections[0];
+        assert_eq!(capture.folders.len(), 2);
+
+        let cart = &capture.folders[0].requests[0];
+        assert_eq!(capture.folders[0].name, "api.shop.test");
+        assert_eq!(cart.name, "POST /v1/cart");
+        assert_eq!(cart.body, "{\"sku\":1}");
+        assert_eq!(cart.auth, AuthType::Bearer("abc".to_string()));
+        let keys: Vec<&str> = cart.headers.iter().map(|h| h.key.as_str()).collect();
+        assert_eq!(keys, vec!["content-type", "cookie"]);
+
+        assert_eq!(capture.folders[1].requests[0].url, "https://shop.test/api/me");
+        assert_eq!(
+            imported.warnings,
+            vec!["1 non-HTTP entries (data:, blob:, ws: ...)", "1 entries did not match the filter"]
+        );
+    }
+
+    #[test]
+    fn test_export_history() {
+        let sent = Request {
+            method: HttpMethod::POST,
+            url: "https://api.shop.test/v1/cart?x=1".to_string(),
+            body: "{}".to_string(),
+            auth: AuthType::Bearer("abc".to_string()),
+            ..Request::default()
+        };
+        let entry = HistoryEntry {
+            request: Request {
+                url: "{{host}}/v1/cart?x=1".to_string(),
+                auth: AuthType::Bearer("{{token}}".to_string()),
+                ..sent.clone()
+            },
+            sent,
+            response: Response {
+                status_code: Some(201),
+                body: "{\"id\": 7}".into(),
+                time_ms: 42,
+                timing: Some(ResponseTiming {
+                    dns_ms: Some(2),
+                    connect_ms: Some(5),
+                    tls_ms: Some(10),
+                    ttfb_ms: 20,
+                    download_ms: 3,
+                }),
+                headers: vec![Header::new("content-type", "application/json")],
+                http_version: Some("HTTP/2.0".to_string()),
+                ..Response::default()
+            },
+            timestamp: chrono::Utc::now(),
+        };
+        let har: Value = serde_json::from_str(&export(&[entry]).unwrap()).unwrap();
+        let entry = &har["log"]["entries"][0];
+        assert_eq!(har["log"]["version"], "1.2");
+        assert_eq!(entry["time"], 40);
+        assert_eq!(entry["timings"]["connect"], 15);
+        assert_eq!(entry["timings"]["ssl"], 10);
+        assert_eq!(entry["request"]["url"], "https://api.shop.test/v1/cart?x=1");
+        assert_eq!(entry["request"]["queryString"][0]["name"], "x");
+        assert_eq!(entry["request"]["postData"]["mimeType"], "application/json");
+        assert!(entry["request"]["headers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|h| h["name"] == "Authorization" && h["value"] == "Bearer abc"));
+        assert_eq!(entry["response"]["statusText"], "Created");
+        assert_eq!(entry["response"]["httpVersion"], "HTTP/2.0");
+        assert_eq!(entry["response"]["content"]["mimeType"], "application/json");
+    }
+}
@@ -0,0 +1,642 @@
+//! `.http` / `.rest` request files (VS Code REST Client and JetBrains
+//! HTTP Client format)
+//!
+//! The file is kept as blocks of source text so that saving an edited
+//! request rewrites only that request's lines: comments, separators,
+//! variable definitions, response handlers and every untouched request
+//! are written back byte for byte. A commented-out header (`# Key: value`)
+//! is read as a disabled header and written back the same way. Rewritten
+//! lines use the file's line ending (`\r\n` or `\n`).
+
+use anyhow::{bail, Result};
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::models::{AuthType, Collection, Environment, Header, HttpMethod, Request};
+
+/// Whether a path names a request file, by extension
+pub fn is_http_file(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("http") || ext.eq_ignore_ascii_case("rest"))
+}
+
+/// A parsed request file
+#[derive(Clone, Debug)]
+pub struct HttpFile {
+    blocks: Vec<Block>,
+    variables: Vec<(String, String)>,
+    /// Variables whose definitions are rewritten on save
+    edited_variables: BTreeSet<String>,
+    warnings: Vec<String>,
+    /// `\r\n` when the file uses it, otherwise `\n`
+    line_ending: &'static str,
+}
+
+/// The text between two `###` separators
+#[derive(Clone, Debug)]
+struct Block {
+    /// Separator, comments, `@variable` lines and `# @name` metadata
+    prefix: String,
+    request: Option<BlockRequest>,
+    /// Trailing blank lines, comments and response handlers
+    suffix: String,
+}
+
+#[derive(Clone, Debug)]
+struct BlockRequest {
+    /// Request line, headers and body as written in the file
+    source: String,
+    /// `HTTP/1.1` and the like, kept when the request is rewritten
+    version: Option<String>,
+    request: Request,
+}
+
+impl HttpFile {
+    pub fn parse(content: &str) -> Result<HttpFile> {
+        let mut file = HttpFile {
+            blocks: Vec::new(),
+            variables: Vec::new(),
+            edited_variables: BTreeSet::new(),
+            warnings: Vec::new(),
+            line_ending: if content.contains("\r\n") { "\r\n" } else { "\n" },
+        };
+        let mut block: Vec<&str> = Vec::new();
+        for line in content.split_inclusive('\n') {
+            if line.trim_start().starts_with("###") && !block.is_empty() {
+                file.push_block(&block)?;
+                block.clear();
+            }
+            block.push(line);
+        }
+        if !block.is_empty() {
+            file.push_block(&block)?;
+        }
+        file.check_references();
+        Ok(file)
+    }
+
+    fn push_block(&mut self, lines: &[&str]) -> Result<()> {
+        let number = self.blocks.len() + 1;
+        let mut prefix_end = 0;
+        let mut name = None;
+        for line in lines {
+            let trimmed = line.trim();
+            if let Some(comment) = comment(trimmed) {
+                if let Some(value) = metadata(comment, "name") {
+                    name = Some(value.to_string());
+                } else if metadata(comment, "prompt").is_some() {
+                    self.warnings
+                        .push(format!("Request {}: @prompt variables are not asked for", number));
+                }
+            } else if trimmed.starts_with('@') {
+                let Some((key, value)) = definition(trimmed) else {
+                    bail!("Request {}: variable definition without '=': {}", number, trimmed);
+                };
+                self.variables.push((key.to_string(), value.to_string()));
+            } else if !trimmed.is_empty() && !trimmed.starts_with("###") {
+                break;
+            }
+            prefix_end += 1;
+        }
+        let prefix = lines[..prefix_end].concat();
+        if prefix_end == lines.len() {
+            self.blocks.push(Block {
+                prefix,
+                request: None,
+                suffix: String::new(),
+            });
+            return Ok(());
+        }
+
+        let rest = &lines[prefix_end..];
+        let suffix_start = suffix_start(rest);
+        let source = rest[..suffix_start].concat();
+        let suffix = rest[suffix_start..].concat();
+        if rest[suffix_start..]
+            .iter()
+            .any(|line| line.trim_start().starts_with('>'))
+        {
+            self.warnings
+                .push(format!("Request {}: response handlers are kept but not run", number));
+        }
+
+        let Some((mut request, version)) = parse_request(&source) else {
+            self.warnings.push(format!(
+                "Request {}: unsupported method in '{}', kept as text",
+                number,
+                rest[0].trim()
+            ));
+            self.blocks.push(Block {
+                prefix: format!("{}{}", prefix, source),
+                request: None,
+                suffix,
+            });
+            return Ok(());
+        };
+        if request.body.lines().any(|line| line.starts_with("< ")) {
+            self.warnings
+                .push(format!("Request {}: file bodies (< path) are sent as text", number));
+        }
+        let separator = lines[0].trim().trim_start_matches('#').trim();
+        request.name = name
+            .or_else(|| (!separator.is_empty()).then(|| separator.to_string()))
+            .unwrap_or_else(|| format!("{} {}", request.method.as_str(), request.url));
+
+        self.blocks.push(Block {
+            prefix,
+            request: Some(BlockRequest {
+                source,
+                version,
+                request,
+            }),
+            suffix,
+        });
+        Ok(())
+    }
+
+    /// Note references freeman cannot resolve: system variables like
+    /// `{{$guid}}` and other requests' responses like `{{login.response...}}`
+    fn check_references(&mut self) {
+        let reference = Regex::new(r"\{\{\s*([^}\s]+)\s*\}\}").expect("valid regex");
+        let mut dynamic = Vec::new();
+        let mut chained = Vec::new();
+        for request in self.requests() {
+            let texts = [&request.url, &request.body]
+                .into_iter()
+                .chain(request.headers.iter().map(|h| &h.value));
+            for text in texts {
+                for capture in reference.captures_iter(text) {
+                    let name = capture[1].to_string();
+                    let list = if name.starts_with('$') {
+                        &mut dynamic
+                    } else if name.contains(".response.") || name.contains(".request.") {
+                        &mut chained
+                    } else {
+                        continue;
+                    };
+                    if !list.contains(&name) {
+                        list.push(name);
+                    }
+                }
+            }
+        }
+        if !dynamic.is_empty() {
+            self.warnings
+                .push(format!("System variables are not generated: {}", dynamic.join(", ")));
+        }
+        if !chained.is_empty() {
+            self.warnings.push(format!(
+                "Request variables are not resolved: {}",
+                chained.join(", ")
+            ));
+        }
+    }
+
+    pub fn requests(&self) -> impl Iterator<Item = &Request> {
+        self.blocks
+            .iter()
+            .filter_map(|block| block.request.as_ref().map(|r| &r.request))
+    }
+
+    /// Parts of the file freeman keeps but does not act on
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// The requests as a collection named `name`
+    pub fn collection(&self, name: &str) -> Collection {
+        let mut collection = Collection::new(name);
+        collection.requests = self.requests().cloned().collect();
+        collection
+    }
+
+    /// The file variables as an environment named `name`. Values that
+    /// reference earlier variables are resolved, as the file is read.
+    pub fn environment(&self, name: &str) -> Environment {
+        let mut environment = Environment::new(name);
+        for (key, value) in &self.variables {
+            let value = environment.substitute(value);
+            environment.set(key.clone(), value);
+        }
+        environment
+    }
+
+    /// Take the values of `environment` for the file's variables, and
+    /// add the ones it does not define. Values equal to what the file
+    /// resolves to keep their definitions as written, references included.
+    pub fn update_variables(&mut self, environment: &Environment) {
+        let current = self.environment(&environment.name);
+        let mut changed: Vec<(&String, &String)> = environment
+            .variables
+            .iter()
+            .filter(|(key, value)| current.get(key) != Some(*value))
+            .collect();
+        changed.sort();
+        for (key, value) in changed {
+            match self.variables.iter_mut().filter(|(k, _)| k == key).last() {
+                Some((_, old)) => *old = value.clone(),
+                None => self.variables.push((key.clone(), value.clone())),
+            }
+            self.edited_variables.insert(key.clone());
+        }
+    }
+
+    /// Replace the `index`-th request. Its lines are rewritten on save
+    /// unless it renders the same as before.
+    pub fn update(&mut self, index: usize, request: &Request) -> Result<()> {
+        let Some(block) = self
+            .blocks
+            .iter_mut()
+            .filter_map(|block| block.request.as_mut())
+            .nth(index)
+        else {
+            bail!("The file has no request {}", index + 1);
+        };
+        block.request = Request {
+            name: block.request.name.clone(),
+            ..request.clone()
+        };
+        Ok(())
+    }
+
+    /// The file text, with edited requests and variables rewritten;
+    /// variables the file did not define go at the top
+    pub fn render(&self) -> String {
+        let eol = self.line_ending;
+        let mut out = String::new();
+        let mut defined = BTreeSet::new();
+        for block in &self.blocks {
+            for line in block.prefix.split_inclusive('\n') {
+                match definition(line.trim()).filter(|(key, _)| self.edited_variables.contains(*key)) {
+                    Some((key, _)) => {
+                        let indent = &line[..line.len() - line.trim_start().len()];
+                        out.push_str(&format!("{}@{} = {}{}", indent, key, self.variable(key), eol));
+                        defined.insert(key);
+                    }
+                    None => out.push_str(line),
+                }
+            }
+            if let Some(request) = &block.request {
+                let comments = header_comments(&request.source);
+                let version = request.version.as_deref();
+                let rendered = write_request(&request.request, version, &comments);
+                match parse_request(&request.source) {
+                    Some((original, _)) if write_request(&original, version, &comments) == rendered => {
+                        out.push_str(&request.source)
+                    }
+                    _ => out.push_str(&rendered.replace("\r\n", "\n").replace('\n', eol)),
+                }
+            }
+            out.push_str(&block.suffix);
+        }
+        let added: String = self
+            .edited_variables
+            .iter()
+            .filter(|key| !defined.contains(key.as_str()))
+            .map(|key| format!("@{} = {}{}", key, self.variable(key), eol))
+            .collect();
+        if !added.is_empty() {
+            out.insert_str(0, &format!("{}{}", added, eol));
+        }
+        out
+    }
+
+    /// The value of the last definition of a variable, as written
+    fn variable(&self, key: &str) -> &str {
+        self.variables
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map_or("", |(_, value)| value.as_str())
+    }
+}
+
+/// Where the trailing blank lines, comments and response handlers
+/// (`> {% ... %}`, `> handler.js`, `>> output.json`) of a block start.
+/// Comments right under the last line of the request, with no blank line
+/// between, belong to it.
+fn suffix_start(lines: &[&str]) -> usize {
+    let handler = lines
+        .iter()
+        .skip(1)
+        .position(|line| line.starts_with('>') || line.starts_with("<>"))
+        .map_or(lines.len(), |pos| pos + 1);
+    let mut start = handler;
+    while start > 1 {
+        let line = lines[start - 1].trim();
+        if !line.is_empty() && comment(line).is_none() {
+            break;
+        }
+        start -= 1;
+    }
+    lines[start..handler]
+        .iter()
+        .position(|line| line.trim().is_empty())
+        .map_or(handler, |blank| start + blank)
+}
+
+/// The key and value of an `@key = value` line
+fn definition(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.strip_prefix('@')?.split_once('=')?;
+    Some((key.trim(), value.trim()))
+}
+
+/// A header commented out with `#` or `//`, given the comment's text
+fn disabled_header(comment: &str) -> Option<Header> {
+    let (key, value) = comment.split_once(':')?;
+    let key = key.trim();
+    let token = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    token.then(|| Header {
+        enabled: false,
+        ..Header::new(key, value.trim())
+    })
+}
+
+/// Comment lines among a request's headers that are not disabled headers,
+/// each with the number of headers above it
+fn header_comments(source: &str) -> Vec<(usize, String)> {
+    let mut comments = Vec::new();
+    let mut headers = 0;
+    let lines = source
+        .lines()
+        .skip(1)
+        .skip_while(|line| line.trim_start().starts_with(['?', '&']));
+    for line in lines {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            break;
+        }
+        match comment(trimmed) {
+            Some(text) if disabled_header(text).is_none() => comments.push((headers, line.trim_end().to_string())),
+            _ => headers += 1,
+        }
+    }
+    comments
+}
+
+/// The text of a `#` or `//` comment line
+fn comment(line: &str) -> Option<&str> {
+    if line.starts_with("###") {
+        return None;
+    }
+    line.strip_prefix('#')
+        .or_else(|| line.strip_prefix("//"))
+        .map(str::trim)
+}
+
+/// The value of an `@key value` / `@key = value` comment
+fn metadata<'a>(comment: &'a str, key: &str) -> Option<&'a str> {
+    let rest = comment.strip_prefix('@')?.strip_prefix(key)?;
+    if !rest.is_empty() && !rest.starts_with([' ', '=', '\t']) {
+        return None;
+    }
+    Some(rest.trim_start().trim_start_matches('=').trim())
+}
+
+/// Parse a request line, its headers and body. `None` when the method is
+/// one freeman cannot send.
+fn parse_request(source: &str) -> Option<(Request, Option<String>)> {
+    let mut lines = source.lines();
+    let line = lines.next().unwrap_or_default().trim();
+    let mut tokens: Vec<&str> = line.split_whitespace().collect();
+    let version = match tokens.last() {
+        Some(last) if tokens.len() > 1 && last.starts_with("HTTP/") => tokens.pop().map(str::to_string),
+        _ => None,
+    };
+    let (method, mut url) = match tokens.as_slice() {
+        [url] => (HttpMethod::GET, url.to_string()),
+        [method, url] => (HttpMethod::parse(method)?, url.to_string()),
+        _ => return None,
+    };
+
+    let mut request = Request {
+        method,
+        headers: Vec::new(),
+        ..Request::default()
+    };
+    let mut lines = lines.peekable();
+    // Long query strings may continue on lines starting with ? or &
+    while let Some(line) = lines.next_if(|l| l.trim_start().starts_with(['?', '&'])) {
+        url.push_str(line.trim());
+    }
+    request.url = url;
+
+    for line in lines.by_ref() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            break;
+        }
+        if let Some(text) = comment(trimmed) {
+            request.headers.extend(disabled_header(text));
+            continue;
+        }
+        if let Some((key, value)) = trimmed.split_once(':') {
+            request.headers.push(Header::new(key.trim(), value.trim()));
+        }
+    }
+    request.body = lines.collect::<Vec<_>>().join("\n");
+    Some((request, version))
+}
+
+/// Write a request in the file format, with the header block's comments
+/// where they were. Settings the format has no syntax for (proxy, TLS,
+/// host overrides) are left out.
+fn write_request(request: &Request, version: Option<&str>, comments: &[(usize, String)]) -> String {
+    let mut out = format!("{} {}", request.method.as_str(), request.url);
+    if let Some(version) = version {
+        out.push(' ');
+        out.push_str(version);
+    }
+    out.push('\n');
+    let mut comments = comments.iter().peekable();
+    for (index, header) in request.headers.iter().enumerate() {
+        while let Some((_, comment)) = comments.next_if(|(above, _)| *above <= index) {
+            out.push_str(&format!("{}\n", comment));
+        }
+        let disabled = if header.enabled { "" } else { "# " };
+        out.push_str(&format!("{}{}: {}\n", disabled, header.key, header.value));
+    }
+    for (_, comment) in comments {
+        out.push_str(&format!("{}\n", comment));
+    }
+    match &request.auth {
+        AuthType::None => {}
+        AuthType::Bearer(token) => out.push_str(&format!("Authorization: Bearer {}\n", token)),
+        // Both REST Client and JetBrains accept `Basic user password`
+        AuthType::Basic { username, password } => {
+            out.push_str(&format!("Authorization: Basic {} {}\n", username, password))
+        }
+    }
+    if !request.body.is_empty() {
+        out.push('\n');
+        out.push_str(&request.body);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = "\
+@host = api.example.com
+@baseUrl = https://{{host}}/v1
+
+# Users
+### List users
+GET {{baseUrl}}/users
+    ?page=2
+    &limit=10
+Accept: application/json
+
+###
+# @name login
+POST {{baseUrl}}/login HTTP/1.1
+Content-Type: application/json
+
+{
+  \"user\": \"{{$randomInt}}\"
+}
+
+> {% client.global.set(\"token\", response.body.token); %}
+
+### Profile
+// comment kept as is
+https://{{host}}/me
+Authorization: Bearer {{login.response.body.token}}
+";
+
+    #[test]
+    fn test_parse() {
+        let file = HttpFile::parse(FILE).unwrap();
+        let requests: Vec<&Request> = file.requests().collect();
+        assert_eq!(requests.len(), 3);
+
+        assert_eq!(requests[0].name, "List users");
+        assert_eq!(requests[0].url, "{{baseUrl}}/users?page=2&limit=10");
+        assert_eq!(requests[0].headers[0].key, "Accept");
+
+        assert_eq!(requests[1].name, "login");
+        assert_eq!(requests[1].method, HttpMethod::POST);
+        assert_eq!(requests[1].body, "{\n  \"user\": \"{{$randomInt}}\"\n}");
+
+        assert_eq!(requests[2].name, "Profile");
+        assert_eq!(requests[2].method, HttpMethod::GET);
+
+        let environment = file.environment("users.http");
+        assert_eq!(
+            environment.get("baseUrl").map(String::as_str),
+            Some("https://api.example.com/v1")
+        );
+        assert_eq!(file.warnings().len(), 3);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let file = HttpFile::parse(FILE).unwrap();
+        assert_eq!(file.render(), FILE);
+    }
+
+    #[test]
+    fn test_update_rewrites_only_the_edited_request() {
+        let mut file = HttpFile::parse(FILE).unwrap();
+        let mut login = file.requests().nth(1).unwrap().clone();
+        login.body = "{}".to_string();
+        login.headers.push(Header::new("X-Trace", "1"));
+        file.update(1, &login).unwrap();
+
+        let expected = FILE.replace(
+            "Content-Type: application/json\n\n{\n  \"user\": \"{{$randomInt}}\"\n}\n",
+            "Content-Type: application/json\nX-Trace: 1\n\n{}\n",
+        );
+        assert_eq!(file.render(), expected);
+        assert_eq!(file.requests().nth(1).unwrap().name, "login");
+        assert!(file.update(3, &login).is_err());
+    }
+
+    #[test]
+    fn test_update_keeps_crlf_line_endings() {
+        let content = FILE.replace('\n', "\r\n");
+        let mut file = HttpFile::parse(&content).unwrap();
+        assert_eq!(file.render(), content);
+
+        let mut login = file.requests().nth(1).unwrap().clone();
+        login.body = "{\n  \"user\": \"ann\"\r\n}".to_string();
+        file.update(1, &login).unwrap();
+        let mut environment = file.environment("users.http");
+        environment.set("host", "staging.example.com");
+        environment.set("token", "abc");
+        file.update_variables(&environment);
+
+        let rendered = file.render();
+        assert!(rendered.contains("{\r\n  \"user\": \"ann\"\r\n}\r\n"));
+        assert!(rendered.starts_with("@token = abc\r\n\r\n@host = staging.example.com\r\n"));
+        assert!(!rendered.replace("\r\n", "").contains('\n'));
+        assert!(!rendered.contains("\r\r"));
+    }
+
+    #[test]
+    fn test_save_keeps_comments_disabled_headers_and_variables() {
+        let content = "\
+@host = api.example.com
+@url = https://{{host}}
+
+### Create
+POST {{url}}/items
+# sent by the CLI too
+Content-Type: application/json
+# X-Debug: 1
+// Accept: text/plain
+
+{\"id\": 1}
+# not a comment: the body ends here
+
+# Trailing note
+";
+        let mut file = HttpFile::parse(content).unwrap();
+        assert_eq!(file.render(), content);
+        let mut create = file.requests().next().unwrap().clone();
+        assert_eq!(create.headers.iter().filter(|h| !h.enabled).count(), 2);
+        assert_eq!(create.body, "{\"id\": 1}\n# not a comment: the body ends here");
+
+        create.headers.retain(|h| h.key != "Accept");
+        create.headers.push(Header::new("X-Trace", "1"));
+        create.body = "{}".to_string();
+        file.update(0, &create).unwrap();
+        let mut environment = file.environment("items.http");
+        environment.set("host", "staging.example.com");
+        environment.set("token", "abc");
+        file.update_variables(&environment);
+
+        let expected = "\
+@token = abc
+
+@host = staging.example.com
+@url = https://{{host}}
+
+### Create
+POST {{url}}/items
+# sent by the CLI too
+Content-Type: application/json
+# X-Debug: 1
+X-Trace: 1
+
+{}
+
+# Trailing note
+";
+        assert_eq!(file.render(), expected);
+
+        let reopened = HttpFile::parse(expected).unwrap();
+        let reopened_request = reopened.requests().next().unwrap();
+        let headers: Vec<(&str, bool)> =
+            reopened_request.headers.iter().map(|h| (h.key.as_str(), h.enabled)).collect();
+        assert_eq!(headers, vec![("Content-Type", true), ("X-Debug", false), ("X-Trace", true)]);
+        let url = reopened.environment("items.http").get("url").cloned();
+        assert_eq!(url.as_deref(), Some("https://staging.example.com"));
+    }
+}
@@ -0,0 +1,655 @@
+//! Insomnia v4 export (JSON or YAML) import
+
+use anyhow::{bail, Result};
+use regex::Regex;
+use serde_json::Value;
+
+use crate::curl::{form_part, set_default_header, FORM_BOUNDARY};
+use crate::interop::{
+    add_path_variable, array, enabled, encode_form, file_placeholder_part, file_placeholder_warning,
+    path_variables_to_placeholders, text, Imported,
+};
+use crate::models::{AuthType, Collection, Environment, Header, HttpMethod, Request};
+
+/// Whether a document is an Insomnia export
+pub fn is_insomnia(value: &Value) -> bool {
+    value.get("_type").and_then(Value::as_str) == Some("export")
+        || value
+            .get("type")
+            .and_then(Value::as_str)
+            .is_some_and(|kind| kind.starts_with("collection.insomnia.rest/"))
+}
+
+/// Convert every workspace into a collection. A workspace's base
+/// environment becomes an environment named after it, each sub
+/// environment one named `<workspace> - <name>` holding the base
+/// variables too.
+pub fn import(value: &Value) -> Result<Imported> {
+    if let Some(kind) = value.get("type").and_then(Value::as_str) {
+        bail!(
+            "Insomnia {} exports are not supported; export the collection in the Insomnia v4 format",
+            kind
+        );
+    }
+    let format = value.get("__export_format").and_then(Value::as_u64).unwrap_or(0);
+    if format < 3 {
+        bail!("Unsupported Insomnia export format {}; export it as Insomnia v4", format);
+    }
+
+    let mut importer = Importer {
+        resources: array(value.get("resources")),
+        template: Regex::new(r"\{\{\s*(?:_\.)?([A-Za-z0-9_\-\.]+)\s*\}\}").expect("valid regex"),
+        variables: Environment::default(),
+        warnings: Vec::new(),
+    };
+    let mut imported = Imported::default();
+
+    let workspaces: Vec<&Value> = importer.of_type("workspace").collect();
+    for workspace in workspaces {
+        let id = text(workspace.get("_id"));
+        let name = workspace
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or("Insomnia workspace");
+        importer.variables = Environment::new(name);
+        let first_environment = imported.environments.len();
+
+        let base_environments: Vec<&Value> = importer
+            .of_type("environment")
+            .filter(|env| text(env.get("parentId")) == id)
+            .collect();
+        for base in &base_environments {
+            for (key, value) in flatten(base.get("data")) {
+                importer.variables.set(key, value);
+            }
+        }
+        let collection = importer.folder(&id, name, None, &[], name);
+        imported.collections.push(collection);
+
+        // Sub-environments start from the base with the folder variables
+        // and path parameter examples the walk added
+        for base in &base_environments {
+            let base_id = text(base.get("_id"));
+            let sub_environments: Vec<&Value> = importer
+                .of_type("environment")
+                .filter(|env| text(env.get("parentId")) == base_id)
+                .collect();
+            for sub in sub_environments {
+                let sub_name = text(sub.get("name"));
+                let mut environment = importer.variables.clone();
+                environment.name = format!("{} - {}", name, sub_name);
+                for (key, value) in flatten(sub.get("data")) {
+                    environment.set(key, value);
+                }
+                imported.environments.push(environment);
+            }
+        }
+
+        let variables = std::mem::take(&mut importer.variables);
+        if !variables.variables.is_empty() {
+            // Listed ahead of the workspace's sub-environments
+            imported.environments.insert(first_environment, variables);
+        }
+    }
+
+    for (kind, label) in [
+        ("grpc_request", "gRPC request(s)"),
+        ("websocket_request", "WebSocket request(s)"),
+        ("unit_test_suite", "test suite(s)"),
+    ] {
+        let count = importer.of_type(kind).count();
+        if count > 0 {
+            importer.warnings.push(format!("{} {}", count, label));
+        }
+    }
+    imported.warnings = importer.warnings;
+    Ok(imported)
+}
+
+/// Turns the flat resource list into a tree
+struct Importer<'a> {
+    resources: &'a [Value],
+    /// `{{ _.name }}` and `{{ name }}` references
+    template: Regex,
+    /// Variables of the workspace being imported
+    variables: Environment,
+    warnings: Vec<String>,
+}
+
+impl<'a> Importer<'a> {
+    fn of_type(&self, kind: &'a str) -> impl Iterator<Item = &'a Value> {
+        self.resources
+            .iter()
+            .filter(move |resource| resource.get("_type").and_then(Value::as_str) == Some(kind))
+    }
+
+    /// Requests and folders under `parent_id`, in Insomnia's sort order
+    fn children(&self, parent_id: &str) -> Vec<&'a Value> {
+        let mut children: Vec<&Value> = self
+            .resources
+            .iter()
+            .filter(|resource| text(resource.get("parentId")) == parent_id)
+            .filter(|resource| {
+                matches!(
+                    resource.get("_type").and_then(Value::as_str),
+                    Some("request" | "request_group")
+                )
+            })
+            .collect();
+        children.sort_by(|a, b| {
+            let key = |r: &Value| r.get("metaSortKey").and_then(Value::as_f64).unwrap_or(0.0);
+            key(a).total_cmp(&key(b))
+        });
+        children
+    }
+
+    /// Convert a workspace or folder. Folder auth and headers apply to
+    /// the requests inside unless they set their own.
+    fn folder(
+        &mut self,
+        id: &str,
+        name: &str,
+        parent_auth: Option<&'a Value>,
+        parent_headers: &[Header],
+        path: &str,
+    ) -> Collection {
+        let mut collection = Collection::new(name);
+        for child in self.children(id) {
+            let child_id = text(child.get("_id"));
+            let child_name = child.get("name").and_then(Value::as_str).unwrap_or("Untitled");
+            let child_path = format!("{} / {}", path, child_name);
+            self.check_scripts(child, &child_path);
+
+            if child.get("_type").and_then(Value::as_str) == Some("request_group") {
+                for (key, value) in flatten(child.get("environment")) {
+                    if self.variables.get(&key).is_some_and(|existing| *existing != value) {
+                        self.warnings.push(format!(
+                            "{}: folder variable '{}' differs from the workspace value, which was kept",
+                            child_path, key
+                        ));
+                    } else {
+                        self.variables.set(key, value);
+                    }
+                }
+                let auth = inherited(child.get("authentication")).or(parent_auth);
+                let mut headers = self.headers(child);
+                headers.extend(parent_headers.iter().cloned());
+                collection
+                    .folders
+                    .push(self.folder(&child_id, child_name, auth, &headers, &child_path));
+            } else {
+                let request = self.request(child_name, child, parent_auth, parent_headers, &child_path);
+                collection.requests.push(request);
+            }
+        }
+        collection
+    }
+
+    fn request(
+        &mut self,
+        name: &str,
+        resource: &Value,
+        parent_auth: Option<&Value>,
+        parent_headers: &[Header],
+        path: &str,
+    ) -> Request {
+        let mut request = Request {
+            name: name.to_string(),
+            headers: self.headers(resource),
+            ..Request::default()
+        };
+        for header in parent_headers {
+            if !request.headers.iter().any(|h| h.key.eq_ignore_ascii_case(&header.key)) {
+                request.headers.push(header.clone());
+            }
+        }
+
+        let method = text(resource.get("method"));
+        request.method = HttpMethod::parse(&method).unwrap_or_else(|| {
+            self.warnings.push(format!("{}: {} method (sent as GET)", path, method));
+            HttpMethod::GET
+        });
+
+        let mut url = self.convert(&text(resource.get("url")));
+        let query: Vec<String> = enabled(resource.get("parameters"))
+            .map(|param| {
+                format!(
+                    "{}={}",
+                    encode_form(&self.convert(&text(param.get("name")))),
+                    encode_form(&self.convert(&text(param.get("value"))))
+                )
+            })
+            .collect();
+        if !query.is_empty() {
+            let separator = if url.contains('?') { '&' } else { '?' };
+            url = format!("{}{}{}", url, separator, query.join("&"));
+        }
+        for param in array(resource.get("pathParameters")) {
+            let value = self.convert(&text(param.get("value")));
+            if !value.is_empty() {
+                add_path_variable(&mut self.variables, &mut self.warnings, text(param.get("name")), value);
+            }
+        }
+        request.url = path_variables_to_placeholders(&url);
+
+        if let Some(body) = resource.get("body") {
+            self.body(body, &mut request, path);
+        }
+        if let Some(auth) = inherited(resource.get("authentication")).or(parent_auth) {
+            self.apply_auth(auth, &mut request, path);
+        }
+        if resource.pointer("/settingDisableRenderRequestBody") == Some(&Value::Bool(true)) {
+            self.warnings.push(format!("{}: disabled template rendering of the body", path));
+        }
+
+        let has_tags = [&request.url, &request.body]
+            .into_iter()
+            .chain(request.headers.iter().map(|h| &h.value))
+            .any(|text| text.contains("{%"));
+        if has_tags {
+            self.warnings.push(format!("{}: template tags such as {{% uuid %}} (left as written)", path));
+        }
+        request
+    }
+
+    fn headers(&self, resource: &Value) -> Vec<Header> {
+        array(resource.get("headers"))
+            .iter()
+            .map(|header| Header {
+                key: self.convert(&text(header.get("name"))),
+                value: self.convert(&text(header.get("value"))),
+                enabled: header.get("disabled").and_then(Value::as_bool) != Some(true),
+            })
+            .collect()
+    }
+
+    fn body(&mut self, body: &Value, request: &mut Request, path: &str) {
+        let mime = text(body.get("mimeType"));
+        match mime.as_str() {
+            "application/x-www-form-urlencoded" => {
+                request.body = enabled(body.get("params"))
+                    .map(|param| {
+                        format!(
+                            "{}={}",
+                            encode_form(&self.convert(&text(param.get("name")))),
+                            encode_form(&self.convert(&text(param.get("value"))))
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("&");
+                set_default_header(request, "Content-Type", &mime);
+            }
+            "multipart/form-data" => {
+                let mut parts = Vec::new();
+                for param in enabled(body.get("params")) {
+                    let name = self.convert(&text(param.get("name")));
+                    if param.get("type").and_then(Value::as_str) == Some("file") {
+                        let file = text(param.get("fileName"));
+                        parts.push(file_placeholder_part(&name, &file));
+                        self.warnings.push(file_placeholder_warning(path, &name, &file));
+                        continue;
+                    }
+                    match form_part(&format!("{}={}", name, self.convert(&text(param.get("value")))), true) {
+                        Ok(part) => parts.push(part),
+                        Err(e) => self
+                            .warnings
+                            .push(format!("{}: form field '{}' ({:#})", path, name, e)),
+                    }
+                }
+                request.body = format!("{}--{}--\r\n", parts.concat(), FORM_BOUNDARY);
+                request.headers.retain(|h| !h.key.eq_ignore_ascii_case("content-type"));
+                request.headers.push(Header::new(
+                    "Content-Type",
+                    format!("multipart/form-data; boundary={}", FORM_BOUNDARY),
+                ));
+            }
+            _ if body.get("fileName").is_some() && body.get("text").is_none() => {
+                self.warnings.push(format!("{}: file body", path));
+            }
+            _ => {
+                request.body = self.convert(&text(body.get("text")));
+                let content_type = match mime.as_str() {
+                    // The text is already the JSON payload of the request
+                    "application/graphql" => "application/json",
+                    mime => mime,
+                };
+                if !content_type.is_empty() {
+                    set_default_header(request, "Content-Type", content_type);
+                }
+            }
+        }
+    }
+
+    fn apply_auth(&mut self, auth: &Value, request: &mut Request, path: &str) {
+        if auth.get("disabled").and_then(Value::as_bool) == Some(true) {
+            return;
+        }
+        let param = |key: &str| self.convert(&text(auth.get(key)));
+        match auth.get("type").and_then(Value::as_str).unwrap_or("none") {
+            "none" => {}
+            "bearer" => {
+                let prefix = param("prefix");
+                if prefix.is_empty() || prefix.eq_ignore_ascii_case("bearer") {
+                    request.auth = AuthType::Bearer(param("token"));
+                } else {
+                    let value = format!("{} {}", prefix, param("token"));
+                    request.headers.push(Header::new("Authorization", value));
+                }
+            }
+            "basic" => {
+                request.auth = AuthType::Basic {
+                    username: param("username"),
+                    password: param("password"),
+                }
+            }
+            "apikey" => {
+                let (key, value) = (param("key"), param("value"));
+                match text(auth.get("addTo")).as_str() {
+                    "queryParams" => {
+                        let separator = if request.url.contains('?') { '&' } else { '?' };
+                        request.url =
+                            format!("{}{}{}={}", request.url, separator, encode_form(&key), encode_form(&value));
+                    }
+                    "cookie" => request.headers.push(Header::new("Cookie", format!("{}={}", key, value))),
+                    _ => request.headers.push(Header::new(key, value)),
+                }
+            }
+            other => self.warnings.push(format!("{}: {} auth", path, other)),
+        }
+    }
+
+    fn check_scripts(&mut self, resource: &Value, path: &str) {
+        for (key, label) in [
+            ("preRequestScript", "pre-request script"),
+            ("afterResponseScript", "after-response script"),
+        ] {
+            if !text(resource.get(key)).trim().is_empty() {
+                self.warnings.push(format!("{}: {}", path, label));
+            }
+        }
+    }
+
+    /// Rewrite Insomnia's `{{ _.name }}` references as `{{name}}`
+    fn convert(&self, s: &str) -> String {
+        self.template.replace_all(s, "{{$1}}").into_owned()
+    }
+}
+
+/// An auth object that is set, as opposed to inheriting from the folder
+fn inherited(auth: Option<&Value>) -> Option<&Value> {
+    auth.filter(|auth| auth.as_object().is_some_and(|fields| !fields.is_empty()))
+}
+
+/// Environment data as `key`/`value` pairs; nested objects become
+/// dotted keys, as Insomnia references them (`{{ _.api.host }}`)
+fn flatten(data: Option<&Value>) -> Vec<(String, String)> {
+    fn walk(prefix: &str, value: &Value, out: &mut Vec<(String, String)>) {
+        match value {
+            Value::Object(fields) => {
+                for (key, value) in fields {
+                    let key = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    walk(&key, value, out);
+                }
+            }
+            value => out.push((prefix.to_string(), text(Some(value)))),
+        }
+    }
+    let mut out = Vec::new();
+    if let Some(data) = data {
+        walk("", data, &mut out);
+    }
+    out.retain(|(key, _)| !key.is_empty());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = r#"
+_type: export
+__export_format: 4
+resources:
+  - _id: wrk_1
+    _type: workspace
+    parentId: null
+    name: Billing
+  - _id: env_base
+    _type: environment
+    parentId: wrk_1
+    name: Base Environment
+    data:
+      baseUrl: http://localhost:4000
+      api:
+        version: v2
+  - _id: env_prod
+    _type: environment
+    parentId: env_base
+    name: Production
+    data:
+      baseUrl: https://billing.example.com
+  - _id: fld_1
+    _type: request_group
+    parentId: wrk_1
+    name: Invoices
+    metaSortKey: -10
+    environment:
+      tenant: acme
+    authentication:
+      type: bearer
+      token: "{{ _.token }}"
+    headers:
+      - name: X-Tenant
+        value: acme
+  - _id: req_2
+    _type: request
+    parentId: fld_1
+    name: Create invoice
+    metaSortKey: 2
+    method: POST
+    url: "{{ _.baseUrl }}/{{ _.api.version }}/invoices"
+    body:
+      mimeType: application/json
+      text: '{"amount": 10}'
+    headers: []
+    authentication: {}
+  - _id: req_1
+    _type: request
+    parentId: fld_1
+    name: List invoices
+    metaSortKey: 1
+    method: GET
+    url: "{{_.baseUrl}}/invoices"
+    parameters:
+      - name: page
+        value: "2"
+      - name: debug
+        value: "1"
+        disabled: true
+    authentication:
+      type: basic
+      username: admin
+      password: "{{ _.password }}"
+    preRequestScript: "insomnia.environment.set('x', 1)"
+  - _id: req_3
+    _type: request
+    parentId: wrk_1
+    name: Health
+    method: GET
+    url: "{{ _.baseUrl }}/health/:check"
+    pathParameters:
+      - name: check
+        value: db
+  - _id: ws_1
+    _type: websocket_request
+    parentId: wrk_1
+    name: Events
+"#;
+
+    #[test]
+    fn test_import_workspace() {
+        let value: Value = serde_yaml::from_str(EXPORT).unwrap();
+        assert!(is_insomnia(&value));
+        let imported = import(&value).unwrap();
+
+        let billing = &imported.collections[0];
+        assert_eq!(billing.name, "Billing");
+        let health = &billing.requests[0];
+        assert_eq!(health.url, "{{baseUrl}}/health/{{check}}");
+
+        let invoices = &billing.folders[0];
+        let list = &invoices.requests[0];
+        assert_eq!(list.name, "List invoices");
+        assert_eq!(list.url, "{{baseUrl}}/invoices?page=2");
+        assert_eq!(
+            list.auth,
+            AuthType::Basic { username: "admin".to_string(), password: "{{password}}".to_string() }
+        );
+        assert!(list.headers.iter().any(|h| h.key == "X-Tenant"));
+
+        let create = &invoices.requests[1];
+        assert_eq!(create.url, "{{baseUrl}}/{{api.version}}/invoices");
+        assert_eq!(create.auth, AuthType::Bearer("{{token}}".to_string()));
+        assert!(create.headers.iter().any(|h| h.value == "application/json"));
+
+        let base = &imported.environments[0];
+        assert_eq!(base.name, "Billing");
+        assert_eq!(base.get("api.version").map(String::as_str), Some("v2"));
+        assert_eq!(base.get("check").map(String::as_str), Some("db"));
+        let production = &imported.environments[1];
+        assert_eq!(production.name, "Billing - Production");
+        assert_eq!(production.get("baseUrl").map(String::as_str), Some("https://billing.example.com"));
+        assert_eq!(production.get("api.version").map(String::as_str), Some("v2"));
+        assert_eq!(production.get("tenant").map(String::as_str), Some("acme"));
+        assert_eq!(production.get("check").map(String::as_str), Some("db"));
+
+        assert_eq!(
+            imported.warnings,
+            vec![
+                "Billing / Invoices / List invoices: pre-request script".to_string(),
+                "1 WebSocket request(s)".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_environment_override_order() {
+        let export = serde_json::json!({
+            "_type": "export",
+            "__export_format": 4,
+            "resources": [
+                { "_id": "wrk_1", "_type": "workspace", "name": "Shop" },
+                {
+                    "_id": "env_base",
+                    "_type": "environment",
+                    "parentId": "wrk_1",
+                    "data": { "host": "localhost", "tenant": "base" }
+                },
+                {
+                    "_id": "env_staging",
+                    "_type": "environment",
+                    "parentId": "env_base",
+                    "name": "Staging",
+                    "data": { "tenant": "staging" }
+                },
+                {
+                    "_id": "fld_1",
+                    "_type": "request_group",
+                    "parentId": "wrk_1",
+                    "name": "Orders",
+                    "environment": { "tenant": "folder", "region": "eu" }
+                }
+            ]
+        });
+
+        let imported = import(&export).unwrap();
+        let base = &imported.environments[0];
+        assert_eq!(base.get("tenant").map(String::as_str), Some("base"));
+        assert_eq!(base.get("region").map(String::as_str), Some("eu"));
+        let staging = &imported.environments[1];
+        assert_eq!(staging.name, "Shop - Staging");
+        assert_eq!(staging.get("tenant").map(String::as_str), Some("staging"));
+        assert_eq!(staging.get("host").map(String::as_str), Some("localhost"));
+        assert_eq!(staging.get("region").map(String::as_str), Some("eu"));
+        assert_eq!(
+            imported.warnings,
+            vec!["Shop / Orders: folder variable 'tenant' differs from the workspace value, which was kept"
+                .to_string()]
+        );
+    }
+
+    #[test]
+    fn test_import_encodes_query_and_keeps_workspace_order() {
+        let export = serde_json::json!({
+            "_type": "export",
+            "__export_format": 4,
+            "resources": [
+                { "_id": "wrk_1", "_type": "workspace", "name": "First" },
+                { "_id": "env_1", "_type": "environment", "parentId": "wrk_1", "data": { "host": "one.test" } },
+                { "_id": "env_1a", "_type": "environment", "parentId": "env_1", "name": "Dev", "data": {} },
+                { "_id": "wrk_2", "_type": "workspace", "name": "Second" },
+                { "_id": "env_2", "_type": "environment", "parentId": "wrk_2", "data": { "host": "two.test" } },
+                {
+                    "_id": "req_1",
+                    "_type": "request",
+                    "parentId": "wrk_1",
+                    "name": "Search",
+                    "method": "GET",
+                    "url": "https://{{ _.host }}/search",
+                    "parameters": [
+                        { "name": "q", "value": "a&b c" },
+                        { "name": "tag", "value": "{{ _.tag }}" }
+                    ],
+                    "authentication": { "type": "apikey", "key": "api key", "value": "k=1&x", "addTo": "queryParams" }
+                }
+            ]
+        });
+
+        let imported = import(&export).unwrap();
+        assert_eq!(
+            imported.collections[0].requests[0].url,
+            "https://{{host}}/search?q=a%26b%20c&tag={{tag}}&api%20key=k%3D1%26x"
+        );
+        let names: Vec<&str> = imported.environments.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["First", "First - Dev", "Second"]);
+    }
+
+    #[test]
+    fn test_import_form_file_is_not_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let secret = dir.path().join(".env");
+        std::fs::write(&secret, "API_KEY=hunter2").unwrap();
+        let export = serde_json::json!({
+            "_type": "export",
+            "__export_format": 4,
+            "resources": [
+                { "_id": "wrk_1", "_type": "workspace", "name": "Files" },
+                {
+                    "_id": "req_1",
+                    "_type": "request",
+                    "parentId": "wrk_1",
+                    "name": "Upload",
+                    "method": "POST",
+                    "url": "https://files.test",
+                    "body": {
+                        "mimeType": "multipart/form-data",
+                        "params": [
+                            { "name": "config", "type": "file", "fileName": secret.to_string_lossy() }
+                        ]
+                    }
+                }
+            ]
+        });
+
+        let imported = import(&export).unwrap();
+        let body = &imported.collections[0].requests[0].body;
+        assert!(!body.contains("hunter2"));
+        assert!(body.contains("name=\"config\"; filename=\".env\""));
+        assert_eq!(imported.warnings.len(), 1);
+        assert!(imported.warnings[0].contains("Files / Upload: form file 'config'"));
+    }
+}
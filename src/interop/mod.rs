@@ -0,0 +1,187 @@
+//! Conversion between freeman collections and other API tools' formats
+
+pub mod bruno;
+pub mod har;
+pub mod http_file;
+pub mod insomnia;
+pub mod openapi;
+pub mod postman;
+
+use crate::curl::{percent_encode, FORM_BOUNDARY};
+use crate::models::{Collection, Environment};
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+/// Collections and environments read from another tool's export, with
+/// notes on everything that could not be converted
+#[derive(Debug, Default)]
+pub struct Imported {
+    pub collections: Vec<Collection>,
+    pub environments: Vec<Environment>,
+    pub warnings: Vec<String>,
+}
+
+impl Imported {
+    /// Human readable summary for the response panel
+    pub fn summary(&self) -> String {
+        let requests: usize = self.collections.iter().map(Collection::request_count).sum();
+        let mut summary = format!(
+            "✓ Imported {} collection(s) with {} request(s) and {} environment(s)",
+            self.collections.len(),
+            requests,
+            self.environments.len()
+        );
+        for collection in &self.collections {
+            summary.push_str(&format!("\n  {} ({} requests)", collection.name, collection.request_count()));
+        }
+        for environment in &self.environments {
+            summary.push_str(&format!("\n  {} (environment)", environment.name));
+        }
+        if !self.warnings.is_empty() {
+            summary.push_str("\n\nNot converted:");
+            for warning in &self.warnings {
+                summary.push_str(&format!("\n  • {}", warning));
+            }
+        }
+        summary
+    }
+}
+
+/// A document written for another tool, with notes on everything that
+/// could not be represented in it
+#[derive(Debug)]
+pub struct Exported {
+    pub content: String,
+    pub warnings: Vec<String>,
+}
+
+/// Import an export file or collection folder, detecting its format.
+/// `filter` selects the entries of a HAR capture.
+pub fn import_path(path: &Path, filter: &har::HarFilter) -> Result<Imported> {
+    if path.is_dir() {
+        if !filter.is_empty() {
+            bail!("Filters only apply to HAR files: {}", path.display());
+        }
+        if bruno::is_collection(path) {
+            return bruno::import(path);
+        }
+        bail!("{} is not a Bruno collection (no bruno.json)", path.display());
+    }
+
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    // Insomnia exports may be YAML; JSON is tried first for better errors
+    let value: Value = match serde_json::from_str(&content) {
+        Ok(value) => value,
+        Err(json_error) => serde_yaml::from_str(&content).map_err(|_| {
+            anyhow::anyhow!("{} is neither JSON nor YAML: {}", path.display(), json_error)
+        })?,
+    };
+
+    if har::is_har(&value) {
+        let name = path.file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+        return har::import(&value, filter, &name);
+    }
+    if !filter.is_empty() {
+        bail!("Filters only apply to HAR files: {}", path.display());
+    }
+    if postman::is_postman(&value) {
+        return postman::import(&value);
+    }
+    if insomnia::is_insomnia(&value) {
+        return insomnia::import(&value);
+    }
+    bail!(
+        "Unrecognized file format: {}\n\n\
+         Supported: Postman collection v2.0/v2.1, Postman environment, \
+         Insomnia v4 export (JSON/YAML), Bruno collection folder, HAR 1.2",
+        path.display()
+    )
+}
+
+/// Replace `/:name` path segments (Postman, Insomnia and Bruno path
+/// parameters) with `{{name}}`
+pub(crate) fn path_variables_to_placeholders(url: &str) -> String {
+    let (path, query) = match url.find(['?', '#']) {
+        Some(pos) => url.split_at(pos),
+        None => (url, ""),
+    };
+    let variable = Regex::new(r"/:([A-Za-z_][A-Za-z0-9_\-]*)").expect("valid regex");
+    format!("{}{}", variable.replace_all(path, "/{{$1}}"), query)
+}
+
+/// Keep a path parameter's example value as an environment variable,
+/// noting values that differ between requests
+pub(crate) fn add_path_variable(
+    variables: &mut Environment,
+    warnings: &mut Vec<String>,
+    key: String,
+    value: String,
+) {
+    match variables.get(&key) {
+        Some(existing) if *existing != value => warnings.push(format!(
+            "Path variable '{}' has several example values; kept '{}'",
+            key, existing
+        )),
+        Some(_) => {}
+        None => variables.set(key, value),
+    }
+}
+
+/// A multipart part standing in for a file upload. Importers never read
+/// local files, so the part keeps the file name but has no content.
+pub(crate) fn file_placeholder_part(name: &str, path: &str) -> String {
+    let filename = Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n\
+         Content-Type: application/octet-stream\r\n\r\n\r\n",
+        FORM_BOUNDARY, name, filename
+    )
+}
+
+/// Note for a form file left empty by [`file_placeholder_part`]
+pub(crate) fn file_placeholder_warning(path: &str, name: &str, file: &str) -> String {
+    format!("{}: form file '{}' ({}) imported empty; attach its content before sending", path, name, file)
+}
+
+/// Percent-encode a form value, leaving `{{variable}}` references intact
+pub(crate) fn encode_form(s: &str) -> String {
+    let mut out = String::new();
+    let mut rest = s;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start..].find("}}") else {
+            break;
+        };
+        out.push_str(&percent_encode(&rest[..start]));
+        out.push_str(&rest[start..start + len + 2]);
+        rest = &rest[start + len + 2..];
+    }
+    out.push_str(&percent_encode(rest));
+    out
+}
+
+pub(crate) fn array(value: Option<&Value>) -> &[Value] {
+    value.and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default()
+}
+
+/// Entries of a key/value list that are not disabled
+pub(crate) fn enabled(value: Option<&Value>) -> impl Iterator<Item = &Value> {
+    array(value)
+        .iter()
+        .filter(|entry| entry.get("disabled").and_then(Value::as_bool) != Some(true))
+}
+
+/// A scalar as text; exports allow numbers and booleans in value fields
+pub(crate) fn text(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Null) | None => String::new(),
+        Some(other) => other.to_string(),
+    }
+}
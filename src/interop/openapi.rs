@@ -0,0 +1,656 @@
+//! OpenAPI 3.0 document generated from a collection, the reverse of
+//! `discovery::openapi`
+//!
+//! Paths come from request URLs, with `{{variable}}` segments (and
+//! numeric or UUID segments) as path parameters. Requests sharing a
+//! method and path template become one operation. Example responses are
+//! taken from history entries sent to a matching URL.
+//!
+//! Only server URLs are resolved with the environment. Other variables
+//! stay as `{{variable}}` placeholders, and credential headers and query
+//! parameters get no example, so a shared document holds no secrets.
+
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+
+use crate::interop::Exported;
+use crate::models::{AuthType, Collection, Environment, HistoryEntry, Request};
+
+/// Headers described by the operation itself (body media type,
+/// response media types, security) rather than as parameters
+const DESCRIBED_HEADERS: &[&str] = &["content-type", "accept", "authorization", "content-length"];
+
+/// Parts of header and query parameter names that mark credentials,
+/// whose values are never written as examples
+const CREDENTIAL_NAMES: &[&str] = &["auth", "cookie", "key", "password", "secret", "session", "signature", "token"];
+
+/// Generate an OpenAPI 3.0 document, as YAML or JSON. Variables in
+/// server URLs are resolved with `environment` when given.
+pub fn export(
+    collection: &Collection,
+    environment: Option<&Environment>,
+    history: &[HistoryEntry],
+    as_json: bool,
+) -> Result<Exported> {
+    let mut generator = Generator {
+        environment,
+        history,
+        paths: Map::new(),
+        servers: Vec::new(),
+        server_variables: Map::new(),
+        security: Map::new(),
+        warnings: Vec::new(),
+    };
+    generator.collection(collection, None);
+
+    let mut document = json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": collection.name,
+            "version": "1.0.0",
+            "description": format!("Generated by freeman from the {} collection", collection.name),
+        },
+    });
+    if !generator.servers.is_empty() {
+        document["servers"] = Value::Array(
+            generator
+                .servers
+                .iter()
+                .map(|url| match generator.server_variables.get(url.as_str()) {
+                    Some(variables) => json!({ "url": url, "variables": variables }),
+                    None => json!({ "url": url }),
+                })
+                .collect(),
+        );
+    }
+    document["paths"] = Value::Object(generator.paths);
+    if !generator.security.is_empty() {
+        document["components"] = json!({ "securitySchemes": generator.security });
+    }
+
+    let content = if as_json {
+        serde_json::to_string_pretty(&document)?
+    } else {
+        serde_yaml::to_string(&document)?
+    };
+    Ok(Exported {
+        content,
+        warnings: generator.warnings,
+    })
+}
+
+struct Generator<'a> {
+    environment: Option<&'a Environment>,
+    history: &'a [HistoryEntry],
+    paths: Map<String, Value>,
+    servers: Vec<String>,
+    /// Variables of servers whose URL is an unresolved `{{variable}}`
+    server_variables: Map<String, Value>,
+    security: Map<String, Value>,
+    warnings: Vec<String>,
+}
+
+impl Generator<'_> {
+    fn substitute(&self, text: &str) -> String {
+        match self.environment {
+            Some(environment) => environment.substitute(text),
+            None => text.to_string(),
+        }
+    }
+
+    /// Requests of a collection and its folders; folder names become tags
+    fn collection(&mut self, collection: &Collection, tag: Option<&str>) {
+        for request in &collection.requests {
+            self.request(request, tag);
+        }
+        for folder in &collection.folders {
+            self.collection(folder, Some(&folder.name));
+        }
+    }
+
+    fn request(&mut self, request: &Request, tag: Option<&str>) {
+        let (Some(target), Some(resolved)) = (
+            Target::parse(&request.url),
+            Target::parse(&self.substitute(&request.url)),
+        ) else {
+            self.warnings
+                .push(format!("{}: not an HTTP URL ({})", request.name, request.url));
+            return;
+        };
+        self.add_server(&self.substitute(&target.origin));
+
+        let method = request.method.as_str().to_ascii_lowercase();
+        let path = target.template();
+        let entry = self
+            .paths
+            .entry(path.clone())
+            .or_insert_with(|| Value::Object(Map::new()));
+        if entry.get(&method).is_some() {
+            self.warnings.push(format!(
+                "{}: {} {} is already described by another request",
+                request.name,
+                method.to_uppercase(),
+                path
+            ));
+            return;
+        }
+
+        let mut operation = json!({ "summary": request.name });
+        if let Some(tag) = tag {
+            operation["tags"] = json!([tag]);
+        }
+        let parameters = self.parameters(request, &target);
+        if !parameters.is_empty() {
+            operation["parameters"] = Value::Array(parameters);
+        }
+        if let Some(body) = self.request_body(request) {
+            operation["requestBody"] = body;
+        }
+        operation["responses"] = self.responses(request, &target, &resolved);
+        if let Some(scheme) = self.security_scheme(request) {
+            operation["security"] = json!([{ scheme: [] }]);
+        }
+        self.paths[&path][&method] = operation;
+    }
+
+    fn add_server(&mut self, origin: &str) {
+        if origin.is_empty() {
+            return;
+        }
+        // An origin the environment could not resolve, e.g. `{{baseUrl}}`
+        let url = match origin.strip_prefix("{{").and_then(|o| o.strip_suffix("}}")) {
+            Some(name) => {
+                let url = format!("{{{}}}", name.trim());
+                self.server_variables.insert(
+                    url.clone(),
+                    json!({ name.trim(): { "default": "http://localhost" } }),
+                );
+                url
+            }
+            None => origin.to_string(),
+        };
+        if !self.servers.contains(&url) {
+            self.servers.push(url);
+        }
+    }
+
+    fn parameters(&self, request: &Request, target: &Target) -> Vec<Value> {
+        let mut parameters = Vec::new();
+        for segment in &target.segments {
+            if let Segment::Parameter { name, example } = segment {
+                let mut parameter = json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": { "type": example.as_deref().map_or("string", scalar_type) },
+                });
+                if let Some(example) = example {
+                    parameter["example"] = json!(example);
+                }
+                parameters.push(parameter);
+            }
+        }
+        for (name, value) in &target.query {
+            let mut parameter = json!({
+                "name": name,
+                "in": "query",
+                "schema": { "type": scalar_type(value) },
+            });
+            if !value.contains("{{") && !is_credential(name) {
+                parameter["example"] = json!(value);
+            }
+            parameters.push(parameter);
+        }
+        for header in request.headers.iter().filter(|h| h.enabled) {
+            if DESCRIBED_HEADERS.contains(&header.key.to_ascii_lowercase().as_str()) {
+                continue;
+            }
+            let mut parameter = json!({
+                "name": header.key,
+                "in": "header",
+                "schema": { "type": "string" },
+            });
+            if !header.value.contains("{{") && !is_credential(&header.key) {
+                parameter["example"] = json!(header.value);
+            }
+            parameters.push(parameter);
+        }
+        parameters
+    }
+
+    fn request_body(&self, request: &Request) -> Option<Value> {
+        if !request.method.has_body() || request.body.trim().is_empty() {
+            return None;
+        }
+        let body = &request.body;
+        let content_type = request
+            .headers
+            .iter()
+            .find(|h| h.enabled && h.key.eq_ignore_ascii_case("content-type"))
+            .map(|h| h.value.split(';').next().unwrap_or_default().trim().to_string());
+        let (media_type, schema, example) = describe_body(body, content_type.as_deref());
+        let mut media = json!({ "schema": schema });
+        if let Some(example) = example {
+            media["example"] = example;
+        }
+        Some(json!({ "required": true, "content": { media_type: media } }))
+    }
+
+    /// One response per status code seen in history for this operation,
+    /// the most recent first. An entry matches when it was sent to the
+    /// origin `resolved` names, or, when the environment leaves the
+    /// origin unresolved, when it was written with the same origin.
+    fn responses(&self, request: &Request, target: &Target, resolved: &Target) -> Value {
+        let mut responses = Map::new();
+        for entry in self.history {
+            let Some(status) = entry.response.status_code else {
+                continue;
+            };
+            let status_key = status.to_string();
+            if entry.request.method != request.method || responses.contains_key(&status_key) {
+                continue;
+            }
+            let Some(sent) = Target::parse(&entry.sent.url) else {
+                continue;
+            };
+            let same_origin = if resolved.origin.contains("{{") {
+                Target::parse(&entry.request.url).is_some_and(|written| written.origin == target.origin)
+            } else {
+                resolved.origin.eq_ignore_ascii_case(&sent.origin)
+            };
+            if !same_origin || !resolved.matches(&sent) {
+                continue;
+            }
+
+            let description = reqwest::StatusCode::from_u16(status)
+                .ok()
+                .and_then(|code| code.canonical_reason())
+                .unwrap_or("Response");
+            let mut response = json!({ "description": description });
+            if !entry.response.body.is_empty() {
+                let content_type = entry
+                    .response
+                    .headers
+                    .iter()
+                    .find(|h| h.key.eq_ignore_ascii_case("content-type"))
+                    .map(|h| h.value.split(';').next().unwrap_or_default().trim().to_string());
+                let (media_type, schema, example) =
+                    describe_body(&entry.response.body, content_type.as_deref());
+                let mut media = json!({ "schema": schema });
+                if let Some(example) = example {
+                    media["example"] = example;
+                }
+                response["content"] = json!({ media_type: media });
+            }
+            responses.insert(status_key, response);
+        }
+        if responses.is_empty() {
+            responses.insert(
+                "default".to_string(),
+                json!({ "description": "No response captured in history" }),
+            );
+        }
+        Value::Object(responses)
+    }
+
+    /// Register the security scheme for the request's auth and return its name
+    fn security_scheme(&mut self, request: &Request) -> Option<String> {
+        let header = request
+            .headers
+            .iter()
+            .find(|h| h.enabled && h.key.eq_ignore_ascii_case("authorization"))
+            .map(|h| h.value.to_ascii_lowercase());
+        let scheme = match (&request.auth, header.as_deref()) {
+            (AuthType::Bearer(_), _) => "bearer",
+            (AuthType::Basic { .. }, _) => "basic",
+            (AuthType::None, Some(value)) if value.starts_with("bearer ") => "bearer",
+            (AuthType::None, Some(value)) if value.starts_with("basic ") => "basic",
+            _ => return None,
+        };
+        let name = format!("{}Auth", scheme);
+        self.security
+            .entry(name.clone())
+            .or_insert_with(|| json!({ "type": "http", "scheme": scheme }));
+        Some(name)
+    }
+}
+
+/// A request URL split into its origin, path segments and query
+#[derive(Debug)]
+struct Target {
+    /// `https://host:port`, an unresolved `{{variable}}`, or empty
+    origin: String,
+    segments: Vec<Segment>,
+    query: Vec<(String, String)>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    /// A path parameter with the value the request used, if known
+    Parameter { name: String, example: Option<String> },
+}
+
+impl Target {
+    fn parse(url: &str) -> Option<Target> {
+        let url = url.split('#').next().unwrap_or_default();
+        let (location, query) = url.split_once('?').unwrap_or((url, ""));
+        let (origin, path) = if location.starts_with("{{") {
+            let end = location.find("}}")? + 2;
+            location.split_at(end)
+        } else if let Some(scheme_end) = location.find("://") {
+            let scheme = &location[..scheme_end];
+            if !scheme.eq_ignore_ascii_case("http") && !scheme.eq_ignore_ascii_case("https") {
+                return None;
+            }
+            let host_end = location[scheme_end + 3..]
+                .find('/')
+                .map_or(location.len(), |pos| scheme_end + 3 + pos);
+            location.split_at(host_end)
+        } else if location.starts_with('/') {
+            ("", location)
+        } else {
+            return None;
+        };
+
+        let mut segments: Vec<Segment> = Vec::new();
+        for part in path.split('/').filter(|part| !part.is_empty()) {
+            let segment = if let Some(name) = part.strip_prefix("{{").and_then(|p| p.strip_suffix("}}")) {
+                Segment::Parameter {
+                    name: name.trim().to_string(),
+                    example: None,
+                }
+            } else if is_identifier(part) {
+                let name = match segments.last() {
+                    Some(Segment::Literal(previous)) => {
+                        format!("{}Id", previous.strip_suffix('s').unwrap_or(previous))
+                    }
+                    _ => "id".to_string(),
+                };
+                Segment::Parameter {
+                    name,
+                    example: Some(part.to_string()),
+                }
+            } else {
+                Segment::Literal(part.to_string())
+            };
+            segments.push(segment);
+        }
+
+        let query = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (name.to_string(), value.to_string())
+            })
+            .collect();
+        Some(Target {
+            origin: origin.to_string(),
+            segments,
+            query,
+        })
+    }
+
+    /// The OpenAPI path, e.g. `/users/{userId}`
+    fn template(&self) -> String {
+        if self.segments.is_empty() {
+            return "/".to_string();
+        }
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(text) => format!("/{}", text),
+                Segment::Parameter { name, .. } => format!("/{{{}}}", name),
+            })
+            .collect()
+    }
+
+    /// Whether a sent URL fits this target's path, parameters matching
+    /// any segment
+    fn matches(&self, sent: &Target) -> bool {
+        self.segments.len() == sent.segments.len()
+            && self.segments.iter().zip(&sent.segments).all(|pair| match pair {
+                (Segment::Literal(a), Segment::Literal(b)) => a == b,
+                _ => true,
+            })
+    }
+}
+
+/// Whether a header or query parameter name looks like it carries a
+/// credential, e.g. `Cookie`, `X-Api-Key` or `access_token`
+fn is_credential(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    CREDENTIAL_NAMES.iter().any(|part| name.contains(part))
+}
+
+/// Numeric ids and UUIDs, which vary between requests to one endpoint
+fn is_identifier(segment: &str) -> bool {
+    let is_number = segment.bytes().all(|b| b.is_ascii_digit());
+    let is_uuid = segment.len() == 36
+        && segment.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        });
+    is_number || is_uuid
+}
+
+/// The OpenAPI type of a parameter value
+fn scalar_type(value: &str) -> &'static str {
+    if value.parse::<i64>().is_ok() {
+        "integer"
+    } else if value.parse::<f64>().is_ok() {
+        "number"
+    } else if value == "true" || value == "false" {
+        "boolean"
+    } else {
+        "string"
+    }
+}
+
+/// Media type, schema and example of a request or response body
+fn describe_body(body: &str, content_type: Option<&str>) -> (String, Value, Option<Value>) {
+    let is_form = content_type == Some("application/x-www-form-urlencoded");
+    if is_form {
+        let mut properties = Map::new();
+        let mut example = Map::new();
+        for pair in body.split('&').filter(|pair| !pair.is_empty()) {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            properties.insert(name.to_string(), json!({ "type": scalar_type(value) }));
+            example.insert(name.to_string(), json!(value));
+        }
+        let schema = json!({ "type": "object", "properties": properties });
+        return (content_type.unwrap_or_default().to_string(), schema, Some(Value::Object(example)));
+    }
+    match serde_json::from_str::<Value>(body) {
+        Ok(value) if content_type.map_or(true, |ct| ct.contains("json")) => (
+            content_type.unwrap_or("application/json").to_string(),
+            infer_schema(&value),
+            Some(value),
+        ),
+        _ => (
+            content_type.unwrap_or("text/plain").to_string(),
+            json!({ "type": "string" }),
+            Some(json!(body)),
+        ),
+    }
+}
+
+/// A JSON schema describing an example value
+fn infer_schema(value: &Value) -> Value {
+    match value {
+        Value::Null => json!({ "nullable": true }),
+        Value::Bool(_) => json!({ "type": "boolean" }),
+        Value::Number(n) if n.is_i64() || n.is_u64() => json!({ "type": "integer" }),
+        Value::Number(_) => json!({ "type": "number" }),
+        Value::String(_) => json!({ "type": "string" }),
+        Value::Array(items) => match items.first() {
+            Some(first) => json!({ "type": "array", "items": infer_schema(first) }),
+            None => json!({ "type": "array", "items": {} }),
+        },
+        Value::Object(fields) => {
+            let properties: Map<String, Value> = fields
+                .iter()
+                .map(|(key, value)| (key.clone(), infer_schema(value)))
+                .collect();
+            json!({ "type": "object", "properties": properties })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Header, HttpMethod, Response};
+
+    fn request(name: &str, method: HttpMethod, url: &str) -> Request {
+        Request {
+            name: name.to_string(),
+            method,
+            url: url.to_string(),
+            headers: Vec::new(),
+            ..Request::default()
+        }
+    }
+
+    #[test]
+    fn test_export() {
+        let mut collection = Collection::new("Shop");
+        let mut create = request("Create order", HttpMethod::POST, "{{baseUrl}}/orders");
+        create.headers = vec![
+            Header::new("Content-Type", "application/json"),
+            Header::new("X-Tenant", "{{tenant}}"),
+        ];
+        create.body = r#"{"sku": "A1", "quantity": 2}"#.to_string();
+        create.auth = AuthType::Bearer("{{token}}".to_string());
+        collection.requests.push(create);
+
+        let mut orders = Collection::new("Orders");
+        orders
+            .requests
+            .push(request("Get order", HttpMethod::GET, "{{baseUrl}}/orders/{{orderId}}?expand=items"));
+        orders
+            .requests
+            .push(request("Get order 7", HttpMethod::GET, "https://shop.test/orders/7"));
+        collection.folders.push(orders);
+
+        let mut environment = Environment::new("Shop");
+        environment.set("tenant", "acme");
+        let history = vec![
+            HistoryEntry {
+                request: request("", HttpMethod::GET, "{{baseUrl}}/orders/{{orderId}}"),
+                sent: request("", HttpMethod::GET, "https://shop.test/orders/42"),
+                response: Response {
+                    status_code: Some(200),
+                    body: r#"{"id": 42, "items": [{"sku": "A1"}]}"#.into(),
+                    headers: vec![Header::new("Content-Type", "application/json; charset=utf-8")],
+                    ..Response::default()
+                },
+                timestamp: chrono::Utc::now(),
+            },
+            // Another API with the same path is not this operation
+            HistoryEntry {
+                request: request("", HttpMethod::GET, "https://other.test/orders/1"),
+                sent: request("", HttpMethod::GET, "https://other.test/orders/1"),
+                response: Response {
+                    status_code: Some(404),
+                    ..Response::default()
+                },
+                timestamp: chrono::Utc::now(),
+            },
+        ];
+
+        let exported = export(&collection, Some(&environment), &history, true).unwrap();
+        let document: Value = serde_json::from_str(&exported.content).unwrap();
+
+        assert_eq!(document["servers"][0]["url"], "{baseUrl}");
+        assert_eq!(document["servers"][1]["url"], "https://shop.test");
+
+        let create = &document["paths"]["/orders"]["post"];
+        assert_eq!(create["parameters"][0]["name"], "X-Tenant");
+        assert!(create["parameters"][0].get("example").is_none());
+        let body = &create["requestBody"]["content"]["application/json"];
+        assert_eq!(body["schema"]["properties"]["quantity"]["type"], "integer");
+        assert_eq!(create["security"][0]["bearerAuth"], json!([]));
+        assert!(create["responses"]["default"].is_object());
+
+        let get = &document["paths"]["/orders/{orderId}"]["get"];
+        assert_eq!(get["tags"][0], "Orders");
+        assert_eq!(get["parameters"][0]["in"], "path");
+        assert_eq!(get["parameters"][1]["name"], "expand");
+        let ok = &get["responses"]["200"]["content"]["application/json"];
+        assert_eq!(ok["schema"]["properties"]["items"]["items"]["properties"]["sku"]["type"], "string");
+        assert_eq!(ok["example"]["id"], 42);
+        assert!(get["responses"].get("404").is_none());
+
+        assert_eq!(exported.warnings.len(), 1);
+        assert!(exported.warnings[0].starts_with("Get order 7"));
+    }
+
+    #[test]
+    fn test_export_keeps_secrets_out() {
+        let mut collection = Collection::new("Vault");
+        let mut login = request("Login", HttpMethod::POST, "{{baseUrl}}/login?api_key={{apiKey}}&lang=en");
+        login.headers = vec![
+            Header::new("Content-Type", "application/json"),
+            Header::new("X-Api-Key", "literal-key"),
+            Header::new("Cookie", "session=abc"),
+            Header::new("X-Trace", "on"),
+        ];
+        login.body = r#"{"user": "ann", "password": "{{password}}"}"#.to_string();
+        collection.requests.push(login);
+
+        let mut environment = Environment::new("Vault");
+        environment.set("baseUrl", "https://vault.test/v1");
+        environment.set("apiKey", "SECRET-API-KEY");
+        environment.set("password", "SECRET-PASSWORD");
+        let sent = |url: &str, status: u16| HistoryEntry {
+            request: request("", HttpMethod::POST, "{{baseUrl}}/login"),
+            sent: request("", HttpMethod::POST, url),
+            response: Response {
+                status_code: Some(status),
+                ..Response::default()
+            },
+            timestamp: chrono::Utc::now(),
+        };
+        let history = vec![
+            sent("https://vault.test/v1/login?api_key=SECRET-API-KEY", 200),
+            sent("https://staging.vault.test/v1/login", 500),
+        ];
+
+        let exported = export(&collection, Some(&environment), &history, true).unwrap();
+        assert!(!exported.content.contains("SECRET"));
+        assert!(!exported.content.contains("literal-key"));
+        assert!(!exported.content.contains("session=abc"));
+
+        let document: Value = serde_json::from_str(&exported.content).unwrap();
+        assert_eq!(document["servers"][0]["url"], "https://vault.test/v1");
+        let login = &document["paths"]["/login"]["post"];
+        let example = |name: &str| {
+            login["parameters"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|p| p["name"] == name)
+                .map(|p| p["example"].clone())
+                .unwrap()
+        };
+        assert_eq!(example("api_key"), Value::Null);
+        assert_eq!(example("lang"), "en");
+        assert_eq!(example("X-Api-Key"), Value::Null);
+        assert_eq!(example("Cookie"), Value::Null);
+        assert_eq!(example("X-Trace"), "on");
+        let body = &login["requestBody"]["content"]["application/json"]["example"];
+        assert_eq!(body["password"], "{{password}}");
+        assert!(login["responses"]["200"].is_object());
+        assert!(login["responses"].get("500").is_none());
+    }
+
+    #[test]
+    fn test_identifier_segments() {
+        let target = Target::parse("https://api.test/users/123/posts/0b7c5e1e-3f2a-4c1d-9a7e-2f4e1d3c5b6a").unwrap();
+        assert_eq!(target.template(), "/users/{userId}/posts/{postId}");
+        assert_eq!(Target::parse("https://api.test").unwrap().template(), "/");
+        assert!(Target::parse("unix:///var/run/docker.sock:/info").is_none());
+    }
+}
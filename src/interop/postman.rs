@@ -0,0 +1,742 @@
+//! Postman collection (v2.0/v2.1) and environment import and export
+
+use anyhow::{bail, Result};
+use serde_json::{json, Map, Value};
+
+use crate::curl::{form_part, set_default_header, FORM_BOUNDARY};
+use crate::interop::{
+    add_path_variable, array, enabled, encode_form, file_placeholder_part, file_placeholder_warning,
+    path_variables_to_placeholders, text, Exported, Imported,
+};
+use crate::models::{AuthType, Collection, Environment, Header, HttpMethod, Request};
+
+const SCHEMA_V21: &str = "https://schema.getpostman.com/json/collection/v2.1.0/collection.json";
+
+/// Whether a JSON document is a Postman collection or environment
+pub fn is_postman(value: &Value) -> bool {
+    value
+        .pointer("/info/schema")
+        .and_then(Value::as_str)
+        .is_some_and(|schema| schema.contains("schema.getpostman.com"))
+        || value.get("_postman_variable_scope").is_some()
+}
+
+/// Convert a Postman collection into a collection plus an environment
+/// holding its variables, or a Postman environment into an environment
+pub fn import(value: &Value) -> Result<Imported> {
+    let mut imported = Imported::default();
+
+    if value.get("_postman_variable_scope").is_some() {
+        imported.environments.push(import_environment(value)?);
+        return Ok(imported);
+    }
+
+    let schema = value
+        .pointer("/info/schema")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    if !schema.contains("/v2.") {
+        bail!(
+            "Unsupported Postman collection format ({}); export it as Collection v2.1",
+            schema
+        );
+    }
+
+    let name = value
+        .pointer("/info/name")
+        .and_then(Value::as_str)
+        .unwrap_or("Postman collection");
+    let mut importer = Importer {
+        variables: Environment::new(name),
+        warnings: Vec::new(),
+    };
+    for variable in array(value.get("variable")) {
+        if variable.get("disabled").and_then(Value::as_bool) == Some(true) {
+            continue;
+        }
+        let key = text(variable.get("key"));
+        if !key.is_empty() {
+            importer.variables.set(key, text(variable.get("value")));
+        }
+    }
+
+    let collection = importer.folder(name, value, None, name);
+    imported.collections.push(collection);
+    if !importer.variables.variables.is_empty() {
+        imported.environments.push(importer.variables);
+    }
+    imported.warnings = importer.warnings;
+    Ok(imported)
+}
+
+/// Convert a Postman environment (or globals) export
+fn import_environment(value: &Value) -> Result<Environment> {
+    let Some(values) = value.get("values").and_then(Value::as_array) else {
+        bail!("Postman environment has no values");
+    };
+    let name = value
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or("Postman environment");
+    let mut environment = Environment::new(name);
+    for variable in values {
+        if variable.get("enabled").and_then(Value::as_bool) == Some(false) {
+            continue;
+        }
+        let key = text(variable.get("key"));
+        if !key.is_empty() {
+            environment.set(key, text(variable.get("value")));
+        }
+    }
+    Ok(environment)
+}
+
+/// Walks a collection's item tree, collecting variables and warnings
+struct Importer {
+    /// Collection variables, plus path variables with example values
+    variables: Environment,
+    warnings: Vec<String>,
+}
+
+impl Importer {
+    /// Convert a folder (or the collection itself). Requests without
+    /// their own auth inherit `parent_auth`, as in Postman.
+    fn folder(&mut self, name: &str, item: &Value, parent_auth: Option<&Value>, path: &str) -> Collection {
+        let own_auth = item.get("auth");
+        if let Some(auth) = own_auth {
+            self.check_auth(auth, path);
+        }
+        let auth = own_auth.or(parent_auth);
+        self.check_events(item, path);
+
+        let mut collection = Collection::new(name);
+        for child in array(item.get("item")) {
+            let child_name = child
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or("Untitled");
+            let child_path = format!("{} / {}", path, child_name);
+            if child.get("item").is_some() {
+                collection
+                    .folders
+                    .push(self.folder(child_name, child, auth, &child_path));
+            } else if child.get("request").is_some() {
+                collection
+                    .requests
+                    .push(self.request(child_name, child, auth, &child_path));
+            }
+        }
+        collection
+    }
+
+    fn request(&mut self, name: &str, item: &Value, parent_auth: Option<&Value>, path: &str) -> Request {
+        let mut request = Request {
+            name: name.to_string(),
+            headers: Vec::new(),
+            ..Request::default()
+        };
+        self.check_events(item, path);
+        let saved = array(item.get("response")).len();
+        if saved > 0 {
+            self.warn(path, &format!("{} saved example response(s)", saved));
+        }
+        if item.pointer("/protocolProfileBehavior/strictSSL").and_then(Value::as_bool) == Some(false) {
+            request.ignore_ssl_errors = true;
+        }
+
+        let definition = &item["request"];
+        // A request may be given as just its URL
+        if let Some(url) = definition.as_str() {
+            request.url = url.to_string();
+            return request;
+        }
+
+        let method = definition
+            .get("method")
+            .and_then(Value::as_str)
+            .unwrap_or("GET");
+        request.method = HttpMethod::parse(method).unwrap_or_else(|| {
+            self.warn(path, &format!("{} method (sent as GET)", method));
+            HttpMethod::GET
+        });
+
+        for header in array(definition.get("header")) {
+            request.headers.push(Header {
+                key: text(header.get("key")),
+                value: text(header.get("value")),
+                enabled: header.get("disabled").and_then(Value::as_bool) != Some(true),
+            });
+        }
+
+        request.url = self.url(definition.get("url"));
+        if let Some(body) = definition.get("body") {
+            self.body(body, &mut request, path);
+        }
+        match definition.get("auth") {
+            Some(auth) => {
+                self.check_auth(auth, path);
+                apply_auth(auth, &mut request);
+            }
+            None => {
+                if let Some(auth) = parent_auth {
+                    apply_auth(auth, &mut request);
+                }
+            }
+        }
+
+        let uses_dynamic = std::iter::once(&request.url)
+            .chain(std::iter::once(&request.body))
+            .chain(request.headers.iter().map(|h| &h.value))
+            .any(|text| text.contains("{{$"));
+        if uses_dynamic {
+            self.warn(path, "dynamic variables such as {{$guid}} (left as written)");
+        }
+        request
+    }
+
+    /// Build the URL, turning `:name` path variables into `{{name}}` and
+    /// keeping their example values as environment variables
+    fn url(&mut self, url: Option<&Value>) -> String {
+        let raw = match url {
+            Some(Value::String(raw)) => raw.clone(),
+            Some(url) => match url.get("raw").and_then(Value::as_str) {
+                Some(raw) => raw.to_string(),
+                None => build_url(url),
+            },
+            None => String::new(),
+        };
+
+        for variable in url.map(|url| array(url.get("variable"))).unwrap_or_default() {
+            let key = text(variable.get("key"));
+            let value = text(variable.get("value"));
+            if key.is_empty() || value.is_empty() {
+                continue;
+            }
+            add_path_variable(&mut self.variables, &mut self.warnings, key, value);
+        }
+        path_variables_to_placeholders(&raw)
+    }
+
+    fn body(&mut self, body: &Value, request: &mut Request, path: &str) {
+        if body.get("disabled").and_then(Value::as_bool) == Some(true) {
+            return;
+        }
+        match body.get("mode").and_then(Value::as_str).unwrap_or("raw") {
+            "raw" => {
+                request.body = text(body.get("raw"));
+                let content_type = match body
+                    .pointer("/options/raw/language")
+                    .and_then(Value::as_str)
+                {
+                    Some("json") => Some("application/json"),
+                    Some("xml") => Some("application/xml"),
+                    Some("html") => Some("text/html"),
+                    Some("javascript") => Some("application/javascript"),
+                    _ => None,
+                };
+                if let Some(content_type) = content_type {
+                    set_default_header(request, "Content-Type", content_type);
+                }
+            }
+            "urlencoded" => {
+                request.body = enabled(body.get("urlencoded"))
+                    .map(|field| {
+                        format!(
+                            "{}={}",
+                            encode_form(&text(field.get("key"))),
+                            encode_form(&text(field.get("value")))
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("&");
+                set_default_header(request, "Content-Type", "application/x-www-form-urlencoded");
+            }
+            "formdata" => {
+                let mut parts = Vec::new();
+                for field in enabled(body.get("formdata")) {
+                    let key = text(field.get("key"));
+                    if field.get("type").and_then(Value::as_str) == Some("file") {
+                        let src = match field.get("src") {
+                            Some(Value::Array(files)) => text(files.first()),
+                            src => text(src),
+                        };
+                        parts.push(file_placeholder_part(&key, &src));
+                        self.warnings.push(file_placeholder_warning(path, &key, &src));
+                        continue;
+                    }
+                    match form_part(&format!("{}={}", key, text(field.get("value"))), true) {
+                        Ok(part) => parts.push(part),
+                        Err(e) => self.warn(path, &format!("form field '{}' ({:#})", key, e)),
+                    }
+                }
+                request.body = format!("{}--{}--\r\n", parts.concat(), FORM_BOUNDARY);
+                set_default_header(
+                    request,
+                    "Content-Type",
+                    &format!("multipart/form-data; boundary={}", FORM_BOUNDARY),
+                );
+            }
+            "graphql" => {
+                let query = text(body.pointer("/graphql/query"));
+                let variables = match body.pointer("/graphql/variables") {
+                    Some(Value::String(variables)) if !variables.trim().is_empty() => {
+                        serde_json::from_str(variables).unwrap_or(Value::String(variables.clone()))
+                    }
+                    Some(Value::Object(variables)) => Value::Object(variables.clone()),
+                    _ => json!({}),
+                };
+                let payload = json!({ "query": query, "variables": variables });
+                request.body = serde_json::to_string_pretty(&payload).unwrap_or_default();
+                set_default_header(request, "Content-Type", "application/json");
+            }
+            mode => self.warn(path, &format!("{} body", mode)),
+        }
+    }
+
+    fn check_auth(&mut self, auth: &Value, path: &str) {
+        match auth.get("type").and_then(Value::as_str).unwrap_or("noauth") {
+            "noauth" | "bearer" | "basic" | "apikey" => {}
+            other => self.warn(path, &format!("{} auth", other)),
+        }
+    }
+
+    fn check_events(&mut self, item: &Value, path: &str) {
+        for event in array(item.get("event")) {
+            let lines = match event.pointer("/script/exec") {
+                Some(Value::Array(lines)) => lines.iter().map(|line| text(Some(line))).collect(),
+                exec => vec![text(exec)],
+            };
+            if lines.iter().all(|line| line.trim().is_empty()) {
+                continue;
+            }
+            let listen = match event.get("listen").and_then(Value::as_str) {
+                Some("prerequest") => "pre-request",
+                Some(listen) => listen,
+                None => "event",
+            };
+            self.warn(path, &format!("{} script", listen));
+        }
+    }
+
+    fn warn(&mut self, path: &str, what: &str) {
+        self.warnings.push(format!("{}: {}", path, what));
+    }
+}
+
+/// Set the request's auth from a Postman auth object. API keys become a
+/// header or query parameter.
+fn apply_auth(auth: &Value, request: &mut Request) {
+    match auth.get("type").and_then(Value::as_str).unwrap_or("noauth") {
+        "bearer" => request.auth = AuthType::Bearer(auth_param(auth, "bearer", "token")),
+        "basic" => {
+            request.auth = AuthType::Basic {
+                username: auth_param(auth, "basic", "username"),
+                password: auth_param(auth, "basic", "password"),
+            }
+        }
+        "apikey" => {
+            let key = auth_param(auth, "apikey", "key");
+            let value = auth_param(auth, "apikey", "value");
+            if auth_param(auth, "apikey", "in") == "query" {
+                let separator = if request.url.contains('?') { '&' } else { '?' };
+                request.url = format!("{}{}{}={}", request.url, separator, key, value);
+            } else if !request.headers.iter().any(|h| h.key.eq_ignore_ascii_case(&key)) {
+                request.headers.push(Header::new(key, value));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Read an auth parameter; v2.1 stores a list of key/value pairs, v2.0 an object
+fn auth_param(auth: &Value, kind: &str, key: &str) -> String {
+    match auth.get(kind) {
+        Some(Value::Array(params)) => text(
+            params
+                .iter()
+                .find(|param| param.get("key").and_then(Value::as_str) == Some(key))
+                .and_then(|param| param.get("value")),
+        ),
+        Some(Value::Object(params)) => text(params.get(key)),
+        _ => String::new(),
+    }
+}
+
+/// Reassemble a URL from its parsed parts when `raw` is missing
+fn build_url(url: &Value) -> String {
+    let join = |part: Option<&Value>, separator: &str| match part {
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .map(|part| text(Some(part)))
+            .collect::<Vec<_>>()
+            .join(separator),
+        part => text(part),
+    };
+    let mut raw = String::new();
+    if let Some(protocol) = url.get("protocol").and_then(Value::as_str) {
+        raw.push_str(&format!("{}://", protocol));
+    }
+    raw.push_str(&join(url.get("host"), "."));
+    if let Some(port) = url.get("port") {
+        raw.push_str(&format!(":{}", text(Some(port))));
+    }
+    let path = join(url.get("path"), "/");
+    if !path.is_empty() {
+        raw.push('/');
+        raw.push_str(path.trim_start_matches('/'));
+    }
+    let query: Vec<String> = enabled(url.get("query"))
+        .map(|param| match param.get("value") {
+            Some(Value::Null) | None => text(param.get("key")),
+            value => format!("{}={}", text(param.get("key")), text(value)),
+        })
+        .collect();
+    if !query.is_empty() {
+        raw.push('?');
+        raw.push_str(&query.join("&"));
+    }
+    raw
+}
+
+/// Convert a collection to a Postman v2.1 collection. `variables`
+/// become collection variables.
+pub fn export(collection: &Collection, variables: Option<&Environment>) -> Result<Exported> {
+    let mut warnings = Vec::new();
+    let mut document = Map::new();
+    document.insert(
+        "info".to_string(),
+        json!({ "name": collection.name, "schema": SCHEMA_V21 }),
+    );
+    document.insert(
+        "item".to_string(),
+        Value::Array(export_items(collection, &collection.name, &mut warnings)),
+    );
+    if let Some(environment) = variables {
+        let mut keys: Vec<&String> = environment.variables.keys().collect();
+        keys.sort();
+        let variables = keys
+            .into_iter()
+            .map(|key| json!({ "key": key, "value": environment.variables[key] }))
+            .collect();
+        document.insert("variable".to_string(), Value::Array(variables));
+        if environment.proxy.is_some() || !environment.tls.is_empty() || !environment.host_overrides.is_empty() {
+            warnings.push(format!(
+                "Environment '{}': proxy, TLS and host override settings",
+                environment.name
+            ));
+        }
+    }
+    Ok(Exported {
+        content: serde_json::to_string_pretty(&Value::Object(document))?,
+        warnings,
+    })
+}
+
+fn export_items(collection: &Collection, path: &str, warnings: &mut Vec<String>) -> Vec<Value> {
+    let mut items = Vec::new();
+    for request in &collection.requests {
+        let request_path = format!("{} / {}", path, request.name);
+        items.push(export_request(request, &request_path, warnings));
+    }
+    for folder in &collection.folders {
+        let folder_items = export_items(folder, &format!("{} / {}", path, folder.name), warnings);
+        items.push(json!({ "name": folder.name, "item": folder_items }));
+    }
+    items
+}
+
+fn export_request(request: &Request, path: &str, warnings: &mut Vec<String>) -> Value {
+    let headers: Vec<Value> = request
+        .headers
+        .iter()
+        .map(|header| {
+            let mut entry = json!({ "key": header.key, "value": header.value });
+            if !header.enabled {
+                entry["disabled"] = json!(true);
+            }
+            entry
+        })
+        .collect();
+
+    let mut definition = json!({
+        "method": request.method.as_str(),
+        "header": headers,
+        "url": export_url(&request.url),
+    });
+    if !request.body.is_empty() {
+        let is_json = request.headers.iter().any(|h| {
+            h.enabled && h.key.eq_ignore_ascii_case("content-type") && h.value.contains("json")
+        });
+        let mut body = json!({ "mode": "raw", "raw": request.body });
+        if is_json {
+            body["options"] = json!({ "raw": { "language": "json" } });
+        }
+        definition["body"] = body;
+    }
+    match &request.auth {
+        AuthType::None => {}
+        AuthType::Bearer(token) => {
+            definition["auth"] = json!({
+                "type": "bearer",
+                "bearer": [{ "key": "token", "value": token, "type": "string" }],
+            });
+        }
+        AuthType::Basic { username, password } => {
+            definition["auth"] = json!({
+                "type": "basic",
+                "basic": [
+                    { "key": "username", "value": username, "type": "string" },
+                    { "key": "password", "value": password, "type": "string" },
+                ],
+            });
+        }
+    }
+
+    let mut item = json!({ "name": request.name, "request": definition });
+    if request.ignore_ssl_errors {
+        item["protocolProfileBehavior"] = json!({ "strictSSL": false });
+    }
+    let unsupported = [
+        (request.proxy.is_some(), "proxy override"),
+        (
+            request.unix_socket.is_some() || request.url.starts_with(crate::models::UNIX_SCHEME),
+            "Unix socket",
+        ),
+        (!request.host_overrides.is_empty(), "host overrides"),
+        (request.tls.is_some(), "TLS profile"),
+    ];
+    for (_, what) in unsupported.iter().filter(|(present, _)| *present) {
+        warnings.push(format!("{}: {}", path, what));
+    }
+    item
+}
+
+/// Postman's structured URL; `raw` is what Postman actually sends
+fn export_url(raw: &str) -> Value {
+    let (base, query) = match raw.split_once('?') {
+        Some((base, query)) => (base, Some(query)),
+        None => (raw, None),
+    };
+    let (protocol, rest) = match base.split_once("://") {
+        Some((protocol, rest)) => (Some(protocol), rest),
+        None => (None, base),
+    };
+    let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => {
+            (host, Some(port))
+        }
+        _ => (authority, None),
+    };
+
+    let mut url = json!({ "raw": raw });
+    if let Some(protocol) = protocol {
+        url["protocol"] = json!(protocol);
+    }
+    url["host"] = json!(host.split('.').collect::<Vec<_>>());
+    if let Some(port) = port {
+        url["port"] = json!(port);
+    }
+    url["path"] = json!(path.split('/').filter(|s| !s.is_empty()).collect::<Vec<_>>());
+    if let Some(query) = query {
+        let params: Vec<Value> = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| match pair.split_once('=') {
+                Some((key, value)) => json!({ "key": key, "value": value }),
+                None => json!({ "key": pair, "value": null }),
+            })
+            .collect();
+        url["query"] = json!(params);
+    }
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLLECTION: &str = r#"{
+        "info": {
+            "name": "Shop",
+            "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json"
+        },
+        "auth": { "type": "bearer", "bearer": [{ "key": "token", "value": "{{token}}" }] },
+        "variable": [
+            { "key": "baseUrl", "value": "https://api.shop.test" },
+            { "key": "unused", "value": "x", "disabled": true }
+        ],
+        "item": [
+            {
+                "name": "Users",
+                "item": [
+                    {
+                        "name": "Get user",
+                        "event": [{ "listen": "test", "script": { "exec": ["pm.test('ok')"] } }],
+                        "request": {
+                            "method": "GET",
+                            "header": [{ "key": "X-Debug", "value": "1", "disabled": true }],
+                            "url": {
+                                "raw": "{{baseUrl}}/users/:id?expand=orders",
+                                "variable": [{ "key": "id", "value": "42" }]
+                            }
+                        }
+                    },
+                    {
+                        "name": "Login",
+                        "request": {
+                            "method": "POST",
+                            "auth": { "type": "basic", "basic": { "username": "ann", "password": "pw" } },
+                            "body": {
+                                "mode": "urlencoded",
+                                "urlencoded": [
+                                    { "key": "user", "value": "{{user}}" },
+                                    { "key": "note", "value": "a b&c" }
+                                ]
+                            },
+                            "url": "{{baseUrl}}/login"
+                        }
+                    }
+                ]
+            },
+            {
+                "name": "Create order",
+                "request": {
+                    "method": "POST",
+                    "header": [],
+                    "body": {
+                        "mode": "raw",
+                        "raw": "{\"sku\": \"A1\"}",
+                        "options": { "raw": { "language": "json" } }
+                    },
+                    "url": {
+                        "protocol": "https",
+                        "host": ["api", "shop", "test"],
+                        "path": ["orders"],
+                        "query": [{ "key": "dry", "value": "1" }]
+                    }
+                }
+            },
+            {
+                "name": "Signed",
+                "request": {
+                    "method": "GET",
+                    "auth": { "type": "awsv4", "awsv4": [] },
+                    "url": "https://aws.test/"
+                }
+            }
+        ]
+    }"#;
+
+    fn import_str(json: &str) -> Imported {
+        import(&serde_json::from_str(json).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_import_collection() {
+        let imported = import_str(COLLECTION);
+        let shop = &imported.collections[0];
+        assert_eq!(shop.name, "Shop");
+        assert_eq!(shop.request_count(), 4);
+
+        let order = &shop.requests[0];
+        assert_eq!(order.url, "https://api.shop.test/orders?dry=1");
+        assert_eq!(order.body, "{\"sku\": \"A1\"}");
+        assert!(order.headers.iter().any(|h| h.value == "application/json"));
+        assert_eq!(order.auth, AuthType::Bearer("{{token}}".to_string()));
+
+        let users = &shop.folders[0];
+        let get_user = &users.requests[0];
+        assert_eq!(get_user.url, "{{baseUrl}}/users/{{id}}?expand=orders");
+        assert!(!get_user.headers[0].enabled);
+
+        let login = &users.requests[1];
+        assert_eq!(login.body, "user={{user}}&note=a%20b%26c");
+        assert_eq!(
+            login.auth,
+            AuthType::Basic { username: "ann".to_string(), password: "pw".to_string() }
+        );
+
+        let variables = &imported.environments[0];
+        assert_eq!(variables.name, "Shop");
+        assert_eq!(variables.get("baseUrl").map(String::as_str), Some("https://api.shop.test"));
+        assert_eq!(variables.get("id").map(String::as_str), Some("42"));
+        assert!(variables.get("unused").is_none());
+
+        assert_eq!(imported.warnings.len(), 2);
+        assert!(imported.warnings[0].contains("Get user: test script"));
+        assert!(imported.warnings[1].contains("Signed: awsv4 auth"));
+    }
+
+    #[test]
+    fn test_export_roundtrip() {
+        let imported = import_str(COLLECTION);
+        let exported = export(&imported.collections[0], imported.environments.first()).unwrap();
+        assert!(exported.warnings.is_empty());
+        assert!(exported.content.contains(SCHEMA_V21));
+
+        let again = import_str(&exported.content);
+        let (before, after) = (&imported.collections[0], &again.collections[0]);
+        assert_eq!(before.request_count(), after.request_count());
+        for (a, b) in before.entries().iter().zip(after.entries()) {
+            assert_eq!(a.name, b.name);
+            if let (Some(a), Some(b)) = (a.request, b.request) {
+                assert_eq!(a.method, b.method);
+                assert_eq!(a.url, b.url);
+                assert_eq!(a.body, b.body);
+                assert_eq!(a.auth, b.auth);
+                assert_eq!(a.headers.len(), b.headers.len());
+            }
+        }
+        assert_eq!(again.environments[0].variables, imported.environments[0].variables);
+    }
+
+    #[test]
+    fn test_import_form_file_is_not_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let secret = dir.path().join("id_rsa");
+        std::fs::write(&secret, "PRIVATE KEY MATERIAL").unwrap();
+        let collection = json!({
+            "info": { "name": "Upload", "schema": SCHEMA_V21 },
+            "item": [{
+                "name": "Send",
+                "request": {
+                    "method": "POST",
+                    "url": "https://files.test/upload",
+                    "body": {
+                        "mode": "formdata",
+                        "formdata": [
+                            { "key": "note", "value": "hi", "type": "text" },
+                            { "key": "doc", "src": secret.to_string_lossy(), "type": "file" }
+                        ]
+                    }
+                }
+            }]
+        });
+        let imported = import(&collection).unwrap();
+        let body = &imported.collections[0].requests[0].body;
+        assert!(!body.contains("PRIVATE KEY MATERIAL"));
+        assert!(body.contains("name=\"doc\"; filename=\"id_rsa\""));
+        assert!(body.contains("\r\n\r\nhi\r\n"));
+        assert_eq!(imported.warnings.len(), 1);
+        assert!(imported.warnings[0].contains("Upload / Send: form file 'doc'"));
+    }
+
+    #[test]
+    fn test_import_environment() {
+        let imported = import_str(
+            r#"{
+                "name": "Staging",
+                "_postman_variable_scope": "environment",
+                "values": [
+                    { "key": "baseUrl", "value": "https://staging.test", "enabled": true },
+                    { "key": "off", "value": "1", "enabled": false }
+                ]
+            }"#,
+        );
+        assert!(imported.collections.is_empty());
+        let staging = &imported.environments[0];
+        assert_eq!(staging.name, "Staging");
+        assert_eq!(staging.variables.len(), 1);
+    }
+}
@@ -0,0 +1,524 @@
+//! Response filters: JSONPath and a subset of jq
+//!
+//! Expressions starting with `$` are JSONPath and return an array of the
+//! matches: `$.items[*].id`, `$..name`, `$.items[?(@.price < 10)]`,
+//! `$.items[0,2]`, `$.items[-2:]`.
+//!
+//! Anything else is jq: paths (`.items[].id`, `.[0]`, `..`), pipes,
+//! `select(.price < 10)`, `length` and `keys`. Each output is printed
+//! on its own, like `jq` does.
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::Value;
+use std::cmp::Ordering;
+
+/// Apply a filter to a JSON body, returning the pretty-printed result
+pub fn apply(expression: &str, body: &str) -> Result<String> {
+    let expression = expression.trim();
+    let value: Value =
+        serde_json::from_str(body).map_err(|e| anyhow!("Response is not JSON: {}", e))?;
+
+    if let Some(path) = expression.strip_prefix('$') {
+        let steps = Parser::new(path).steps_to_end()?;
+        let matches = evaluate(&steps, vec![&value]);
+        let matches: Vec<Value> = matches.into_iter().cloned().collect();
+        return Ok(serde_json::to_string_pretty(&Value::Array(matches))?);
+    }
+
+    let mut outputs = vec![value];
+    for stage in split_pipes(expression) {
+        let stage = Stage::parse(stage.trim())?;
+        outputs = outputs
+            .iter()
+            .map(|value| stage.run(value))
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+    }
+    let outputs: Vec<String> = outputs
+        .iter()
+        .map(serde_json::to_string_pretty)
+        .collect::<serde_json::Result<_>>()?;
+    Ok(outputs.join("\n"))
+}
+
+/// One jq filter between pipes
+enum Stage {
+    Path(Vec<Step>),
+    Select(Predicate),
+    Length,
+    Keys,
+}
+
+impl Stage {
+    fn parse(text: &str) -> Result<Stage> {
+        match text {
+            "length" => return Ok(Stage::Length),
+            "keys" => return Ok(Stage::Keys),
+            _ => {}
+        }
+        if let Some(condition) = text.strip_prefix("select(").and_then(|t| t.strip_suffix(')')) {
+            let mut parser = Parser::new(condition);
+            let predicate = parser.predicate()?;
+            parser.expect_end()?;
+            return Ok(Stage::Select(predicate));
+        }
+        if !text.starts_with('.') {
+            bail!("Unsupported filter '{}'; expected a path, select(...), length or keys", text);
+        }
+        Ok(Stage::Path(Parser::new(text).steps_to_end()?))
+    }
+
+    fn run(&self, value: &Value) -> Result<Vec<Value>> {
+        Ok(match self {
+            Stage::Path(steps) => evaluate(steps, vec![value]).into_iter().cloned().collect(),
+            Stage::Select(predicate) => {
+                if predicate.matches(value) {
+                    vec![value.clone()]
+                } else {
+                    Vec::new()
+                }
+            }
+            Stage::Length => vec![Value::from(match value {
+                Value::Array(items) => items.len(),
+                Value::Object(fields) => fields.len(),
+                Value::String(s) => s.chars().count(),
+                Value::Null => 0,
+                other => bail!("{} has no length", other),
+            })],
+            Stage::Keys => match value {
+                Value::Object(fields) => {
+                    let mut keys: Vec<&String> = fields.keys().collect();
+                    keys.sort();
+                    vec![Value::from(keys.into_iter().cloned().collect::<Vec<_>>())]
+                }
+                Value::Array(items) => vec![Value::from((0..items.len()).collect::<Vec<_>>())],
+                other => bail!("{} has no keys", other),
+            },
+        })
+    }
+}
+
+/// Split on `|` outside of quotes and parentheses
+fn split_pipes(expression: &str) -> Vec<&str> {
+    let mut stages = Vec::new();
+    let (mut depth, mut quote, mut start) = (0, None, 0);
+    for (i, c) in expression.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(' | '[') => depth += 1,
+            (None, ')' | ']') => depth -= 1,
+            (None, '|') if depth == 0 => {
+                stages.push(&expression[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    stages.push(&expression[start..]);
+    stages
+}
+
+#[derive(Debug)]
+enum Step {
+    Field(String),
+    Index(i64),
+    Slice(Option<i64>, Option<i64>),
+    /// Every array item or object value
+    Wildcard,
+    /// Fields and indices listed in one bracket, e.g. `[0,2]`
+    Union(Vec<Step>),
+    /// The value and everything nested in it, for `..`
+    Descendants,
+    /// Children matching a JSONPath `[?(...)]` filter
+    Filter(Predicate),
+}
+
+#[derive(Debug)]
+struct Predicate {
+    path: Vec<Step>,
+    /// Without a comparison the path must yield a value other than
+    /// `null` or `false`
+    comparison: Option<(Comparison, Value)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Predicate {
+    fn matches(&self, value: &Value) -> bool {
+        let found = evaluate(&self.path, vec![value]);
+        match &self.comparison {
+            None => found
+                .iter()
+                .any(|v| !matches!(v, Value::Null | Value::Bool(false))),
+            Some((comparison, literal)) => found.iter().any(|v| {
+                let ordering = compare(v, literal);
+                match comparison {
+                    Comparison::Eq => ordering == Some(Ordering::Equal),
+                    Comparison::Ne => ordering != Some(Ordering::Equal),
+                    Comparison::Lt => ordering == Some(Ordering::Less),
+                    Comparison::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                    Comparison::Gt => ordering == Some(Ordering::Greater),
+                    Comparison::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+                }
+            }),
+        }
+    }
+}
+
+/// Order numbers numerically and strings lexically; other values are
+/// only equal or not
+fn compare(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64()?.partial_cmp(&y.as_f64()?),
+        (Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+        _ if a == b => Some(Ordering::Equal),
+        _ => None,
+    }
+}
+
+fn evaluate<'a>(steps: &[Step], mut values: Vec<&'a Value>) -> Vec<&'a Value> {
+    for step in steps {
+        values = values
+            .into_iter()
+            .flat_map(|value| apply_step(step, value))
+            .collect();
+    }
+    values
+}
+
+fn apply_step<'a>(step: &Step, value: &'a Value) -> Vec<&'a Value> {
+    match (step, value) {
+        (Step::Field(name), Value::Object(fields)) => fields.get(name).into_iter().collect(),
+        (Step::Index(index), Value::Array(items)) => {
+            let index = if *index < 0 { items.len() as i64 + index } else { *index };
+            usize::try_from(index)
+                .ok()
+                .and_then(|i| items.get(i))
+                .into_iter()
+                .collect()
+        }
+        (Step::Slice(start, end), Value::Array(items)) => {
+            let len = items.len() as i64;
+            let bound = |i: i64| if i < 0 { (len + i).max(0) } else { i.min(len) };
+            let start = bound(start.unwrap_or(0)) as usize;
+            let end = bound(end.unwrap_or(len)) as usize;
+            items.get(start..end.max(start)).unwrap_or_default().iter().collect()
+        }
+        (Step::Wildcard, Value::Array(items)) => items.iter().collect(),
+        (Step::Wildcard, Value::Object(fields)) => fields.values().collect(),
+        (Step::Union(steps), _) => steps.iter().flat_map(|s| apply_step(s, value)).collect(),
+        (Step::Descendants, _) => {
+            let mut all = Vec::new();
+            descendants(value, &mut all);
+            all
+        }
+        (Step::Filter(predicate), _) => apply_step(&Step::Wildcard, value)
+            .into_iter()
+            .filter(|child| predicate.matches(child))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// A value and everything nested in it, in document order
+fn descendants<'a>(value: &'a Value, all: &mut Vec<&'a Value>) {
+    all.push(value);
+    match value {
+        Value::Array(items) => items.iter().for_each(|item| descendants(item, all)),
+        Value::Object(fields) => fields.values().for_each(|field| descendants(field, all)),
+        _ => {}
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn new(text: &str) -> Parser {
+        Parser {
+            chars: text.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect_end(&mut self) -> Result<()> {
+        self.skip_spaces();
+        match self.peek() {
+            None => Ok(()),
+            Some(c) => bail!("Unexpected '{}' at position {}", c, self.pos + 1),
+        }
+    }
+
+    fn steps_to_end(&mut self) -> Result<Vec<Step>> {
+        let steps = self.steps()?;
+        self.expect_end()?;
+        Ok(steps)
+    }
+
+    /// `.name`, `.*`, `..`, `[...]` steps, and jq's `?` (ignored)
+    fn steps(&mut self) -> Result<Vec<Step>> {
+        let mut steps = Vec::new();
+        loop {
+            if self.eat('.') {
+                if self.eat('.') {
+                    steps.push(Step::Descendants);
+                }
+                if self.eat('*') {
+                    steps.push(Step::Wildcard);
+                } else if self.peek().is_some_and(is_name_char) {
+                    steps.push(Step::Field(self.name()));
+                } else if self.peek() == Some('"') {
+                    steps.push(Step::Field(self.string()?));
+                }
+            } else if self.eat('[') {
+                steps.push(self.bracket()?);
+            } else if !self.eat('?') {
+                return Ok(steps);
+            }
+        }
+    }
+
+    fn name(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(is_name_char) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    /// A quoted string, single or double quotes
+    fn string(&mut self) -> Result<String> {
+        let quote = self.peek().ok_or_else(|| anyhow!("Expected a string"))?;
+        self.pos += 1;
+        let mut text = String::new();
+        loop {
+            match self.peek() {
+                None => bail!("Unterminated string"),
+                Some(c) if c == quote => {
+                    self.pos += 1;
+                    return Ok(text);
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    text.extend(self.peek());
+                    self.pos += 1;
+                }
+                Some(c) => {
+                    text.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    fn integer(&mut self) -> Result<Option<i64>> {
+        self.skip_spaces();
+        let start = self.pos;
+        self.eat('-');
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return Ok(None);
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        text.parse()
+            .map(Some)
+            .map_err(|_| anyhow!("Invalid index '{}'", text))
+    }
+
+    /// The inside of `[...]`, after the opening bracket
+    fn bracket(&mut self) -> Result<Step> {
+        self.skip_spaces();
+        let step = if self.eat(']') {
+            return Ok(Step::Wildcard);
+        } else if self.eat('*') {
+            Step::Wildcard
+        } else if self.eat('?') {
+            self.skip_spaces();
+            let parenthesized = self.eat('(');
+            let predicate = self.predicate()?;
+            if parenthesized && !self.eat(')') {
+                bail!("Expected ')' at position {}", self.pos + 1);
+            }
+            Step::Filter(predicate)
+        } else {
+            let mut selectors = Vec::new();
+            loop {
+                self.skip_spaces();
+                let selector = if matches!(self.peek(), Some('\'' | '"')) {
+                    Step::Field(self.string()?)
+                } else {
+                    let start = self.integer()?;
+                    self.skip_spaces();
+                    if self.eat(':') {
+                        Step::Slice(start, self.integer()?)
+                    } else {
+                        Step::Index(start.ok_or_else(|| {
+                            anyhow!("Expected an index, name or '*' at position {}", self.pos + 1)
+                        })?)
+                    }
+                };
+                selectors.push(selector);
+                self.skip_spaces();
+                if !self.eat(',') {
+                    break;
+                }
+            }
+            if selectors.len() == 1 {
+                selectors.remove(0)
+            } else {
+                Step::Union(selectors)
+            }
+        };
+        self.skip_spaces();
+        if !self.eat(']') {
+            bail!("Expected ']' at position {}", self.pos + 1);
+        }
+        Ok(step)
+    }
+
+    /// `@.path op literal` (JSONPath) or `.path op literal` (jq)
+    fn predicate(&mut self) -> Result<Predicate> {
+        self.skip_spaces();
+        self.eat('@');
+        let path = self.steps()?;
+        self.skip_spaces();
+        let operators = [
+            ("==", Comparison::Eq),
+            ("!=", Comparison::Ne),
+            ("<=", Comparison::Le),
+            (">=", Comparison::Ge),
+            ("<", Comparison::Lt),
+            (">", Comparison::Gt),
+        ];
+        let Some((operator, comparison)) = operators.into_iter().find(|(operator, _)| {
+            operator
+                .chars()
+                .enumerate()
+                .all(|(i, c)| self.chars.get(self.pos + i) == Some(&c))
+        }) else {
+            return Ok(Predicate {
+                path,
+                comparison: None,
+            });
+        };
+        self.pos += operator.len();
+        self.skip_spaces();
+        let literal = if matches!(self.peek(), Some('\'' | '"')) {
+            Value::String(self.string()?)
+        } else {
+            let start = self.pos;
+            while self
+                .peek()
+                .is_some_and(|c| !c.is_whitespace() && c != ')' && c != ']')
+            {
+                self.pos += 1;
+            }
+            let text: String = self.chars[start..self.pos].iter().collect();
+            serde_json::from_str(&text).map_err(|_| anyhow!("Invalid value '{}' in filter", text))?
+        };
+        self.skip_spaces();
+        Ok(Predicate {
+            path,
+            comparison: Some((comparison, literal)),
+        })
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '$'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &str = r#"{
+        "store": {
+            "books": [
+                { "title": "Dune", "price": 9.5, "tags": ["sf"] },
+                { "title": "Emma", "price": 12, "isbn": "0-1" },
+                { "title": "Ubik", "price": 8 }
+            ],
+            "owner": { "name": "Ann" }
+        }
+    }"#;
+
+    fn filter(expression: &str) -> Value {
+        let output = apply(expression, BODY).unwrap();
+        serde_json::from_str(&output).unwrap()
+    }
+
+    #[test]
+    fn test_jsonpath() {
+        assert_eq!(filter("$.store.books[*].title"), serde_json::json!(["Dune", "Emma", "Ubik"]));
+        assert_eq!(filter("$.store.books[-1].title"), serde_json::json!(["Ubik"]));
+        assert_eq!(filter("$.store.books[0,2].price"), serde_json::json!([9.5, 8]));
+        assert_eq!(filter("$.store.books[1:].title"), serde_json::json!(["Emma", "Ubik"]));
+        assert_eq!(filter("$..name"), serde_json::json!(["Ann"]));
+        assert_eq!(filter("$['store']['owner'].name"), serde_json::json!(["Ann"]));
+        assert_eq!(
+            filter("$.store.books[?(@.price < 10)].title"),
+            serde_json::json!(["Dune", "Ubik"])
+        );
+        assert_eq!(filter("$.store.books[?(@.isbn)].title"), serde_json::json!(["Emma"]));
+        assert_eq!(
+            filter("$.store.books[?(@.title == 'Dune')].tags[0]"),
+            serde_json::json!(["sf"])
+        );
+    }
+
+    #[test]
+    fn test_jq() {
+        assert_eq!(apply(".store.books[].title", BODY).unwrap(), "\"Dune\"\n\"Emma\"\n\"Ubik\"");
+        assert_eq!(apply(".store.books | length", BODY).unwrap(), "3");
+        assert_eq!(
+            apply(".store.books[] | select(.price >= 9) | .title", BODY).unwrap(),
+            "\"Dune\"\n\"Emma\""
+        );
+        assert_eq!(filter(".store.owner | keys"), serde_json::json!(["name"]));
+        assert_eq!(filter(".store.books[1]")["isbn"], "0-1");
+        assert_eq!(filter("."), serde_json::from_str::<Value>(BODY).unwrap());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(apply("$.store[", BODY).is_err());
+        assert!(apply("map(.x)", BODY).is_err());
+        assert!(apply(".a", "not json").is_err());
+    }
+}
@@ -0,0 +1,206 @@
+//! Foldable tree view of a JSON document
+//!
+//! Nodes are identified by their JSONPath (`$.items[0].id`), so the
+//! folded set survives new responses with the same shape.
+
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// One row of the JSON tree view
+#[derive(Clone, Debug, PartialEq)]
+pub struct TreeLine {
+    pub depth: usize,
+    /// Object key or `[index]`; `None` for the root
+    pub key: Option<String>,
+    pub node: TreeNode,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TreeNode {
+    Object { len: usize, collapsed: bool },
+    Array { len: usize, collapsed: bool },
+    Scalar(Value),
+}
+
+impl TreeNode {
+    pub fn is_container(&self) -> bool {
+        !matches!(self, TreeNode::Scalar(_))
+    }
+
+    pub fn is_collapsed(&self) -> bool {
+        matches!(
+            self,
+            TreeNode::Object { collapsed: true, .. } | TreeNode::Array { collapsed: true, .. }
+        )
+    }
+}
+
+/// A visible row with the node it shows
+#[derive(Clone, Debug)]
+pub struct TreeRow {
+    pub line: TreeLine,
+    /// JSONPath of the node
+    pub path: String,
+    /// JSON Pointer of the node, to look its value up in the document
+    pub pointer: String,
+}
+
+/// The rows visible with the nodes in `collapsed` folded, in document order
+pub fn rows(root: &Value, collapsed: &HashSet<String>) -> Vec<TreeRow> {
+    let mut rows = Vec::new();
+    push_rows(root, None, "$".to_string(), String::new(), 0, collapsed, &mut rows);
+    rows
+}
+
+fn push_rows(
+    value: &Value,
+    key: Option<String>,
+    path: String,
+    pointer: String,
+    depth: usize,
+    collapsed: &HashSet<String>,
+    rows: &mut Vec<TreeRow>,
+) {
+    let folded = collapsed.contains(&path);
+    let node = match value {
+        Value::Object(fields) => TreeNode::Object {
+            len: fields.len(),
+            collapsed: folded,
+        },
+        Value::Array(items) => TreeNode::Array {
+            len: items.len(),
+            collapsed: folded,
+        },
+        scalar => TreeNode::Scalar(scalar.clone()),
+    };
+    rows.push(TreeRow {
+        line: TreeLine { depth, key, node },
+        path: path.clone(),
+        pointer: pointer.clone(),
+    });
+    if folded {
+        return;
+    }
+    match value {
+        Value::Object(fields) => {
+            for (name, child) in fields {
+                let child_path = field_path(&path, name);
+                let child_pointer = format!("{}/{}", pointer, name.replace('~', "~0").replace('/', "~1"));
+                push_rows(child, Some(name.clone()), child_path, child_pointer, depth + 1, collapsed, rows);
+            }
+        }
+        Value::Array(items) => {
+            for (index, child) in items.iter().enumerate() {
+                let child_path = format!("{}[{}]", path, index);
+                let child_pointer = format!("{}/{}", pointer, index);
+                push_rows(child, Some(format!("[{}]", index)), child_path, child_pointer, depth + 1, collapsed, rows);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// `$.name`, or `$['odd name']` when the key is not an identifier
+pub fn field_path(parent: &str, name: &str) -> String {
+    let identifier = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    if identifier {
+        format!("{}.{}", parent, name)
+    } else {
+        format!("{}['{}']", parent, name.replace('\\', "\\\\").replace('\'', "\\'"))
+    }
+}
+
+/// A variable name for a captured node: its key, or `value` for array
+/// items and the root
+pub fn variable_name(line: &TreeLine) -> String {
+    match &line.key {
+        Some(key) if !key.starts_with('[') => key.clone(),
+        _ => "value".to_string(),
+    }
+}
+
+/// The text a captured node is stored as: strings without quotes,
+/// anything else as compact JSON
+pub fn capture_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_rows_and_folding() {
+        let value = json!({ "items": [{ "id": 1 }, { "id": 2 }], "next page": null });
+        let all = rows(&value, &HashSet::new());
+        let paths: Vec<&str> = all.iter().map(|row| row.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["$", "$.items", "$.items[0]", "$.items[0].id", "$.items[1]", "$.items[1].id", "$['next page']"]
+        );
+        assert_eq!(all[3].line.depth, 3);
+        assert_eq!(all[3].line.key.as_deref(), Some("id"));
+
+        let collapsed: HashSet<String> = ["$.items".to_string()].into();
+        let folded = rows(&value, &collapsed);
+        assert_eq!(folded.len(), 3);
+        assert_eq!(folded[1].line.node, TreeNode::Array { len: 2, collapsed: true });
+
+        assert_eq!(variable_name(&all[3].line), "id");
+        assert_eq!(variable_name(&all[2].line), "value");
+        assert_eq!(capture_text(&json!("abc")), "abc");
+        assert_eq!(all[3].pointer, "/items/0/id");
+        assert_eq!(capture_text(value.pointer(&all[2].pointer).unwrap()), "{\"id\":1}");
+    }
+
+    #[test]
+    fn test_collapsed_paths() {
+        let value = json!({ "data": { "user's": { "tags": ["a", "b"] }, "total": 2 } });
+
+        // A folded node hides its children whatever else is folded below it
+        let collapsed: HashSet<String> =
+            ["$.data".to_string(), "$.data['user\\'s'].tags".to_string()].into();
+        let folded = rows(&value, &collapsed);
+        assert_eq!(folded.len(), 2);
+        assert_eq!(folded[1].line.node, TreeNode::Object { len: 2, collapsed: true });
+
+        let collapsed: HashSet<String> = ["$.data['user\\'s']".to_string()].into();
+        let paths: Vec<String> = rows(&value, &collapsed).into_iter().map(|row| row.path).collect();
+        assert_eq!(paths, vec!["$", "$.data", "$.data.total", "$.data['user\\'s']"]);
+
+        // Paths that name no container, or nothing at all, fold nothing
+        let collapsed: HashSet<String> = ["$.data.total".to_string(), "$.gone".to_string()].into();
+        let all = rows(&value, &collapsed);
+        assert_eq!(all.len(), 7);
+        assert!(!all.iter().any(|row| row.line.node.is_collapsed()));
+
+        let collapsed: HashSet<String> = ["$".to_string()].into();
+        let root = rows(&value, &collapsed);
+        assert_eq!(root.len(), 1);
+        assert!(root[0].line.node.is_collapsed());
+    }
+
+    #[test]
+    fn test_pointer_escaping() {
+        let value = json!({ "a/b": { "~1": 1 }, "m~n": [true], "/": "slash", "~": "tilde" });
+        let all = rows(&value, &HashSet::new());
+        let pointers: Vec<&str> = all.iter().map(|row| row.pointer.as_str()).collect();
+        assert_eq!(pointers, vec!["", "/~1", "/a~1b", "/a~1b/~01", "/m~0n", "/m~0n/0", "/~0"]);
+        for row in &all {
+            let node = value.pointer(&row.pointer).unwrap();
+            match &row.line.node {
+                TreeNode::Scalar(scalar) => assert_eq!(node, scalar),
+                container => assert!(container.is_container() && (node.is_object() || node.is_array())),
+            }
+        }
+        assert_eq!(all[1].path, "$['/']");
+    }
+}
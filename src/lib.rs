@@ -3,14 +3,22 @@
 //! A minimal terminal-based API testing tool, similar to Postman/Insomnia.
 //!
 //! ## Features
-//! - HTTP methods: GET, POST, PUT, PATCH, DELETE
+//! - HTTP methods: GET, POST, PUT, PATCH, DELETE, HEAD, OPTIONS
 //! - Request body editor
 //! - Custom headers
 //! - Auth support (Bearer, Basic)
 //! - Request history
 //! - cURL import/export
-//! - JSON syntax highlighting
-//! - Workspace discovery (OpenAPI, FastAPI, Express)
+//! - Postman collection import/export, Insomnia, Bruno and HAR import, HAR history export
+//! - OpenAPI 3 export of collections, with example responses from history
+//! - `.http` / `.rest` request files, opened as collections and saved back
+//! - Pretty printing and highlighting of JSON, XML, HTML, YAML, CSV and form bodies
+//! - JSONPath/jq response filters
+//! - Foldable JSON tree view with path copying and variable capture
+//! - Side-by-side diff of two history responses
+//! - Workspace discovery (OpenAPI 3, Swagger 2.0, FastAPI, Express)
+//! - OpenAPI server selection, with server variables and environment mapping
+//! - Response validation against OpenAPI response schemas
 //!
 //! ## Architecture
 //! Actor-based with channels:
@@ -22,7 +30,16 @@ pub mod models;
 pub mod storage;
 pub mod ui;
 pub mod curl;
+pub mod codegen;
+pub mod clipboard;
 pub mod discovery;
+pub mod interop;
+pub mod formatting;
+pub mod indexed_text;
+pub mod append_log;
+pub mod json_filter;
+pub mod json_tree;
+pub mod response_diff;
 pub mod messages;
 pub mod app;
 pub mod network;
@@ -30,7 +47,8 @@ pub mod constants;
 
 // Re-export commonly used types
 pub use models::{Request, HttpMethod, Header, AuthType, Collection, Environment};
-pub use curl::{parse_curl, to_curl};
+pub use curl::{import_curl, parse_curl, to_curl, CurlImport};
+pub use codegen::CodeTarget;
 pub use discovery::{DiscoveredEndpoint, WorkspaceProject, Framework};
 pub use messages::{UiEvent, NetworkCommand, NetworkResponse, RenderState};
 pub use app::{AppState, AppActor};
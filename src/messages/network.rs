@@ -1,6 +1,9 @@
 //! Network messages - communication between App and Network layers
 
-use crate::models::{Environment, Request};
+use crate::models::{
+    Environment, Header, HostOverride, ProxySetting, Request, ResponseTiming, TlsDetails,
+    TlsProfile,
+};
 
 /// Commands sent from App layer to Network layer
 #[derive(Debug, Clone)]
@@ -11,19 +14,27 @@ pub enum NetworkCommand {
         id: u64,
         request: Request,
         environment: Option<Environment>,
+        tls: Option<TlsProfile>,
+        host_override: Option<HostOverride>,
     },
     /// Execute an HTTP request with streaming (for large responses)
     ExecuteStreamingRequest {
         id: u64,
         request: Request,
         environment: Option<Environment>,
+        tls: Option<TlsProfile>,
+        host_override: Option<HostOverride>,
     },
     /// Cancel a pending request
     CancelRequest(u64),
 
     // WebSocket commands
     /// Connect to a WebSocket server
-    ConnectWebSocket { id: u64, url: String },
+    ConnectWebSocket {
+        id: u64,
+        url: String,
+        tls: Option<TlsProfile>,
+    },
     /// Send a message through an active WebSocket connection
     SendWebSocketMessage { id: u64, message: String },
     /// Close a WebSocket connection
@@ -37,6 +48,9 @@ pub enum NetworkCommand {
         variables: Option<String>,
         headers: Vec<crate::models::Header>,
         auth: crate::models::AuthType,
+        proxy: ProxySetting,
+        tls: Option<TlsProfile>,
+        host_override: Option<HostOverride>,
     },
 
     /// Shutdown the network actor
@@ -51,8 +65,15 @@ pub enum NetworkResponse {
     Success {
         id: u64,
         status: u16,
+        /// Body as received, formatted for display by the app layer
         body: String,
         time_ms: u64,
+        timing: ResponseTiming,
+        /// Present for HTTPS responses
+        tls: Option<TlsDetails>,
+        headers: Vec<Header>,
+        /// e.g. `HTTP/1.1`
+        http_version: String,
     },
     /// Streaming chunk received
     StreamChunk {
@@ -1,9 +1,42 @@
 //! Render state - data structure sent from App layer to UI for rendering
+//!
+//! Large parts (bodies, the WebSocket log, the workspace) are shared
+//! with the app state, so building one per event stays cheap.
 
-use crate::app::state::WsLogEntry;
+use std::sync::Arc;
+
+use crate::app::search::{Matches, ResponseSearch};
+use crate::app::state::{TreeRows, WsLogEntry};
+use crate::append_log::AppendLog;
+use crate::discovery::validate::Violation;
 use crate::discovery::WorkspaceProject;
-use crate::messages::ui_events::{AppTab, AuthField, GqlField, InputMode, Panel};
+use crate::formatting::BodyKind;
+use crate::indexed_text::IndexedText;
+use crate::messages::ui_events::{
+    AppTab, AuthField, GqlField, InputMode, OpenPopups, Panel, ResponseView,
+};
 use crate::models::{AuthType, Header, HttpMethod, Response};
+use crate::response_diff::ResponseDiff;
+
+/// One line of the collections popup
+#[derive(Debug, Clone)]
+pub struct CollectionRow {
+    pub depth: usize,
+    pub name: String,
+    /// `None` for collections and folders
+    pub method: Option<HttpMethod>,
+}
+
+/// One line of the servers popup
+#[derive(Debug, Clone)]
+pub struct ServerRow {
+    /// `None` for a server, else the name of its variable
+    pub variable: Option<String>,
+    /// Resolved URL of a server, or the value of a variable
+    pub text: String,
+    /// A server requests are built against, or a variable with choices
+    pub active: bool,
+}
 
 /// Complete state needed by the UI to render
 #[derive(Debug, Clone)]
@@ -14,11 +47,19 @@ pub struct RenderState {
     // HTTP Request data
     pub method: HttpMethod,
     pub url: String,
-    pub body: String,
-    pub headers: Vec<Header>,
+    pub body: Arc<str>,
+    pub headers: Arc<[Header]>,
     pub auth: AuthType,
     /// Whether SSL certificate errors should be ignored (for testing environments)
     pub ignore_ssl_errors: bool,
+    /// Effective proxy, when it differs from the system default
+    pub proxy_indicator: Option<String>,
+    /// TLS profile applying to the active tab's URL ("mTLS" or "custom CA")
+    pub tls_indicator: Option<&'static str>,
+    /// Unix socket the HTTP request is sent over, if any
+    pub unix_socket: Option<String>,
+    /// Where connections go when a host override applies, e.g. `10.0.0.12:8443`
+    pub host_override: Option<String>,
 
     // UI state
     pub active_panel: Panel,
@@ -27,7 +68,25 @@ pub struct RenderState {
 
     // HTTP Response
     pub response: Response,
-    pub response_scroll: u16,
+    /// Format of the shown body
+    pub body_kind: BodyKind,
+    /// The body pretty printed, when that is what is shown
+    pub pretty_body: Option<IndexedText>,
+    pub response_raw: bool,
+    /// Filter expression of the current request, empty for none
+    pub response_filter: String,
+    /// The body through `response_filter`, or why it could not be applied
+    pub filtered_body: Option<Result<IndexedText, String>>,
+    pub search: ResponseSearch,
+    /// Matches of `search` in the shown body, or why the regex is invalid
+    pub search_matches: Arc<Matches>,
+    /// Visible lines of the JSON tree, built only in the tree view, or
+    /// why the body could not be parsed
+    pub tree: Option<Arc<TreeRows>>,
+    pub tree_selected: usize,
+    /// First body line shown in the response panel
+    pub response_scroll: usize,
+    pub response_view: ResponseView,
     pub is_loading: bool,
 
     // Headers panel
@@ -41,15 +100,50 @@ pub struct RenderState {
     pub history_index: Option<usize>,
 
     // Workspace
-    pub workspace: Option<WorkspaceProject>,
+    pub workspace: Option<Arc<WorkspaceProject>>,
     pub workspace_path_input: String,
     pub selected_endpoint: usize,
+    /// Spec violations of the response; `None` when it was not validated
+    pub spec_violations: Option<Vec<Violation>>,
 
     // Popups
     pub show_help: bool,
     pub show_curl_import: bool,
     pub curl_import_buffer: String,
     pub show_workspace_input: bool,
+    pub show_proxy_input: bool,
+    pub proxy_input: String,
+    pub show_export: bool,
+    /// Index into `CodeTarget::ALL`
+    pub export_target: usize,
+    /// Whether exported code has environment variables substituted
+    pub export_substitute: bool,
+    pub show_capture_input: bool,
+    pub capture_name: String,
+    pub capture_value: String,
+    /// One-off message shown in the status bar
+    pub notice: Option<String>,
+    pub show_history_diff: bool,
+    /// Labels of the history entries, newest first, filled only while the
+    /// diff popup is open
+    pub history_rows: Vec<String>,
+    pub diff_selected: usize,
+    pub diff_base: Option<usize>,
+    pub history_diff: Option<Arc<ResponseDiff>>,
+    pub diff_scroll: usize,
+    pub show_servers: bool,
+    /// Servers of the workspace and their variables, filled only while the
+    /// popup is open
+    pub server_rows: Vec<ServerRow>,
+    pub server_row: usize,
+    /// Resolved URL of the selected workspace server
+    pub active_server: Option<String>,
+    pub show_collections: bool,
+    /// Rows of all collections, filled only while the popup is open
+    pub collection_rows: Vec<CollectionRow>,
+    pub selected_collection_row: usize,
+    /// Name of the active environment
+    pub environment: Option<String>,
 
     // WebSocket
     pub ws_url: String,
@@ -58,7 +152,7 @@ pub struct RenderState {
     #[allow(dead_code)] // Reserved for cursor display
     pub ws_editing_url: bool,
     pub ws_connected: bool,
-    pub ws_messages: Vec<WsLogEntry>,
+    pub ws_messages: AppendLog<WsLogEntry>,
     pub ws_input: String,
     #[allow(dead_code)] // Reserved for cursor display
     pub ws_input_cursor: usize,
@@ -68,14 +162,14 @@ pub struct RenderState {
     pub gql_endpoint: String,
     #[allow(dead_code)] // Reserved for cursor display
     pub gql_endpoint_cursor: usize,
-    pub gql_query: String,
+    pub gql_query: Arc<str>,
     #[allow(dead_code)] // Reserved for cursor display
     pub gql_query_cursor: usize,
-    pub gql_variables: String,
+    pub gql_variables: Arc<str>,
     #[allow(dead_code)] // Reserved for cursor display
     pub gql_variables_cursor: usize,
     pub gql_active_field: GqlField,
-    pub gql_response: String,
+    pub gql_response: IndexedText,
     pub gql_response_scroll: u16,
     pub gql_is_loading: bool,
     pub gql_time_ms: u64,
@@ -88,18 +182,32 @@ impl Default for RenderState {
             active_tab: AppTab::Http,
             method: HttpMethod::GET,
             url: String::from(DEFAULT_HTTP_URL),
-            body: String::new(),
-            headers: vec![
+            body: Arc::from(""),
+            headers: Arc::new([
                 Header::new("Content-Type", "application/json"),
                 Header::new("Accept", "application/json"),
-            ],
+            ]),
             auth: AuthType::None,
             ignore_ssl_errors: false,
+            proxy_indicator: None,
+            tls_indicator: None,
+            unix_socket: None,
+            host_override: None,
             active_panel: Panel::Url,
             input_mode: InputMode::Normal,
             cursor_position: 24,
             response: Response::default(),
+            body_kind: BodyKind::Text,
+            pretty_body: None,
+            response_raw: false,
+            response_filter: String::new(),
+            filtered_body: None,
+            search: ResponseSearch::default(),
+            search_matches: Arc::new(Ok(Vec::new())),
+            tree: None,
+            tree_selected: 0,
             response_scroll: 0,
+            response_view: ResponseView::Body,
             is_loading: false,
             selected_header: 0,
             auth_field: AuthField::Token,
@@ -107,29 +215,71 @@ impl Default for RenderState {
             workspace: None,
             workspace_path_input: String::new(),
             selected_endpoint: 0,
+            spec_violations: None,
             show_help: false,
             show_curl_import: false,
             curl_import_buffer: String::new(),
             show_workspace_input: false,
+            show_proxy_input: false,
+            proxy_input: String::new(),
+            show_export: false,
+            export_target: 0,
+            export_substitute: true,
+            show_capture_input: false,
+            capture_name: String::new(),
+            capture_value: String::new(),
+            notice: None,
+            show_servers: false,
+            server_rows: Vec::new(),
+            server_row: 0,
+            active_server: None,
+            show_history_diff: false,
+            history_rows: Vec::new(),
+            diff_selected: 0,
+            diff_base: None,
+            history_diff: None,
+            diff_scroll: 0,
+            show_collections: false,
+            collection_rows: Vec::new(),
+            selected_collection_row: 0,
+            environment: None,
             ws_url: String::from(DEFAULT_WS_URL),
             ws_url_cursor: 0,
             ws_editing_url: false,
             ws_connected: false,
-            ws_messages: Vec::new(),
+            ws_messages: AppendLog::default(),
             ws_input: String::new(),
             ws_input_cursor: 0,
             ws_scroll: 0,
             gql_endpoint: String::from("https://api.example.com/graphql"),
             gql_endpoint_cursor: 0,
-            gql_query: String::from("query {\n  \n}"),
+            gql_query: Arc::from("query {\n  \n}"),
             gql_query_cursor: 0,
-            gql_variables: String::from("{}"),
+            gql_variables: Arc::from("{}"),
             gql_variables_cursor: 0,
             gql_active_field: GqlField::Query,
-            gql_response: String::new(),
+            gql_response: IndexedText::default(),
             gql_response_scroll: 0,
             gql_is_loading: false,
             gql_time_ms: 0,
         }
     }
 }
+
+impl RenderState {
+    /// Popups currently capturing keyboard input
+    pub fn open_popups(&self) -> OpenPopups {
+        OpenPopups {
+            help: self.show_help,
+            curl_import: self.show_curl_import,
+            workspace_input: self.show_workspace_input,
+            proxy_input: self.show_proxy_input,
+            export: self.show_export,
+            collections: self.show_collections,
+            search: self.search.editing,
+            capture_input: self.show_capture_input,
+            history_diff: self.show_history_diff,
+            servers: self.show_servers,
+        }
+    }
+}
@@ -22,6 +22,8 @@ pub enum UiEvent {
     PrevPanel,
     ScrollUp,
     ScrollDown,
+    ToggleResponseView,
+    ToggleRawBody,
 
     // Input editing
     StartEditing,
@@ -69,11 +71,85 @@ pub enum UiEvent {
     // cURL
     ShowCurlImport,
     CurlImportChar(char),
+    /// Pasted text, kept whole so multi-line commands are not submitted early
+    CurlImportPaste(String),
     CurlImportBackspace,
     ImportCurl,
     CancelCurlImport,
     ExportCurl,
 
+    // Code export
+    ShowExport,
+    ExportNext,
+    ExportPrev,
+    ToggleExportSubstitution,
+    ExportToClipboard,
+    ExportToFile,
+    CancelExport,
+
+    // Collections
+    ShowCollections,
+    CollectionNext,
+    CollectionPrev,
+    OpenCollectionEntry,
+    ExportCollection,
+    ExportOpenApiYaml,
+    ExportOpenApiJson,
+    CancelCollections,
+    CycleEnvironment,
+    ExportHistoryHar,
+
+    // Response search
+    OpenSearch,
+    SearchChar(char),
+    SearchBackspace,
+    ConfirmSearch,
+    ClearSearch,
+    ToggleSearchCase,
+    ToggleSearchRegex,
+    SearchNext,
+    SearchPrev,
+    SaveHttpFile,
+
+    // JSON tree
+    TreeUp,
+    TreeDown,
+    TreeToggle,
+    TreeExpand,
+    TreeCollapse,
+    CopyTreePath,
+    ShowCaptureInput,
+    CaptureInputChar(char),
+    CaptureInputBackspace,
+    ApplyCapture,
+    CancelCaptureInput,
+
+    // History diff
+    ShowHistoryDiff,
+    DiffUp,
+    DiffDown,
+    DiffPick,
+    DiffPreviousRun,
+    DiffBack,
+
+    // Workspace servers
+    ShowServers,
+    ServerUp,
+    ServerDown,
+    ServerPick,
+    ServerChoice(bool),
+    MapServerToEnvironment,
+    CloseServers,
+
+    // Proxy
+    ShowProxyInput,
+    ProxyInputChar(char),
+    ProxyInputBackspace,
+    ApplyProxyInput,
+    /// Save the popup input as the global proxy
+    SaveProxyInputAsDefault,
+    CancelProxyInput,
+
     // WebSocket actions
     WsConnect,
     WsDisconnect,
@@ -165,15 +241,77 @@ pub enum GqlField {
     Variables,
 }
 
+/// What the HTTP response panel shows
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum ResponseView {
+    #[default]
+    Body,
+    /// Foldable JSON tree
+    Tree,
+    /// Status, timing and TLS details
+    Info,
+}
+
+impl ResponseView {
+    pub fn next(&self) -> ResponseView {
+        match self {
+            ResponseView::Body => ResponseView::Tree,
+            ResponseView::Tree => ResponseView::Info,
+            ResponseView::Info => ResponseView::Body,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ResponseView::Body => "Body",
+            ResponseView::Tree => "Tree",
+            ResponseView::Info => "Info",
+        }
+    }
+}
+
+/// Popups that are currently open (they capture keyboard input)
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct OpenPopups {
+    pub help: bool,
+    pub curl_import: bool,
+    pub workspace_input: bool,
+    pub proxy_input: bool,
+    pub export: bool,
+    pub collections: bool,
+    /// The response search bar is taking input
+    pub search: bool,
+    /// The capture-variable popup of the JSON tree
+    pub capture_input: bool,
+    /// The history picker or comparison
+    pub history_diff: bool,
+    /// The workspace servers popup
+    pub servers: bool,
+}
+
+impl OpenPopups {
+    pub fn any(&self) -> bool {
+        self.help
+            || self.curl_import
+            || self.workspace_input
+            || self.proxy_input
+            || self.export
+            || self.collections
+            || self.search
+            || self.capture_input
+            || self.history_diff
+            || self.servers
+    }
+}
+
 /// Convert a key event to a UiEvent based on current UI context
 pub fn key_to_ui_event(
     key: KeyEvent,
     active_tab: AppTab,
     active_panel: Panel,
     input_mode: InputMode,
-    show_help: bool,
-    show_curl_import: bool,
-    show_workspace_input: bool,
+    response_view: ResponseView,
+    popups: OpenPopups,
 ) -> Option<UiEvent> {
     use crossterm::event::KeyEventKind;
 
@@ -191,7 +329,7 @@ pub fn key_to_ui_event(
     }
 
     // Tab switching: 1, 2, 3 keys (only in normal mode, not editing)
-    if input_mode == InputMode::Normal && !show_help && !show_curl_import && !show_workspace_input {
+    if input_mode == InputMode::Normal && !popups.any() {
         match key.code {
             KeyCode::Char('1') => return Some(UiEvent::SwitchTab(AppTab::Http)),
             KeyCode::Char('2') => return Some(UiEvent::SwitchTab(AppTab::WebSocket)),
@@ -201,11 +339,11 @@ pub fn key_to_ui_event(
     }
 
     // Handle popups first (same for all tabs)
-    if show_help {
+    if popups.help {
         return Some(UiEvent::CloseHelp);
     }
 
-    if show_curl_import {
+    if popups.curl_import {
         return match key.code {
             KeyCode::Esc => Some(UiEvent::CancelCurlImport),
             KeyCode::Enter => Some(UiEvent::ImportCurl),
@@ -215,7 +353,7 @@ pub fn key_to_ui_event(
         };
     }
 
-    if show_workspace_input {
+    if popups.workspace_input {
         return match key.code {
             KeyCode::Esc => Some(UiEvent::CancelWorkspaceInput),
             KeyCode::Enter => Some(UiEvent::LoadWorkspace),
@@ -226,9 +364,94 @@ pub fn key_to_ui_event(
         };
     }
 
+    if popups.proxy_input {
+        return match key.code {
+            KeyCode::Esc => Some(UiEvent::CancelProxyInput),
+            KeyCode::Enter => Some(UiEvent::ApplyProxyInput),
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                Some(UiEvent::SaveProxyInputAsDefault)
+            }
+            KeyCode::Backspace => Some(UiEvent::ProxyInputBackspace),
+            KeyCode::Char(c) => Some(UiEvent::ProxyInputChar(c)),
+            _ => None,
+        };
+    }
+
+    if popups.export {
+        return match key.code {
+            KeyCode::Esc => Some(UiEvent::CancelExport),
+            KeyCode::Down | KeyCode::Char('j') => Some(UiEvent::ExportNext),
+            KeyCode::Up | KeyCode::Char('k') => Some(UiEvent::ExportPrev),
+            KeyCode::Char('e') => Some(UiEvent::ToggleExportSubstitution),
+            KeyCode::Enter | KeyCode::Char('y') => Some(UiEvent::ExportToClipboard),
+            KeyCode::Char('f') => Some(UiEvent::ExportToFile),
+            _ => None,
+        };
+    }
+
+    if popups.collections {
+        return match key.code {
+            KeyCode::Esc => Some(UiEvent::CancelCollections),
+            KeyCode::Down | KeyCode::Char('j') => Some(UiEvent::CollectionNext),
+            KeyCode::Up | KeyCode::Char('k') => Some(UiEvent::CollectionPrev),
+            KeyCode::Enter => Some(UiEvent::OpenCollectionEntry),
+            KeyCode::Char('x') => Some(UiEvent::ExportCollection),
+            KeyCode::Char('o') => Some(UiEvent::ExportOpenApiYaml),
+            KeyCode::Char('O') => Some(UiEvent::ExportOpenApiJson),
+            _ => None,
+        };
+    }
+
+    if popups.search {
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        return match key.code {
+            KeyCode::Esc => Some(UiEvent::ClearSearch),
+            KeyCode::Enter => Some(UiEvent::ConfirmSearch),
+            KeyCode::Backspace => Some(UiEvent::SearchBackspace),
+            KeyCode::Char('c') if alt => Some(UiEvent::ToggleSearchCase),
+            KeyCode::Char('r') if alt => Some(UiEvent::ToggleSearchRegex),
+            KeyCode::Char(c) => Some(UiEvent::SearchChar(c)),
+            _ => None,
+        };
+    }
+
+    if popups.capture_input {
+        return match key.code {
+            KeyCode::Esc => Some(UiEvent::CancelCaptureInput),
+            KeyCode::Enter => Some(UiEvent::ApplyCapture),
+            KeyCode::Backspace => Some(UiEvent::CaptureInputBackspace),
+            KeyCode::Char(c) => Some(UiEvent::CaptureInputChar(c)),
+            _ => None,
+        };
+    }
+
+    if popups.history_diff {
+        return match key.code {
+            KeyCode::Esc => Some(UiEvent::DiffBack),
+            KeyCode::Up | KeyCode::Char('k') => Some(UiEvent::DiffUp),
+            KeyCode::Down | KeyCode::Char('j') => Some(UiEvent::DiffDown),
+            KeyCode::Enter => Some(UiEvent::DiffPick),
+            KeyCode::Char('p') => Some(UiEvent::DiffPreviousRun),
+            _ => None,
+        };
+    }
+
+    if popups.servers {
+        return match key.code {
+            KeyCode::Esc => Some(UiEvent::CloseServers),
+            KeyCode::Up | KeyCode::Char('k') => Some(UiEvent::ServerUp),
+            KeyCode::Down | KeyCode::Char('j') => Some(UiEvent::ServerDown),
+            KeyCode::Enter => Some(UiEvent::ServerPick),
+            KeyCode::Left | KeyCode::Char('h') => Some(UiEvent::ServerChoice(false)),
+            KeyCode::Right | KeyCode::Char('l') => Some(UiEvent::ServerChoice(true)),
+            KeyCode::Char('m') => Some(UiEvent::MapServerToEnvironment),
+            _ => None,
+        };
+    }
+
     // Tab-specific key handling
     match active_tab {
-        AppTab::Http => handle_http_tab_keys(key, active_panel, input_mode),
+        AppTab::Http => handle_http_tab_keys(key, active_panel, input_mode, response_view),
         AppTab::WebSocket => handle_ws_tab_keys(key, input_mode),
         AppTab::GraphQL => handle_gql_tab_keys(key, input_mode),
     }
@@ -239,21 +462,50 @@ fn handle_http_tab_keys(
     key: KeyEvent,
     active_panel: Panel,
     input_mode: InputMode,
+    response_view: ResponseView,
 ) -> Option<UiEvent> {
+    // The tree view takes over navigation keys of the response panel
+    if input_mode == InputMode::Normal
+        && active_panel == Panel::Response
+        && response_view == ResponseView::Tree
+    {
+        let event = match key.code {
+            KeyCode::Up | KeyCode::Char('k') => Some(UiEvent::TreeUp),
+            KeyCode::Down | KeyCode::Char('j') => Some(UiEvent::TreeDown),
+            KeyCode::Enter | KeyCode::Char(' ') => Some(UiEvent::TreeToggle),
+            KeyCode::Right | KeyCode::Char('l') => Some(UiEvent::TreeExpand),
+            KeyCode::Left | KeyCode::Char('h') => Some(UiEvent::TreeCollapse),
+            KeyCode::Char('y') => Some(UiEvent::CopyTreePath),
+            KeyCode::Char('a') => Some(UiEvent::ShowCaptureInput),
+            _ => None,
+        };
+        if event.is_some() {
+            return event;
+        }
+    }
+
     match input_mode {
         InputMode::Normal => match key.code {
             KeyCode::Char('q') => Some(UiEvent::Quit),
             KeyCode::Char('?') => Some(UiEvent::ToggleHelp),
             KeyCode::Char('i') if active_panel == Panel::Url => Some(UiEvent::ShowCurlImport),
             KeyCode::Char('c') => Some(UiEvent::ExportCurl),
+            KeyCode::Char('g') => Some(UiEvent::ShowExport),
             KeyCode::Tab => Some(UiEvent::NextPanel),
             KeyCode::BackTab => Some(UiEvent::PrevPanel),
             KeyCode::Char('e') | KeyCode::Enter => match active_panel {
-                Panel::Url | Panel::Body | Panel::Auth => Some(UiEvent::StartEditing),
+                Panel::Url | Panel::Body | Panel::Auth | Panel::Response => {
+                    Some(UiEvent::StartEditing)
+                }
                 Panel::Headers => Some(UiEvent::ToggleHeader),
                 Panel::Workspace => Some(UiEvent::SelectEndpoint),
-                Panel::Response => None,
             },
+            KeyCode::Char('f') if active_panel == Panel::Response => Some(UiEvent::StartEditing),
+            KeyCode::Char('/') if active_panel == Panel::Response => Some(UiEvent::OpenSearch),
+            KeyCode::Char('n') if active_panel == Panel::Response => Some(UiEvent::SearchNext),
+            KeyCode::Char('N') if active_panel == Panel::Response => Some(UiEvent::SearchPrev),
+            KeyCode::Esc if active_panel == Panel::Response => Some(UiEvent::ClearSearch),
+            KeyCode::Char('b') if active_panel == Panel::Workspace => Some(UiEvent::ShowServers),
             KeyCode::Char('m') => Some(UiEvent::CycleMethod),
             KeyCode::Char('s') => Some(UiEvent::SendRequest),
             KeyCode::Up => match active_panel {
@@ -270,10 +522,20 @@ fn handle_http_tab_keys(
             },
             KeyCode::Char('w') => Some(UiEvent::FocusWorkspace),
             KeyCode::Char('o') => Some(UiEvent::OpenWorkspaceInput),
+            KeyCode::Char('C') => Some(UiEvent::ShowCollections),
+            KeyCode::Char('E') => Some(UiEvent::CycleEnvironment),
+            KeyCode::Char('H') => Some(UiEvent::ExportHistoryHar),
+            KeyCode::Char('D') => Some(UiEvent::ShowHistoryDiff),
+            KeyCode::Char('S') => Some(UiEvent::SaveHttpFile),
             KeyCode::Char('a') if active_panel == Panel::Headers => Some(UiEvent::AddHeader),
             KeyCode::Char('d') if active_panel == Panel::Headers => Some(UiEvent::DeleteHeader),
             KeyCode::Char('t') if active_panel == Panel::Auth => Some(UiEvent::CycleAuth),
+            KeyCode::Char('v') if active_panel == Panel::Response => {
+                Some(UiEvent::ToggleResponseView)
+            }
+            KeyCode::Char('r') if active_panel == Panel::Response => Some(UiEvent::ToggleRawBody),
             KeyCode::Char('k') => Some(UiEvent::ToggleSslErrors),
+            KeyCode::Char('p') => Some(UiEvent::ShowProxyInput),
             _ => None,
         },
         InputMode::Editing => match key.code {
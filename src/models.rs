@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::indexed_text::IndexedText;
 
 /// HTTP Method enum
 #[allow(clippy::upper_case_acronyms)]
@@ -10,6 +13,8 @@ pub enum HttpMethod {
     PUT,
     PATCH,
     DELETE,
+    HEAD,
+    OPTIONS,
 }
 
 impl HttpMethod {
@@ -20,6 +25,22 @@ impl HttpMethod {
             HttpMethod::PUT => "PUT",
             HttpMethod::PATCH => "PATCH",
             HttpMethod::DELETE => "DELETE",
+            HttpMethod::HEAD => "HEAD",
+            HttpMethod::OPTIONS => "OPTIONS",
+        }
+    }
+
+    /// Parse a method name, case-insensitively
+    pub fn parse(s: &str) -> Option<HttpMethod> {
+        match s.to_uppercase().as_str() {
+            "GET" => Some(HttpMethod::GET),
+            "POST" => Some(HttpMethod::POST),
+            "PUT" => Some(HttpMethod::PUT),
+            "PATCH" => Some(HttpMethod::PATCH),
+            "DELETE" => Some(HttpMethod::DELETE),
+            "HEAD" => Some(HttpMethod::HEAD),
+            "OPTIONS" => Some(HttpMethod::OPTIONS),
+            _ => None,
         }
     }
 
@@ -29,7 +50,9 @@ impl HttpMethod {
             HttpMethod::POST => HttpMethod::PUT,
             HttpMethod::PUT => HttpMethod::PATCH,
             HttpMethod::PATCH => HttpMethod::DELETE,
-            HttpMethod::DELETE => HttpMethod::GET,
+            HttpMethod::DELETE => HttpMethod::HEAD,
+            HttpMethod::HEAD => HttpMethod::OPTIONS,
+            HttpMethod::OPTIONS => HttpMethod::GET,
         }
     }
 
@@ -51,7 +74,7 @@ pub enum AuthType {
 }
 
 /// HTTP Header
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Header {
     pub key: String,
     pub value: String,
@@ -68,6 +91,149 @@ impl Header {
     }
 }
 
+/// A manually configured proxy server
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    /// Proxy URL; the scheme selects the protocol (http, https, socks5, socks5h)
+    pub url: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Hosts that bypass the proxy (same syntax as `NO_PROXY`)
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+}
+
+impl ProxyConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        ProxyConfig {
+            url: url.into(),
+            username: None,
+            password: None,
+            no_proxy: Vec::new(),
+        }
+    }
+}
+
+/// How requests reach the network
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProxySetting {
+    /// Use the `HTTP_PROXY`/`HTTPS_PROXY`/`ALL_PROXY`/`NO_PROXY` environment variables
+    #[default]
+    System,
+    /// Connect directly, ignoring any environment proxy
+    Direct,
+    /// Use the given proxy
+    Manual(ProxyConfig),
+}
+
+impl ProxySetting {
+    /// The setting with {{variable}} patterns in the proxy URL,
+    /// credentials and bypass list replaced
+    pub fn substitute(&self, environment: &Environment) -> ProxySetting {
+        match self {
+            ProxySetting::Manual(proxy) => ProxySetting::Manual(ProxyConfig {
+                url: environment.substitute(&proxy.url),
+                username: proxy.username.as_deref().map(|u| environment.substitute(u)),
+                password: proxy.password.as_deref().map(|p| environment.substitute(p)),
+                no_proxy: proxy.no_proxy.iter().map(|host| environment.substitute(host)).collect(),
+            }),
+            other => other.clone(),
+        }
+    }
+}
+
+/// Client certificate presented for mutual TLS
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ClientCertificate {
+    /// PEM certificate chain and private key files
+    Pem { cert: PathBuf, key: PathBuf },
+    /// PKCS#12 archive holding the certificate chain and key
+    Pkcs12 {
+        path: PathBuf,
+        #[serde(default)]
+        password: String,
+    },
+}
+
+/// Extra TLS material for connections to matching hosts
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TlsProfile {
+    /// Host patterns (`api.corp.com`, `*.corp.com`); empty matches every host
+    #[serde(default)]
+    pub hosts: Vec<String>,
+    /// PEM files with CA certificates trusted in addition to the system roots
+    #[serde(default)]
+    pub ca_certs: Vec<PathBuf>,
+    #[serde(default)]
+    pub client_cert: Option<ClientCertificate>,
+}
+
+impl TlsProfile {
+    /// Whether the profile applies to `host`. `*.example.com` matches
+    /// any subdomain of example.com but not example.com itself.
+    pub fn matches(&self, host: &str) -> bool {
+        if self.hosts.is_empty() {
+            return true;
+        }
+        let host = host.to_ascii_lowercase();
+        self.hosts.iter().any(|pattern| {
+            let pattern = pattern.to_ascii_lowercase();
+            match pattern.strip_prefix("*.") {
+                Some(domain) => host
+                    .strip_suffix(domain)
+                    .is_some_and(|rest| rest.ends_with('.') && rest.len() > 1),
+                None => host == pattern,
+            }
+        })
+    }
+}
+
+/// Connect to a different address than DNS gives for a host, keeping the
+/// URL (and so the `Host` header and TLS SNI) unchanged. Mirrors curl's
+/// `--resolve` and `--connect-to`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct HostOverride {
+    /// Host name as it appears in the URL, or `*` for any host
+    pub host: String,
+    /// Only apply to URLs on this port; `None` matches every port
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// IP address or host name to connect to; `None` keeps the URL host
+    #[serde(default)]
+    pub address: Option<String>,
+    /// Port to connect to; `None` keeps the URL port
+    #[serde(default)]
+    pub connect_port: Option<u16>,
+}
+
+impl HostOverride {
+    /// Whether the override applies to a URL with this host and port
+    pub fn matches(&self, host: &str, port: u16) -> bool {
+        (self.host == "*" || self.host.eq_ignore_ascii_case(host))
+            && self.port.map_or(true, |p| p == port)
+    }
+
+    /// This override limited to `host`, so that a `*` override catches
+    /// only the request's own host and not, say, its proxy's
+    pub fn pinned_to(mut self, host: &str) -> HostOverride {
+        if self.host == "*" {
+            self.host = host.to_string();
+        }
+        self
+    }
+
+    /// Short description of where connections go, e.g. `10.0.0.12:8443`
+    pub fn target(&self) -> String {
+        let address = self.address.as_deref().unwrap_or(&self.host);
+        match self.connect_port {
+            Some(port) => format!("{}:{}", address, port),
+            None => address.to_string(),
+        }
+    }
+}
+
 /// A single HTTP request
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Request {
@@ -80,6 +246,50 @@ pub struct Request {
     /// When true, ignores SSL certificate errors (useful for testing environments)
     #[serde(default)]
     pub ignore_ssl_errors: bool,
+    /// Proxy override; `None` inherits from the environment or global settings
+    #[serde(default)]
+    pub proxy: Option<ProxySetting>,
+    /// Unix domain socket to send the request over instead of TCP
+    #[serde(default)]
+    pub unix_socket: Option<String>,
+    /// Host resolution overrides, checked before the environment's
+    #[serde(default)]
+    pub host_overrides: Vec<HostOverride>,
+    /// TLS profile override; `None` uses the environment or global profiles
+    #[serde(default)]
+    pub tls: Option<TlsProfile>,
+    /// JSONPath or jq expression the response body is shown through
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub response_filter: String,
+}
+
+/// Scheme for URLs addressing a Unix socket, e.g.
+/// `unix:///var/run/docker.sock:/v1.43/containers/json`
+pub const UNIX_SCHEME: &str = "unix://";
+
+/// Split a `unix://<socket>:<path>` URL into the socket path and the
+/// request path. The path defaults to `/` when omitted.
+pub fn split_unix_url(url: &str) -> Option<(&str, &str)> {
+    let rest = url.strip_prefix(UNIX_SCHEME)?;
+    let (socket, path) = match rest.find(":/") {
+        Some(pos) => (&rest[..pos], &rest[pos + 1..]),
+        None => (rest.strip_suffix(':').unwrap_or(rest), "/"),
+    };
+    if socket.is_empty() {
+        return None;
+    }
+    Some((socket, path))
+}
+
+impl Request {
+    /// Rewrite a `unix://` URL into `unix_socket` plus an HTTP URL that
+    /// the client can send; other URLs are left untouched
+    pub fn resolve_unix_url(&mut self) {
+        if let Some((socket, path)) = split_unix_url(&self.url) {
+            self.unix_socket = Some(socket.to_string());
+            self.url = format!("http://localhost{}", path);
+        }
+    }
 }
 
 impl Default for Request {
@@ -96,6 +306,11 @@ impl Default for Request {
             body: String::new(),
             auth: AuthType::None,
             ignore_ssl_errors: false,
+            proxy: None,
+            unix_socket: None,
+            host_overrides: Vec::new(),
+            tls: None,
+            response_filter: String::new(),
         }
     }
 }
@@ -105,16 +320,63 @@ impl Default for Request {
 pub struct Collection {
     pub name: String,
     pub requests: Vec<Request>,
+    /// Nested folders, listed after the collection's own requests
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub folders: Vec<Collection>,
+}
+
+/// One row of a collection tree: the collection or a folder when
+/// `request` is `None`, a request otherwise
+#[derive(Clone, Debug)]
+pub struct CollectionEntry<'a> {
+    pub depth: usize,
+    pub name: &'a str,
+    pub request: Option<&'a Request>,
 }
 
-#[allow(dead_code)] // Prepared for future collection feature
 impl Collection {
     pub fn new(name: impl Into<String>) -> Self {
         Collection {
             name: name.into(),
             requests: Vec::new(),
+            folders: Vec::new(),
         }
     }
+
+    /// The collection, its requests and its folders in display order
+    pub fn entries(&self) -> Vec<CollectionEntry<'_>> {
+        let mut entries = Vec::new();
+        self.collect_entries(0, &mut entries);
+        entries
+    }
+
+    fn collect_entries<'a>(&'a self, depth: usize, entries: &mut Vec<CollectionEntry<'a>>) {
+        entries.push(CollectionEntry {
+            depth,
+            name: &self.name,
+            request: None,
+        });
+        for request in &self.requests {
+            entries.push(CollectionEntry {
+                depth: depth + 1,
+                name: &request.name,
+                request: Some(request),
+            });
+        }
+        for folder in &self.folders {
+            folder.collect_entries(depth + 1, entries);
+        }
+    }
+
+    /// Number of requests, folders included
+    pub fn request_count(&self) -> usize {
+        self.requests.len()
+            + self
+                .folders
+                .iter()
+                .map(Collection::request_count)
+                .sum::<usize>()
+    }
 }
 
 /// Environment variables
@@ -122,14 +384,29 @@ impl Collection {
 pub struct Environment {
     pub name: String,
     pub variables: HashMap<String, String>,
+    /// Proxy override for requests sent in this environment
+    #[serde(default)]
+    pub proxy: Option<ProxySetting>,
+    /// TLS profiles, checked before the global ones
+    #[serde(default)]
+    pub tls: Vec<TlsProfile>,
+    /// Host resolution overrides, the first matching one is used
+    #[serde(default)]
+    pub host_overrides: Vec<HostOverride>,
+    /// Workspace server URL endpoints load against in this environment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
 }
 
-#[allow(dead_code)] // Prepared for future environment feature
 impl Environment {
     pub fn new(name: impl Into<String>) -> Self {
         Environment {
             name: name.into(),
             variables: HashMap::new(),
+            proxy: None,
+            tls: Vec::new(),
+            host_overrides: Vec::new(),
+            server: None,
         }
     }
 
@@ -152,19 +429,115 @@ impl Environment {
     }
 }
 
+/// Per-phase timing breakdown of an HTTP exchange.
+/// Connection phases are `None` when a pooled connection was reused.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ResponseTiming {
+    pub dns_ms: Option<u64>,
+    pub connect_ms: Option<u64>,
+    pub tls_ms: Option<u64>,
+    /// Time from the connection being ready until response headers arrived
+    pub ttfb_ms: u64,
+    pub download_ms: u64,
+}
+
+impl ResponseTiming {
+    /// Phases in waterfall order, skipping the ones that did not happen
+    pub fn phases(&self) -> Vec<(&'static str, u64)> {
+        let mut phases = Vec::new();
+        if let Some(ms) = self.dns_ms {
+            phases.push(("DNS", ms));
+        }
+        if let Some(ms) = self.connect_ms {
+            phases.push(("Connect", ms));
+        }
+        if let Some(ms) = self.tls_ms {
+            phases.push(("TLS", ms));
+        }
+        phases.push(("TTFB", self.ttfb_ms));
+        phases.push(("Download", self.download_ms));
+        phases
+    }
+
+    /// True when no new connection was opened for the request
+    pub fn reused_connection(&self) -> bool {
+        self.dns_ms.is_none() && self.connect_ms.is_none() && self.tls_ms.is_none()
+    }
+}
+
+/// A certificate presented by the server
+#[derive(Clone, Debug, PartialEq)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    /// DNS names and IP addresses from the subjectAltName extension
+    pub sans: Vec<String>,
+    pub not_before: chrono::DateTime<chrono::Utc>,
+    pub not_after: chrono::DateTime<chrono::Utc>,
+    /// SHA-256 of the DER encoding, as colon-separated hex
+    pub fingerprint: String,
+}
+
+impl CertificateInfo {
+    /// Whole days until the certificate expires, negative once it has
+    pub fn days_remaining(&self) -> i64 {
+        (self.not_after - chrono::Utc::now()).num_days()
+    }
+}
+
+/// Negotiated TLS parameters and the server's certificate chain (leaf first).
+/// Version and cipher are unknown when the connection was resumed from
+/// before the details could be captured.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TlsDetails {
+    pub version: Option<String>,
+    pub cipher: Option<String>,
+    pub chain: Vec<CertificateInfo>,
+}
+
+impl TlsDetails {
+    /// The certificate closest to expiry, when it is within the warning window
+    pub fn expiring_certificate(&self) -> Option<&CertificateInfo> {
+        use crate::constants::CERT_EXPIRY_WARNING_DAYS;
+        self.chain
+            .iter()
+            .min_by_key(|cert| cert.not_after)
+            .filter(|cert| cert.days_remaining() < CERT_EXPIRY_WARNING_DAYS)
+    }
+}
+
+/// Global application settings
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Settings {
+    /// Default proxy behaviour for all requests
+    #[serde(default)]
+    pub proxy: ProxySetting,
+    /// TLS profiles; the first one matching the request host is used
+    #[serde(default)]
+    pub tls: Vec<TlsProfile>,
+}
+
 /// Response from HTTP request
 #[derive(Clone, Debug)]
 pub struct Response {
     pub status_code: Option<u16>,
-    pub body: String,
+    /// Shared between history and render states, never copied
+    pub body: IndexedText,
     pub time_ms: u64,
+    /// Phase breakdown, absent for errors and locally generated content
+    pub timing: Option<ResponseTiming>,
+    /// TLS session details, present for HTTPS responses
+    pub tls: Option<TlsDetails>,
+    pub headers: Vec<Header>,
+    /// Protocol of the response, e.g. `HTTP/2.0`
+    pub http_version: Option<String>,
 }
 
 impl Default for Response {
     fn default() -> Self {
         Response {
             status_code: None,
-            body: String::from(
+            body: IndexedText::from(
                 r#"Quick Reference:
 ────────────────────────────
   s     Send request
@@ -179,15 +552,104 @@ impl Default for Response {
 Press 's' to send your first request!"#,
             ),
             time_ms: 0,
+            timing: None,
+            tls: None,
+            headers: Vec::new(),
+            http_version: None,
         }
     }
 }
 
 /// History entry
 #[derive(Clone, Debug)]
-#[allow(dead_code)] // Fields stored for future history display feature
 pub struct HistoryEntry {
     pub request: Request,
+    /// The request with the variables of the environment it was sent in
+    /// substituted; ones that environment lacked stay as written
+    pub sent: Request,
     pub response: Response,
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proxy_substitution() {
+        let mut environment = Environment::new("Corp");
+        environment.set("proxyHost", "proxy.corp");
+        environment.set("proxyPass", "s3cret");
+        let proxy = ProxySetting::Manual(ProxyConfig {
+            url: "http://{{proxyHost}}:3128".to_string(),
+            username: Some("me".to_string()),
+            password: Some("{{proxyPass}}".to_string()),
+            no_proxy: vec!["{{proxyHost}}".to_string()],
+        });
+        let ProxySetting::Manual(resolved) = proxy.substitute(&environment) else {
+            panic!("expected a manual proxy");
+        };
+        assert_eq!(resolved.url, "http://proxy.corp:3128");
+        assert_eq!(resolved.password.as_deref(), Some("s3cret"));
+        assert_eq!(resolved.no_proxy, vec!["proxy.corp".to_string()]);
+        assert_eq!(ProxySetting::Direct.substitute(&environment), ProxySetting::Direct);
+    }
+
+    #[test]
+    fn test_tls_profile_host_patterns() {
+        let profile = TlsProfile {
+            hosts: vec!["api.corp.com".to_string(), "*.internal".to_string()],
+            ..TlsProfile::default()
+        };
+        assert!(profile.matches("api.corp.com"));
+        assert!(profile.matches("API.Corp.com"));
+        assert!(profile.matches("db.internal"));
+        assert!(profile.matches("a.b.internal"));
+        assert!(!profile.matches("internal"));
+        assert!(!profile.matches("notinternal"));
+        assert!(!profile.matches("www.corp.com"));
+        assert!(TlsProfile::default().matches("anything.example"));
+    }
+
+    #[test]
+    fn test_host_override_matching() {
+        let exact = HostOverride {
+            host: "API.example.com".to_string(),
+            port: Some(443),
+            address: Some("10.0.0.12".to_string()),
+            connect_port: None,
+        };
+        assert!(exact.matches("api.example.com", 443));
+        assert!(!exact.matches("api.example.com", 80));
+        assert!(!exact.matches("www.example.com", 443));
+        assert_eq!(exact.target(), "10.0.0.12");
+
+        let any = HostOverride {
+            host: "*".to_string(),
+            port: None,
+            address: None,
+            connect_port: Some(8443),
+        };
+        assert!(any.matches("whatever.test", 80));
+        assert_eq!(any.target(), "*:8443");
+    }
+
+    #[test]
+    fn test_split_unix_url() {
+        assert_eq!(
+            split_unix_url("unix:///var/run/docker.sock:/v1.43/containers/json?all=1"),
+            Some(("/var/run/docker.sock", "/v1.43/containers/json?all=1"))
+        );
+        assert_eq!(split_unix_url("unix:///tmp/app.sock"), Some(("/tmp/app.sock", "/")));
+        assert_eq!(split_unix_url("unix://:/health"), None);
+        assert_eq!(split_unix_url("http://localhost/"), None);
+
+        let mut request = Request {
+            url: "unix:///tmp/app.sock:/health".to_string(),
+            ..Request::default()
+        };
+        request.resolve_unix_url();
+        assert_eq!(request.unix_socket.as_deref(), Some("/tmp/app.sock"));
+        assert_eq!(request.url, "http://localhost/health");
+    }
+}
@@ -1,15 +1,18 @@
 //! Network actor - runs HTTP requests and WebSockets in Tokio async runtime
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use tokio::sync::{mpsc, oneshot};
 use tokio::task::JoinSet;
 
 use crate::messages::{NetworkCommand, NetworkResponse};
 use crate::network::client::{
-    create_client, create_insecure_client, execute_request, execute_streaming_request,
+    build_client, execute_request, execute_streaming_request, pin_host_override, ClientOptions,
 };
 use crate::network::websocket::connect_websocket;
 
+/// Clients kept for reuse; each holds its own connection pool
+const MAX_CLIENTS: usize = 8;
+
 /// Tracks an active request for cancellation
 #[allow(dead_code)]
 struct ActiveRequest {
@@ -25,9 +28,9 @@ struct ActiveWebSocket {
 
 /// Network actor that processes HTTP request and WebSocket commands
 pub struct NetworkActor {
-    client: reqwest::Client,
-    /// Client that ignores SSL certificate errors (for testing environments)
-    insecure_client: reqwest::Client,
+    /// Clients for the latest connection options (SSL, proxy, TLS profile),
+    /// most recently used first
+    clients: VecDeque<(ClientOptions, reqwest::Client)>,
     response_tx: mpsc::UnboundedSender<NetworkResponse>,
     active_requests: JoinSet<()>,
     cancel_handles: HashMap<u64, ActiveRequest>,
@@ -37,8 +40,7 @@ pub struct NetworkActor {
 impl NetworkActor {
     pub fn new(response_tx: mpsc::UnboundedSender<NetworkResponse>) -> Self {
         NetworkActor {
-            client: create_client(),
-            insecure_client: create_insecure_client(),
+            clients: VecDeque::new(),
             response_tx,
             active_requests: JoinSet::new(),
             cancel_handles: HashMap::new(),
@@ -46,6 +48,35 @@ impl NetworkActor {
         }
     }
 
+    /// Get (or build) the client for the given options.
+    /// Reports a build failure, such as an invalid proxy URL or an unreadable
+    /// certificate, as an error response.
+    fn client_for(&mut self, id: u64, options: ClientOptions) -> Option<reqwest::Client> {
+        if let Some(index) = self.clients.iter().position(|(o, _)| *o == options) {
+            let entry = self.clients.remove(index)?;
+            let client = entry.1.clone();
+            self.clients.push_front(entry);
+            return Some(client);
+        }
+
+        match build_client(&options) {
+            Ok(client) => {
+                self.clients.push_front((options, client.clone()));
+                self.clients.truncate(MAX_CLIENTS);
+                Some(client)
+            }
+            Err(e) => {
+                tracing::warn!(id, error = %e, "Failed to build HTTP client");
+                let _ = self.response_tx.send(NetworkResponse::Error {
+                    id,
+                    message: format!("Invalid connection settings: {:#}", e),
+                    time_ms: 0,
+                });
+                None
+            }
+        }
+    }
+
     /// Run the network actor message loop
     pub async fn run(mut self, mut cmd_rx: mpsc::UnboundedReceiver<NetworkCommand>) {
         loop {
@@ -55,14 +86,12 @@ impl NetworkActor {
                 // Handle incoming commands
                 cmd = cmd_rx.recv() => {
                     match cmd {
-                        Some(NetworkCommand::ExecuteRequest { id, request, environment }) => {
-                            let response_tx = self.response_tx.clone();
-                            // Use insecure client if ignore_ssl_errors is enabled
-                            let client = if request.ignore_ssl_errors {
-                                self.insecure_client.clone()
-                            } else {
-                                self.client.clone()
+                        Some(NetworkCommand::ExecuteRequest { id, request, environment, tls, host_override }) => {
+                            let options = ClientOptions::for_request(&request, environment.as_ref(), tls, host_override);
+                            let Some(client) = self.client_for(id, options) else {
+                                continue;
                             };
+                            let response_tx = self.response_tx.clone();
 
                             // Simple buffered request - no cancellation tracking
                             self.active_requests.spawn(async move {
@@ -77,17 +106,15 @@ impl NetworkActor {
                             });
                         }
 
-                        Some(NetworkCommand::ExecuteStreamingRequest { id, request, environment }) => {
+                        Some(NetworkCommand::ExecuteStreamingRequest { id, request, environment, tls, host_override }) => {
+                            let options = ClientOptions::for_request(&request, environment.as_ref(), tls, host_override);
+                            let Some(client) = self.client_for(id, options) else {
+                                continue;
+                            };
                             let (cancel_tx, cancel_rx) = oneshot::channel();
                             self.cancel_handles.insert(id, ActiveRequest { cancel_tx });
 
                             let response_tx = self.response_tx.clone();
-                            // Use insecure client if ignore_ssl_errors is enabled
-                            let client = if request.ignore_ssl_errors {
-                                self.insecure_client.clone()
-                            } else {
-                                self.client.clone()
-                            };
 
                             self.active_requests.spawn(async move {
                                 execute_streaming_request(
@@ -109,7 +136,7 @@ impl NetworkActor {
                             }
                         }
 
-                        Some(NetworkCommand::ConnectWebSocket { id, url }) => {
+                        Some(NetworkCommand::ConnectWebSocket { id, url, tls }) => {
                             let (cancel_tx, cancel_rx) = oneshot::channel();
                             let (message_tx, message_rx) = mpsc::unbounded_channel();
 
@@ -121,7 +148,7 @@ impl NetworkActor {
                             let response_tx = self.response_tx.clone();
 
                             self.active_requests.spawn(async move {
-                                connect_websocket(id, &url, response_tx, message_rx, cancel_rx).await;
+                                connect_websocket(id, &url, tls, response_tx, message_rx, cancel_rx).await;
                             });
                         }
 
@@ -137,9 +164,13 @@ impl NetworkActor {
                             }
                         }
 
-                        Some(NetworkCommand::ExecuteGraphQL { id, endpoint, query, variables, headers, auth }) => {
+                        Some(NetworkCommand::ExecuteGraphQL { id, endpoint, query, variables, headers, auth, proxy, tls, host_override }) => {
+                            let host_override = pin_host_override(host_override, &endpoint);
+                            let options = ClientOptions { proxy, tls, host_override, ..ClientOptions::default() };
+                            let Some(client) = self.client_for(id, options) else {
+                                continue;
+                            };
                             let response_tx = self.response_tx.clone();
-                            let client = self.client.clone();
 
                             self.active_requests.spawn(async move {
                                 tracing::info!(id, endpoint = %endpoint, "Executing GraphQL query");
@@ -181,3 +212,29 @@ impl NetworkActor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ProxyConfig, ProxySetting};
+
+    #[tokio::test]
+    async fn test_client_cache_is_bounded() {
+        let (response_tx, _response_rx) = mpsc::unbounded_channel();
+        let mut actor = NetworkActor::new(response_tx);
+        let options = |port: usize| ClientOptions {
+            proxy: ProxySetting::Manual(ProxyConfig::new(format!("http://proxy.test:{}", port))),
+            ..ClientOptions::default()
+        };
+
+        for port in 0..MAX_CLIENTS + 3 {
+            assert!(actor.client_for(0, options(port)).is_some());
+        }
+        assert_eq!(actor.clients.len(), MAX_CLIENTS);
+        // The oldest were dropped; a reused one moves to the front
+        assert!(actor.clients.iter().all(|(o, _)| *o != options(0)));
+        actor.client_for(0, options(5));
+        assert_eq!(actor.clients.len(), MAX_CLIENTS);
+        assert_eq!(actor.clients[0].0, options(5));
+    }
+}
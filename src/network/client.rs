@@ -3,18 +3,43 @@
 use base64::Engine;
 use futures_util::StreamExt;
 use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::{mpsc, oneshot};
 
 use crate::messages::NetworkResponse;
-use crate::models::{AuthType, Environment, HttpMethod, Request};
-
-/// Format detailed error messages for HTTP request failures
-fn format_request_error(e: &reqwest::Error, url: &str) -> String {
+use crate::models::{
+    AuthType, Environment, Header, HostOverride, HttpMethod, ProxyConfig, ProxySetting, Request,
+    TlsDetails, TlsProfile,
+};
+use crate::network::inspect::{self, HandshakeRecorder};
+use crate::network::timing::{TimingLayer, TimingRecorder, TimingResolver};
+use crate::network::tls;
+
+/// Format detailed error messages for HTTP request failures.
+/// `peer` is whatever was captured of a failed TLS handshake.
+fn format_request_error(e: &reqwest::Error, url: &str, peer: Option<&TlsDetails>) -> String {
     let mut lines = Vec::new();
 
     // Main error classification
-    if e.is_timeout() {
+    if let Some(tls_error) = inspect::tls_error(e) {
+        lines.push("🔒 TLS ERROR: Secure connection failed".to_string());
+        lines.extend(inspect::diagnose(tls_error, peer));
+        if let Some(peer) = peer {
+            lines.push(String::new());
+            lines.push("Server certificate chain:".to_string());
+            for (i, cert) in peer.chain.iter().enumerate() {
+                lines.push(format!("  [{}] {}", i, cert.subject));
+                lines.push(format!("      issuer: {}", cert.issuer));
+                lines.push(format!(
+                    "      valid:  {} → {}",
+                    cert.not_before.format("%Y-%m-%d"),
+                    cert.not_after.format("%Y-%m-%d")
+                ));
+            }
+        }
+    } else if e.is_timeout() {
         lines.push("⏱ TIMEOUT: Request timed out after 30 seconds".to_string());
         lines.push("  → The server took too long to respond".to_string());
         lines.push("  → Check if the server is running and accessible".to_string());
@@ -72,24 +97,6 @@ fn format_request_error(e: &reqwest::Error, url: &str) -> String {
     } else {
         // Generic error with full details
         lines.push("❓ REQUEST FAILED".to_string());
-
-        let error_str = e.to_string().to_lowercase();
-
-        // Check for TLS/SSL errors
-        if error_str.contains("ssl")
-            || error_str.contains("tls")
-            || error_str.contains("certificate")
-        {
-            lines.push("  → TLS/SSL error detected".to_string());
-            if error_str.contains("certificate") && error_str.contains("expired") {
-                lines.push("  → Server certificate may be expired".to_string());
-            } else if error_str.contains("self-signed") || error_str.contains("unknown ca") {
-                lines.push("  → Server has an untrusted certificate".to_string());
-            } else if error_str.contains("handshake") {
-                lines.push("  → TLS handshake failed".to_string());
-            }
-        }
-
         lines.push(format!("  → {}", e));
     }
 
@@ -109,6 +116,33 @@ fn format_request_error(e: &reqwest::Error, url: &str) -> String {
     lines.join("\n")
 }
 
+/// Leaf certificate of the connection a response arrived on
+fn peer_certificate(resp: &reqwest::Response) -> Option<&[u8]> {
+    resp.extensions()
+        .get::<reqwest::tls::TlsInfo>()
+        .and_then(|info| info.peer_certificate())
+}
+
+/// Response headers in received order; values that are not valid
+/// UTF-8 are decoded lossily
+fn response_headers(resp: &reqwest::Response) -> Vec<Header> {
+    resp.headers()
+        .iter()
+        .map(|(name, value)| Header::new(name.as_str(), String::from_utf8_lossy(value.as_bytes())))
+        .collect()
+}
+
+fn http_version(resp: &reqwest::Response) -> String {
+    match resp.version() {
+        reqwest::Version::HTTP_09 => "HTTP/0.9",
+        reqwest::Version::HTTP_10 => "HTTP/1.0",
+        reqwest::Version::HTTP_2 => "HTTP/2.0",
+        reqwest::Version::HTTP_3 => "HTTP/3.0",
+        _ => "HTTP/1.1",
+    }
+    .to_string()
+}
+
 /// Build a request from the given parameters
 fn build_request(
     client: &reqwest::Client,
@@ -128,6 +162,8 @@ fn build_request(
         HttpMethod::PUT => client.put(&url),
         HttpMethod::PATCH => client.patch(&url),
         HttpMethod::DELETE => client.delete(&url),
+        HttpMethod::HEAD => client.head(&url),
+        HttpMethod::OPTIONS => client.request(reqwest::Method::OPTIONS, &url),
     };
 
     // Add headers
@@ -181,29 +217,30 @@ pub async fn execute_request(
     request_id: u64,
 ) -> NetworkResponse {
     let start = Instant::now();
+    let recorder = TimingRecorder::new();
+    let handshake = HandshakeRecorder::new();
     let req_builder = build_request(client, &request, &environment);
 
-    let result = req_builder.send().await;
+    let result = recorder.scope(handshake.scope(req_builder.send())).await;
     let elapsed = start.elapsed().as_millis() as u64;
 
     match result {
         Ok(resp) => {
+            recorder.mark_headers();
+            let tls = handshake.finish(peer_certificate(&resp));
             let status = resp.status().as_u16();
+            let (headers, http_version) = (response_headers(&resp), http_version(&resp));
             match resp.text().await {
-                Ok(body) => {
-                    let formatted =
-                        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&body) {
-                            serde_json::to_string_pretty(&json).unwrap_or(body)
-                        } else {
-                            body
-                        };
-                    NetworkResponse::Success {
-                        id: request_id,
-                        status,
-                        body: formatted,
-                        time_ms: elapsed,
-                    }
-                }
+                Ok(body) => NetworkResponse::Success {
+                    id: request_id,
+                    status,
+                    body,
+                    time_ms: start.elapsed().as_millis() as u64,
+                    timing: recorder.finish(),
+                    tls,
+                    headers,
+                    http_version,
+                },
                 Err(e) => NetworkResponse::Error {
                     id: request_id,
                     message: format!("Error reading body: {}", e),
@@ -212,7 +249,7 @@ pub async fn execute_request(
             }
         }
         Err(e) => {
-            let msg = format_request_error(&e, &request.url);
+            let msg = format_request_error(&e, &request.url, handshake.failed_handshake().as_ref());
             NetworkResponse::Error {
                 id: request_id,
                 message: msg,
@@ -232,13 +269,18 @@ pub async fn execute_streaming_request(
     mut cancel_rx: oneshot::Receiver<()>,
 ) {
     let start = Instant::now();
+    let recorder = TimingRecorder::new();
+    let handshake = HandshakeRecorder::new();
     let req_builder = build_request(client, &request, &environment);
 
-    let result = req_builder.send().await;
+    let result = recorder.scope(handshake.scope(req_builder.send())).await;
 
     match result {
         Ok(resp) => {
+            recorder.mark_headers();
+            let tls = handshake.finish(peer_certificate(&resp));
             let status = resp.status().as_u16();
+            let (headers, http_version) = (response_headers(&resp), http_version(&resp));
             let mut stream = resp.bytes_stream();
             let mut total_bytes = 0usize;
             let mut body = String::new();
@@ -273,19 +315,16 @@ pub async fn execute_streaming_request(
                                 return;
                             }
                             None => {
-                                // Stream complete - try to format as JSON
-                                let formatted = if let Ok(json) = serde_json::from_str::<serde_json::Value>(&body) {
-                                    serde_json::to_string_pretty(&json).unwrap_or(body)
-                                } else {
-                                    body
-                                };
-
-                                // Send final formatted body as success
+                                // Stream complete - send the whole body as success
                                 let _ = response_tx.send(NetworkResponse::Success {
                                     id: request_id,
                                     status,
-                                    body: formatted,
+                                    body,
                                     time_ms: start.elapsed().as_millis() as u64,
+                                    timing: recorder.finish(),
+                                    tls,
+                                    headers,
+                                    http_version,
                                 });
                                 return;
                             }
@@ -295,7 +334,7 @@ pub async fn execute_streaming_request(
             }
         }
         Err(e) => {
-            let msg = format_request_error(&e, &request.url);
+            let msg = format_request_error(&e, &request.url, handshake.failed_handshake().as_ref());
             let _ = response_tx.send(NetworkResponse::Error {
                 id: request_id,
                 message: msg,
@@ -366,28 +405,28 @@ pub async fn execute_graphql(
         crate::models::AuthType::None => {}
     }
 
-    let result = req_builder.send().await;
+    let recorder = TimingRecorder::new();
+    let handshake = HandshakeRecorder::new();
+    let result = recorder.scope(handshake.scope(req_builder.send())).await;
     let elapsed = start.elapsed().as_millis() as u64;
 
     match result {
         Ok(resp) => {
+            recorder.mark_headers();
+            let tls = handshake.finish(peer_certificate(&resp));
             let status = resp.status().as_u16();
+            let (headers, http_version) = (response_headers(&resp), http_version(&resp));
             match resp.text().await {
-                Ok(body) => {
-                    // Pretty-print JSON response
-                    let formatted =
-                        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&body) {
-                            serde_json::to_string_pretty(&json).unwrap_or(body)
-                        } else {
-                            body
-                        };
-                    NetworkResponse::Success {
-                        id: request_id,
-                        status,
-                        body: formatted,
-                        time_ms: elapsed,
-                    }
-                }
+                Ok(body) => NetworkResponse::Success {
+                    id: request_id,
+                    status,
+                    body,
+                    time_ms: start.elapsed().as_millis() as u64,
+                    timing: recorder.finish(),
+                    tls,
+                    headers,
+                    http_version,
+                },
                 Err(e) => NetworkResponse::Error {
                     id: request_id,
                     message: format!("Error reading response: {}", e),
@@ -396,7 +435,7 @@ pub async fn execute_graphql(
             }
         }
         Err(e) => {
-            let msg = format_request_error(&e, &endpoint);
+            let msg = format_request_error(&e, &endpoint, handshake.failed_handshake().as_ref());
             NetworkResponse::Error {
                 id: request_id,
                 message: msg,
@@ -406,24 +445,159 @@ pub async fn execute_graphql(
     }
 }
 
-/// Create an HTTP client with default configuration
-pub fn create_client() -> reqwest::Client {
-    use std::time::Duration;
+/// Connection-level settings that need a dedicated client.
+/// The network actor keeps one client per distinct set of options.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ClientOptions {
+    /// Skip certificate validation (testing environments only!)
+    pub insecure: bool,
+    pub proxy: ProxySetting,
+    /// Extra CA certificates and client certificate
+    pub tls: Option<TlsProfile>,
+    /// Send over this Unix socket instead of TCP; proxies are ignored
+    pub unix_socket: Option<PathBuf>,
+    /// Connect somewhere else than DNS says for the request's host
+    pub host_override: Option<HostOverride>,
+}
 
-    reqwest::Client::builder()
-        .timeout(Duration::from_secs(30))
-        .build()
-        .unwrap_or_else(|_| reqwest::Client::new())
+impl ClientOptions {
+    /// Options for a request whose proxy has already been resolved, with
+    /// the environment's variables substituted in the proxy
+    pub fn for_request(
+        request: &Request,
+        environment: Option<&Environment>,
+        tls: Option<TlsProfile>,
+        host_override: Option<HostOverride>,
+    ) -> Self {
+        let proxy = request.proxy.clone().unwrap_or_default();
+        let url = match environment {
+            Some(environment) => environment.substitute(&request.url),
+            None => request.url.clone(),
+        };
+        ClientOptions {
+            insecure: request.ignore_ssl_errors,
+            proxy: match environment {
+                Some(environment) => proxy.substitute(environment),
+                None => proxy,
+            },
+            tls,
+            unix_socket: request.unix_socket.as_ref().map(PathBuf::from),
+            host_override: pin_host_override(host_override, &url),
+        }
+    }
+}
+
+/// Limit a host override to the host of `url`; see [`HostOverride::pinned_to`]
+pub fn pin_host_override(host_override: Option<HostOverride>, url: &str) -> Option<HostOverride> {
+    let host = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string));
+    match (host_override, host) {
+        (Some(host_override), Some(host)) => Some(host_override.pinned_to(&host)),
+        (host_override, _) => host_override,
+    }
 }
 
-/// Create an HTTP client that ignores SSL certificate errors
-/// WARNING: Only use for testing environments, not production!
-pub fn create_insecure_client() -> reqwest::Client {
+/// Build a client instrumented for per-phase timing
+pub fn build_client(options: &ClientOptions) -> anyhow::Result<reqwest::Client> {
     use std::time::Duration;
 
-    reqwest::Client::builder()
+    let tls_config = tls::client_config(options.insecure, options.tls.as_ref())?;
+    let mut builder = reqwest::Client::builder()
         .timeout(Duration::from_secs(30))
-        .danger_accept_invalid_certs(true)
-        .build()
-        .unwrap_or_else(|_| reqwest::Client::new())
+        .use_preconfigured_tls(tls_config)
+        .tls_info(true)
+        .dns_resolver(Arc::new(TimingResolver::new(options.host_override.clone())))
+        .connector_layer(TimingLayer);
+
+    builder = match &options.proxy {
+        // reqwest reads the proxy environment variables by default
+        ProxySetting::System => builder,
+        ProxySetting::Direct => builder.no_proxy(),
+        ProxySetting::Manual(config) => builder.proxy(build_proxy(config)?),
+    };
+
+    if let Some(socket) = &options.unix_socket {
+        builder = with_unix_socket(builder, socket)?;
+    }
+
+    Ok(builder.build()?)
+}
+
+#[cfg(unix)]
+fn with_unix_socket(
+    builder: reqwest::ClientBuilder,
+    socket: &Path,
+) -> anyhow::Result<reqwest::ClientBuilder> {
+    Ok(builder.unix_socket(socket))
+}
+
+#[cfg(not(unix))]
+fn with_unix_socket(
+    _builder: reqwest::ClientBuilder,
+    socket: &Path,
+) -> anyhow::Result<reqwest::ClientBuilder> {
+    anyhow::bail!(
+        "Cannot use {}: Unix sockets are not supported on this platform",
+        socket.display()
+    )
+}
+
+fn build_proxy(config: &ProxyConfig) -> reqwest::Result<reqwest::Proxy> {
+    let mut proxy = reqwest::Proxy::all(&config.url)?;
+    if let Some(username) = &config.username {
+        proxy = proxy.basic_auth(username, config.password.as_deref().unwrap_or(""));
+    }
+    if !config.no_proxy.is_empty() {
+        proxy = proxy.no_proxy(reqwest::NoProxy::from_string(&config.no_proxy.join(",")));
+    }
+    Ok(proxy)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_wildcard_override_skips_the_proxy_host() {
+        let proxy = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy_port = proxy.local_addr().unwrap().port();
+        let proxied = tokio::spawn(async move {
+            let (mut socket, _) = proxy.accept().await.unwrap();
+            let mut head = vec![0u8; 1024];
+            let n = socket.read(&mut head).await.unwrap();
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok")
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&head[..n]).into_owned()
+        });
+
+        let request = Request {
+            url: "http://api.test/ping".to_string(),
+            proxy: Some(ProxySetting::Manual(ProxyConfig::new(format!(
+                "http://localhost:{}",
+                proxy_port
+            )))),
+            ..Request::default()
+        };
+        // Nothing listens there; the proxy would be unreachable through it
+        let host_override = HostOverride {
+            host: "*".to_string(),
+            port: None,
+            address: Some("127.0.0.2".to_string()),
+            connect_port: None,
+        };
+        let options = ClientOptions::for_request(&request, None, None, Some(host_override));
+        assert_eq!(options.host_override.as_ref().unwrap().host, "api.test");
+
+        let client = build_client(&options).unwrap();
+        match execute_request(&client, request, None, 1).await {
+            NetworkResponse::Success { body, .. } => assert_eq!(body, "ok"),
+            other => panic!("request failed: {:?}", other),
+        }
+        assert!(proxied.await.unwrap().starts_with("GET http://api.test/ping "));
+    }
 }
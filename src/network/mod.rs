@@ -4,6 +4,9 @@
 
 pub mod actor;
 pub mod client;
+pub mod inspect;
+pub mod timing;
+pub mod tls;
 pub mod websocket;
 
 pub use actor::NetworkActor;
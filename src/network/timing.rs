@@ -0,0 +1,288 @@
+//! Request timing instrumentation - DNS, connect, TLS, TTFB and download phases
+//!
+//! The shared clients are built with three hooks: a DNS resolver, a connector
+//! layer and a rustls session store. Each hook records a phase mark on the
+//! [`TimingRecorder`] of the request currently being driven, found through a
+//! task-local. Connections opened in the background (or reused from the pool)
+//! therefore never leak into another request's breakdown.
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use rustls::client::{
+    ClientSessionMemoryCache, ClientSessionStore, Tls12ClientSessionValue, Tls13ClientSessionValue,
+};
+use rustls::pki_types::ServerName;
+use rustls::NamedGroup;
+
+use crate::models::{HostOverride, ResponseTiming};
+
+tokio::task_local! {
+    static RECORDER: Arc<TimingRecorder>;
+}
+
+/// Phase marks collected while a request is in flight
+#[derive(Debug, Default, Clone, Copy)]
+struct Marks {
+    dns_start: Option<Instant>,
+    dns_end: Option<Instant>,
+    connect_start: Option<Instant>,
+    tls_start: Option<Instant>,
+    connect_end: Option<Instant>,
+    headers: Option<Instant>,
+}
+
+impl Marks {
+    /// Derive phase durations. `start` is when the request was issued and
+    /// `end` when the body finished downloading.
+    fn to_timing(self, start: Instant, end: Instant) -> ResponseTiming {
+        let ms = |from: Instant, to: Instant| to.saturating_duration_since(from).as_millis() as u64;
+
+        let dns_ms = match (self.dns_start, self.dns_end) {
+            (Some(from), Some(to)) => Some(ms(from, to)),
+            _ => None,
+        };
+
+        // TCP connect runs from the end of DNS (or the connector call for IP
+        // literals) until the TLS handshake starts or the connector returns.
+        let connect_ms = self.connect_start.and_then(|connect_start| {
+            let from = self.dns_end.unwrap_or(connect_start);
+            self.tls_start.or(self.connect_end).map(|to| ms(from, to))
+        });
+
+        let tls_ms = match (self.tls_start, self.connect_end) {
+            (Some(from), Some(to)) => Some(ms(from, to)),
+            _ => None,
+        };
+
+        let ready = self.connect_end.unwrap_or(start);
+        let headers = self.headers.unwrap_or(end);
+
+        ResponseTiming {
+            dns_ms,
+            connect_ms,
+            tls_ms,
+            ttfb_ms: ms(ready, headers),
+            download_ms: ms(headers, end),
+        }
+    }
+}
+
+/// Collects phase marks for a single request
+#[derive(Debug)]
+pub struct TimingRecorder {
+    start: Instant,
+    marks: Mutex<Marks>,
+}
+
+impl TimingRecorder {
+    pub fn new() -> Arc<Self> {
+        Arc::new(TimingRecorder {
+            start: Instant::now(),
+            marks: Mutex::new(Marks::default()),
+        })
+    }
+
+    /// Drive `fut` with this recorder visible to the client hooks
+    pub async fn scope<F: Future>(self: &Arc<Self>, fut: F) -> F::Output {
+        RECORDER.scope(self.clone(), fut).await
+    }
+
+    /// Mark the arrival of the response headers
+    pub fn mark_headers(&self) {
+        if let Ok(mut marks) = self.marks.lock() {
+            marks.headers.get_or_insert_with(Instant::now);
+        }
+    }
+
+    /// Finish the request and return the breakdown
+    pub fn finish(&self) -> ResponseTiming {
+        let marks = self.marks.lock().map(|m| *m).unwrap_or_default();
+        marks.to_timing(self.start, Instant::now())
+    }
+}
+
+/// Record a mark on the recorder of the current task, if there is one.
+/// Only the first occurrence of each mark is kept.
+fn mark(select: fn(&mut Marks) -> &mut Option<Instant>) {
+    let _ = RECORDER.try_with(|recorder| {
+        if let Ok(mut marks) = recorder.marks.lock() {
+            select(&mut marks).get_or_insert_with(Instant::now);
+        }
+    });
+}
+
+/// DNS resolver that records the resolution phase and applies a host
+/// override, pinned to the request's host beforehand so that a proxy's
+/// host name resolves normally. hyper keeps a non-zero resolved port
+/// unless the URL has an explicit one, which is how the override's
+/// `connect_port` takes effect.
+#[derive(Debug, Default, Clone)]
+pub struct TimingResolver {
+    host_override: Option<Arc<HostOverride>>,
+}
+
+impl TimingResolver {
+    pub fn new(host_override: Option<HostOverride>) -> Self {
+        TimingResolver {
+            host_override: host_override.map(Arc::new),
+        }
+    }
+}
+
+impl Resolve for TimingResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let target = match self.host_override.as_deref() {
+            Some(o) if o.host.eq_ignore_ascii_case(name.as_str()) => (
+                o.address.clone().unwrap_or_else(|| name.as_str().to_string()),
+                o.connect_port.unwrap_or(0),
+            ),
+            _ => (name.as_str().to_string(), 0),
+        };
+        Box::pin(async move {
+            mark(|m| &mut m.dns_start);
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host(target).await?.collect();
+            mark(|m| &mut m.dns_end);
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Connector layer that records when a new connection is requested and when
+/// it is ready for use (after TCP connect and, for HTTPS, the TLS handshake)
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TimingLayer;
+
+impl<S> tower::Layer<S> for TimingLayer {
+    type Service = TimingConnector<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimingConnector { inner }
+    }
+}
+
+/// Connector service produced by [`TimingLayer`]
+#[derive(Debug, Clone)]
+pub struct TimingConnector<S> {
+    inner: S,
+}
+
+impl<S, R> tower::Service<R> for TimingConnector<S>
+where
+    S: tower::Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: R) -> Self::Future {
+        let connecting = self.inner.call(req);
+        Box::pin(async move {
+            mark(|m| &mut m.connect_start);
+            let result = connecting.await;
+            if result.is_ok() {
+                mark(|m| &mut m.connect_end);
+            }
+            result
+        })
+    }
+}
+
+/// TLS session cache that marks the start of the TLS handshake.
+///
+/// rustls asks the store for a key exchange hint while building the
+/// ClientHello, which happens right after the TCP connection is established.
+#[derive(Debug)]
+pub struct TimingSessionStore {
+    inner: ClientSessionMemoryCache,
+}
+
+impl Default for TimingSessionStore {
+    fn default() -> Self {
+        TimingSessionStore {
+            inner: ClientSessionMemoryCache::new(256),
+        }
+    }
+}
+
+impl ClientSessionStore for TimingSessionStore {
+    fn set_kx_hint(&self, server_name: ServerName<'static>, group: NamedGroup) {
+        self.inner.set_kx_hint(server_name, group)
+    }
+
+    fn kx_hint(&self, server_name: &ServerName<'_>) -> Option<NamedGroup> {
+        mark(|m| &mut m.tls_start);
+        self.inner.kx_hint(server_name)
+    }
+
+    fn set_tls12_session(&self, server_name: ServerName<'static>, value: Tls12ClientSessionValue) {
+        self.inner.set_tls12_session(server_name, value)
+    }
+
+    fn tls12_session(&self, server_name: &ServerName<'_>) -> Option<Tls12ClientSessionValue> {
+        self.inner.tls12_session(server_name)
+    }
+
+    fn remove_tls12_session(&self, server_name: &ServerName<'static>) {
+        self.inner.remove_tls12_session(server_name)
+    }
+
+    fn insert_tls13_ticket(&self, server_name: ServerName<'static>, value: Tls13ClientSessionValue) {
+        self.inner.insert_tls13_ticket(server_name, value)
+    }
+
+    fn take_tls13_ticket(&self, server_name: &ServerName<'static>) -> Option<Tls13ClientSessionValue> {
+        self.inner.take_tls13_ticket(server_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_phases_for_new_https_connection() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let marks = Marks {
+            dns_start: Some(at(1)),
+            dns_end: Some(at(11)),
+            connect_start: Some(at(0)),
+            tls_start: Some(at(31)),
+            connect_end: Some(at(71)),
+            headers: Some(at(171)),
+        };
+
+        let timing = marks.to_timing(start, at(176));
+        assert_eq!(timing.dns_ms, Some(10));
+        assert_eq!(timing.connect_ms, Some(20));
+        assert_eq!(timing.tls_ms, Some(40));
+        assert_eq!(timing.ttfb_ms, 100);
+        assert_eq!(timing.download_ms, 5);
+    }
+
+    #[test]
+    fn test_phases_for_reused_connection() {
+        let start = Instant::now();
+        let marks = Marks {
+            headers: Some(start + Duration::from_millis(50)),
+            ..Marks::default()
+        };
+
+        let timing = marks.to_timing(start, start + Duration::from_millis(60));
+        assert!(timing.reused_connection());
+        assert_eq!(timing.ttfb_ms, 50);
+        assert_eq!(timing.download_ms, 10);
+    }
+}
@@ -0,0 +1,311 @@
+//! TLS configuration for the HTTP and WebSocket clients
+
+use std::borrow::Cow;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context, Result};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::{Resumption, WebPkiServerVerifier};
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, RootCertStore, SignatureScheme};
+
+use crate::models::{ClientCertificate, TlsProfile};
+use crate::network::inspect::{self, RecordingVerifier};
+use crate::network::timing::TimingSessionStore;
+
+/// Build the rustls configuration used by the HTTP clients.
+///
+/// Negotiates HTTP/2 or HTTP/1.1 via ALPN and installs the timing session
+/// store on top of [`base_config`].
+pub fn client_config(insecure: bool, profile: Option<&TlsProfile>) -> Result<rustls::ClientConfig> {
+    let mut config = base_config(insecure, profile)?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    config.resumption = Resumption::store(Arc::new(TimingSessionStore::default()));
+    Ok(config)
+}
+
+/// Build the rustls configuration for WebSocket connections. No ALPN is
+/// offered, as the upgrade handshake requires HTTP/1.1.
+pub fn websocket_config(profile: Option<&TlsProfile>) -> Result<rustls::ClientConfig> {
+    base_config(false, profile)
+}
+
+/// Trust the platform's native roots plus the profile's CA certificates and
+/// present the profile's client certificate, if any. When `insecure` is set,
+/// server certificate validation is skipped entirely. Either way the
+/// handshake is recorded for inspection.
+fn base_config(insecure: bool, profile: Option<&TlsProfile>) -> Result<rustls::ClientConfig> {
+    let mut provider = rustls::crypto::ring::default_provider();
+    provider.cipher_suites = inspect::recording_cipher_suites(&provider.cipher_suites);
+    let provider = Arc::new(provider);
+
+    let verifier: Arc<dyn ServerCertVerifier> = if insecure {
+        Arc::new(NoVerifier {
+            provider: provider.clone(),
+        })
+    } else {
+        let mut roots = native_roots();
+        for path in profile.iter().flat_map(|p| &p.ca_certs) {
+            add_ca_certs(&mut roots, path)?;
+        }
+        WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+            .build()
+            .context("Failed to set up certificate verification")?
+    };
+
+    let builder = rustls::ClientConfig::builder_with_provider(provider)
+        .with_protocol_versions(rustls::DEFAULT_VERSIONS)
+        .expect("ring provider supports the default TLS versions")
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(RecordingVerifier::new(verifier)));
+
+    match profile.and_then(|p| p.client_cert.as_ref()) {
+        Some(identity) => {
+            let (chain, key) = load_client_certificate(identity)?;
+            builder
+                .with_client_auth_cert(chain, key)
+                .context("Client certificate does not match its private key")
+        }
+        None => Ok(builder.with_no_client_auth()),
+    }
+}
+
+/// Expand a leading `~` to the home directory
+fn expand_home(path: &Path) -> Cow<'_, Path> {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => Cow::Owned(home.join(rest)),
+        _ => Cow::Borrowed(path),
+    }
+}
+
+/// Add every certificate of a PEM bundle to the root store
+fn add_ca_certs(roots: &mut RootCertStore, path: &Path) -> Result<()> {
+    let path = &*expand_home(path);
+    let certs = read_pem_certs(path)
+        .with_context(|| format!("Failed to read CA bundle {}", path.display()))?;
+    for cert in certs {
+        roots
+            .add(cert)
+            .with_context(|| format!("Invalid CA certificate in {}", path.display()))?;
+    }
+    Ok(())
+}
+
+fn read_pem_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>> {
+    let certs = CertificateDer::pem_file_iter(path)?.collect::<Result<Vec<_>, _>>()?;
+    if certs.is_empty() {
+        bail!("no certificates found");
+    }
+    Ok(certs)
+}
+
+/// Load the certificate chain and private key presented for mutual TLS
+fn load_client_certificate(
+    identity: &ClientCertificate,
+) -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)> {
+    match identity {
+        ClientCertificate::Pem { cert, key } => {
+            let (cert, key) = (&*expand_home(cert), &*expand_home(key));
+            let chain = read_pem_certs(cert)
+                .with_context(|| format!("Failed to read client certificate {}", cert.display()))?;
+            let key = PrivateKeyDer::from_pem_file(key)
+                .with_context(|| format!("Failed to read private key {}", key.display()))?;
+            Ok((chain, key))
+        }
+        ClientCertificate::Pkcs12 { path, password } => {
+            let path = &*expand_home(path);
+            load_pkcs12(path, password)
+                .with_context(|| format!("Failed to read PKCS#12 archive {}", path.display()))
+        }
+    }
+}
+
+fn load_pkcs12(
+    path: &Path,
+    password: &str,
+) -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)> {
+    let data = std::fs::read(path)?;
+    let store = p12_keystore::KeyStore::from_pkcs12(
+        &data,
+        password,
+        p12_keystore::Pkcs12ImportPolicy::Relaxed,
+    )
+    .map_err(|e| anyhow!("{}", e))?;
+    let (_, keychain) = store
+        .private_key_chain()
+        .ok_or_else(|| anyhow!("no private key found"))?;
+    if keychain.certs().is_empty() {
+        bail!("no certificate found for the private key");
+    }
+
+    let chain = keychain
+        .certs()
+        .iter()
+        .map(|cert| CertificateDer::from(cert.as_der().to_vec()))
+        .collect();
+    let key = PrivatePkcs8KeyDer::from(keychain.key().as_der().to_vec()).into();
+    Ok((chain, key))
+}
+
+/// Load the platform's trusted root certificates, skipping unparsable ones
+fn native_roots() -> RootCertStore {
+    let mut roots = RootCertStore::empty();
+    let loaded = rustls_native_certs::load_native_certs();
+    for error in &loaded.errors {
+        tracing::warn!(%error, "Failed to load native certificate");
+    }
+    let (added, ignored) = roots.add_parsable_certificates(loaded.certs);
+    tracing::debug!(added, ignored, "Loaded native root certificates");
+    roots
+}
+
+/// Certificate verifier that accepts any server certificate.
+/// Signatures are still checked so the handshake itself stays well-formed.
+#[derive(Debug)]
+struct NoVerifier {
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for NoVerifier {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::inspect::tests::{TEST_CERT, TEST_KEY};
+    use std::path::PathBuf;
+
+    fn profile(ca_certs: Vec<PathBuf>, client_cert: Option<ClientCertificate>) -> TlsProfile {
+        TlsProfile { hosts: Vec::new(), ca_certs, client_cert }
+    }
+
+    /// The error of a configuration, with its causes
+    fn error(profile: &TlsProfile) -> String {
+        format!("{:#}", client_config(false, Some(profile)).unwrap_err())
+    }
+
+    /// A PKCS#12 archive of the test certificate and key
+    fn pkcs12(password: &str) -> Vec<u8> {
+        let cert = CertificateDer::from_pem_slice(TEST_CERT.as_bytes()).unwrap();
+        let key = PrivateKeyDer::from_pem_slice(TEST_KEY.as_bytes()).unwrap();
+        let chain = p12_keystore::PrivateKeyChain::new(
+            "client",
+            p12_keystore::PrivateKey::from_der(key.secret_der()).unwrap(),
+            [p12_keystore::Certificate::from_der(cert.as_ref()).unwrap()],
+        );
+        let mut store = p12_keystore::KeyStore::new();
+        store.add_entry("client", p12_keystore::KeyStoreEntry::PrivateKeyChain(chain));
+        store.writer(password).write().unwrap()
+    }
+
+    #[test]
+    fn test_client_certificates() {
+        let dir = tempfile::tempdir().unwrap();
+        let cert = dir.path().join("client.pem");
+        let key = dir.path().join("client.key");
+        let archive = dir.path().join("client.p12");
+        std::fs::write(&cert, TEST_CERT).unwrap();
+        std::fs::write(&key, TEST_KEY).unwrap();
+        std::fs::write(&archive, pkcs12("s3cret")).unwrap();
+
+        let pem = profile(Vec::new(), Some(ClientCertificate::Pem { cert: cert.clone(), key: key.clone() }));
+        assert!(client_config(false, Some(&pem)).unwrap().client_auth_cert_resolver.has_certs());
+        assert!(websocket_config(Some(&pem)).unwrap().client_auth_cert_resolver.has_certs());
+
+        let p12 = |password: &str| {
+            profile(
+                Vec::new(),
+                Some(ClientCertificate::Pkcs12 { path: archive.clone(), password: password.to_string() }),
+            )
+        };
+        assert!(client_config(false, Some(&p12("s3cret"))).unwrap().client_auth_cert_resolver.has_certs());
+        assert!(error(&p12("wrong")).starts_with(&format!("Failed to read PKCS#12 archive {}", archive.display())));
+
+        // A key given as the certificate has no certificate to present
+        let key_only = profile(Vec::new(), Some(ClientCertificate::Pem { cert: key.clone(), key: key.clone() }));
+        assert_eq!(
+            error(&key_only),
+            format!("Failed to read client certificate {}: no certificates found", key.display())
+        );
+        let missing_key = profile(
+            Vec::new(),
+            Some(ClientCertificate::Pem { cert: cert.clone(), key: dir.path().join("missing.key") }),
+        );
+        assert!(error(&missing_key).starts_with("Failed to read private key "));
+    }
+
+    #[test]
+    fn test_ca_bundles() {
+        let dir = tempfile::tempdir().unwrap();
+        let (bundle, empty) = (dir.path().join("ca.pem"), dir.path().join("empty.pem"));
+        std::fs::write(&bundle, TEST_CERT).unwrap();
+        std::fs::write(&empty, "").unwrap();
+
+        assert!(client_config(false, Some(&profile(vec![bundle], None))).is_ok());
+        assert_eq!(
+            error(&profile(vec![empty.clone()], None)),
+            format!("Failed to read CA bundle {}: no certificates found", empty.display())
+        );
+        let missing = dir.path().join("missing.pem");
+        assert!(error(&profile(vec![missing.clone()], None))
+            .starts_with(&format!("Failed to read CA bundle {}: ", missing.display())));
+        // Insecure connections verify nothing, so the bundle is not read
+        assert!(client_config(true, Some(&profile(vec![missing], None))).is_ok());
+    }
+
+    #[test]
+    fn test_expand_home() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand_home(Path::new("~/certs/ca.pem")), home.join("certs/ca.pem"));
+        assert_eq!(expand_home(Path::new("~")), home);
+        assert_eq!(expand_home(Path::new("~alice/ca.pem")), Path::new("~alice/ca.pem"));
+        assert_eq!(expand_home(Path::new("/etc/~/ca.pem")), Path::new("/etc/~/ca.pem"));
+    }
+}
@@ -1,21 +1,38 @@
 //! WebSocket client - connects to WebSocket servers
 
+use std::sync::Arc;
+
 use futures_util::{SinkExt, StreamExt};
 use tokio::sync::{mpsc, oneshot};
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio_tungstenite::{connect_async_tls_with_config, tungstenite::Message, Connector};
 
 use crate::messages::NetworkResponse;
+use crate::models::TlsProfile;
+use crate::network::tls;
 
 /// Connect to a WebSocket server and handle bidirectional communication
 pub async fn connect_websocket(
     id: u64,
     url: &str,
+    tls: Option<TlsProfile>,
     response_tx: mpsc::UnboundedSender<NetworkResponse>,
     mut message_rx: mpsc::UnboundedReceiver<String>,
     mut cancel_rx: oneshot::Receiver<()>,
 ) {
+    let tls_config = match tls::websocket_config(tls.as_ref()) {
+        Ok(config) => config,
+        Err(e) => {
+            let _ = response_tx.send(NetworkResponse::WebSocketError {
+                id,
+                error: format!("Invalid TLS settings: {:#}", e),
+            });
+            return;
+        }
+    };
+    let connector = Connector::Rustls(Arc::new(tls_config));
+
     // Attempt to connect
-    let ws_stream = match connect_async(url).await {
+    let ws_stream = match connect_async_tls_with_config(url, None, false, Some(connector)).await {
         Ok((stream, _response)) => stream,
         Err(e) => {
             let _ = response_tx.send(NetworkResponse::WebSocketError {
@@ -0,0 +1,380 @@
+//! Side-by-side comparison of two responses
+//!
+//! JSON bodies are compared structurally, so key order and formatting do
+//! not count as changes; other bodies are compared line by line after
+//! pretty printing. Status and headers are compared first.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::formatting::{self, BodyKind};
+use crate::json_tree::field_path;
+use crate::models::{HistoryEntry, Response};
+
+/// Line diffs of bodies larger than this (lines × lines, after dropping the
+/// common start and end) show the differing part as removed then added
+const MAX_LINE_DIFF_CELLS: usize = 4_000_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffKind {
+    /// Heading of the status, headers or body part
+    Section,
+    Same,
+    Added,
+    Removed,
+    Changed,
+}
+
+/// One row of the comparison; `left` is the older response
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiffRow {
+    pub kind: DiffKind,
+    /// JSONPath or header name; empty for body lines and section headings
+    pub label: String,
+    pub left: String,
+    pub right: String,
+}
+
+impl DiffRow {
+    fn new(kind: DiffKind, label: impl Into<String>, left: impl Into<String>, right: impl Into<String>) -> Self {
+        DiffRow {
+            kind,
+            label: label.into(),
+            left: left.into(),
+            right: right.into(),
+        }
+    }
+
+    fn section(title: &str) -> Self {
+        DiffRow::new(DiffKind::Section, "", title, "")
+    }
+}
+
+/// The comparison of two history entries, older on the left
+#[derive(Clone, Debug, Default)]
+pub struct ResponseDiff {
+    pub left_title: String,
+    pub right_title: String,
+    pub rows: Vec<DiffRow>,
+    /// Number of rows that are not `Same` or `Section`
+    pub changes: usize,
+}
+
+impl ResponseDiff {
+    pub fn new(old: &HistoryEntry, new: &HistoryEntry) -> Self {
+        let rows = diff_responses(&old.response, &new.response);
+        let changes = rows
+            .iter()
+            .filter(|row| !matches!(row.kind, DiffKind::Same | DiffKind::Section))
+            .count();
+        ResponseDiff {
+            left_title: entry_label(old),
+            right_title: entry_label(new),
+            rows,
+            changes,
+        }
+    }
+}
+
+/// `12:04:31 GET https://… → 200`
+pub fn entry_label(entry: &HistoryEntry) -> String {
+    let status = entry
+        .response
+        .status_code
+        .map(|code| code.to_string())
+        .unwrap_or_else(|| "—".to_string());
+    format!(
+        "{} {} {} → {}",
+        entry.timestamp.format("%H:%M:%S"),
+        entry.request.method.as_str(),
+        entry.request.url,
+        status
+    )
+}
+
+/// Status, header and body differences of two responses
+pub fn diff_responses(old: &Response, new: &Response) -> Vec<DiffRow> {
+    let mut rows = vec![DiffRow::section("Status")];
+    let status = |response: &Response| {
+        response
+            .status_code
+            .map(|code| code.to_string())
+            .unwrap_or_else(|| "no response".to_string())
+    };
+    let kind = if old.status_code == new.status_code {
+        DiffKind::Same
+    } else {
+        DiffKind::Changed
+    };
+    rows.push(DiffRow::new(kind, "status", status(old), status(new)));
+
+    rows.push(DiffRow::section("Headers"));
+    let header_rows = diff_headers(old, new);
+    if header_rows.is_empty() {
+        rows.push(DiffRow::new(DiffKind::Same, "", "no changes", "no changes"));
+    }
+    rows.extend(header_rows);
+
+    let parsed = (
+        serde_json::from_str::<Value>(&old.body),
+        serde_json::from_str::<Value>(&new.body),
+    );
+    if let (Ok(old_json), Ok(new_json)) = parsed {
+        rows.push(DiffRow::section("Body · JSON"));
+        let body_rows = diff_json(&old_json, &new_json);
+        if body_rows.is_empty() {
+            rows.push(DiffRow::new(DiffKind::Same, "", "identical", "identical"));
+        }
+        rows.extend(body_rows);
+    } else {
+        rows.push(DiffRow::section("Body · lines"));
+        rows.extend(diff_lines(&readable_body(old), &readable_body(new)));
+    }
+    rows
+}
+
+/// The body as the response panel shows it
+fn readable_body(response: &Response) -> String {
+    let content_type = response
+        .headers
+        .iter()
+        .find(|h| h.key.eq_ignore_ascii_case("content-type"))
+        .map(|h| h.value.as_str());
+    let kind = BodyKind::detect(content_type, &response.body);
+    formatting::pretty(kind, &response.body).unwrap_or_else(|| response.body.to_string())
+}
+
+/// Headers that were added, removed or changed, by case-insensitive name
+fn diff_headers(old: &Response, new: &Response) -> Vec<DiffRow> {
+    fn by_name(response: &Response) -> BTreeMap<String, String> {
+        let mut headers: BTreeMap<String, String> = BTreeMap::new();
+        for header in &response.headers {
+            headers
+                .entry(header.key.to_ascii_lowercase())
+                .and_modify(|value| {
+                    value.push_str(", ");
+                    value.push_str(&header.value);
+                })
+                .or_insert_with(|| header.value.clone());
+        }
+        headers
+    }
+
+    let old = by_name(old);
+    let new = by_name(new);
+    let mut names: Vec<&String> = old.keys().chain(new.keys()).collect();
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .filter_map(|name| match (old.get(name), new.get(name)) {
+            (Some(left), Some(right)) if left == right => None,
+            (Some(left), Some(right)) => Some(DiffRow::new(DiffKind::Changed, name.as_str(), left.as_str(), right.as_str())),
+            (Some(left), None) => Some(DiffRow::new(DiffKind::Removed, name.as_str(), left.as_str(), "")),
+            (None, Some(right)) => Some(DiffRow::new(DiffKind::Added, name.as_str(), "", right.as_str())),
+            (None, None) => None,
+        })
+        .collect()
+}
+
+/// Structural differences of two JSON documents, one row per changed node
+pub fn diff_json(old: &Value, new: &Value) -> Vec<DiffRow> {
+    let mut rows = Vec::new();
+    push_json_diff(old, new, "$".to_string(), &mut rows);
+    rows
+}
+
+fn push_json_diff(old: &Value, new: &Value, path: String, rows: &mut Vec<DiffRow>) {
+    match (old, new) {
+        (Value::Object(left), Value::Object(right)) => {
+            let mut keys: Vec<&String> = left.keys().chain(right.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = field_path(&path, key);
+                match (left.get(key), right.get(key)) {
+                    (Some(a), Some(b)) => push_json_diff(a, b, child, rows),
+                    (Some(a), None) => rows.push(DiffRow::new(DiffKind::Removed, child, a.to_string(), "")),
+                    (None, Some(b)) => rows.push(DiffRow::new(DiffKind::Added, child, "", b.to_string())),
+                    (None, None) => {}
+                }
+            }
+        }
+        (Value::Array(left), Value::Array(right)) => {
+            for index in 0..left.len().max(right.len()) {
+                let child = format!("{}[{}]", path, index);
+                match (left.get(index), right.get(index)) {
+                    (Some(a), Some(b)) => push_json_diff(a, b, child, rows),
+                    (Some(a), None) => rows.push(DiffRow::new(DiffKind::Removed, child, a.to_string(), "")),
+                    (None, Some(b)) => rows.push(DiffRow::new(DiffKind::Added, child, "", b.to_string())),
+                    (None, None) => {}
+                }
+            }
+        }
+        (a, b) if a == b => {}
+        (a, b) => rows.push(DiffRow::new(DiffKind::Changed, path, a.to_string(), b.to_string())),
+    }
+}
+
+/// Line-by-line comparison with unchanged lines kept for context; a run of
+/// removed lines followed by added ones is paired up as changed lines
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffRow> {
+    let left: Vec<&str> = old.lines().collect();
+    let right: Vec<&str> = new.lines().collect();
+
+    let prefix = left.iter().zip(&right).take_while(|(a, b)| a == b).count();
+    let suffix = left[prefix..]
+        .iter()
+        .rev()
+        .zip(right[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut ops: Vec<Op> = vec![Op::Same; prefix];
+    ops.extend(middle_ops(&left[prefix..left.len() - suffix], &right[prefix..right.len() - suffix]));
+    ops.extend(std::iter::repeat(Op::Same).take(suffix));
+
+    let mut rows = Vec::new();
+    let (mut l, mut r) = (0, 0);
+    let mut index = 0;
+    while index < ops.len() {
+        if ops[index] == Op::Same {
+            rows.push(DiffRow::new(DiffKind::Same, "", left[l], right[r]));
+            l += 1;
+            r += 1;
+            index += 1;
+            continue;
+        }
+        let removed = ops[index..].iter().take_while(|op| **op == Op::Removed).count();
+        let added = ops[index + removed..].iter().take_while(|op| **op == Op::Added).count();
+        for step in 0..removed.max(added) {
+            let row = match (step < removed, step < added) {
+                (true, true) => DiffRow::new(DiffKind::Changed, "", left[l + step], right[r + step]),
+                (true, false) => DiffRow::new(DiffKind::Removed, "", left[l + step], ""),
+                _ => DiffRow::new(DiffKind::Added, "", "", right[r + step]),
+            };
+            rows.push(row);
+        }
+        l += removed;
+        r += added;
+        index += removed + added;
+    }
+    rows
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Op {
+    Same,
+    Removed,
+    Added,
+}
+
+/// Edit script of the differing middle part, from a longest common
+/// subsequence table; removals come before additions within a change
+fn middle_ops(left: &[&str], right: &[&str]) -> Vec<Op> {
+    let (n, m) = (left.len(), right.len());
+    if n.saturating_mul(m) > MAX_LINE_DIFF_CELLS {
+        let mut ops = vec![Op::Removed; n];
+        ops.extend(std::iter::repeat(Op::Added).take(m));
+        return ops;
+    }
+
+    // lcs[i][j]: common subsequence length of left[i..] and right[j..]
+    let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+    let at = |i: usize, j: usize| i * (m + 1) + j;
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[at(i, j)] = if left[i] == right[j] {
+                lcs[at(i + 1, j + 1)] + 1
+            } else {
+                lcs[at(i + 1, j)].max(lcs[at(i, j + 1)])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(n + m);
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && left[i] == right[j] {
+            ops.push(Op::Same);
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[at(i + 1, j)] >= lcs[at(i, j + 1)]) {
+            ops.push(Op::Removed);
+            i += 1;
+        } else {
+            ops.push(Op::Added);
+            j += 1;
+        }
+    }
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Header;
+    use serde_json::json;
+
+    #[test]
+    fn test_json_diff_ignores_key_order() {
+        let old = json!({ "id": 1, "tags": ["a", "b"], "user": { "name": "Ann", "role": "admin" } });
+        let new = json!({ "user": { "role": "admin", "name": "Bob" }, "tags": ["a"], "id": 1, "next": null });
+        let rows = diff_json(&old, &new);
+        let summary: Vec<(DiffKind, &str)> = rows.iter().map(|row| (row.kind, row.label.as_str())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (DiffKind::Added, "$.next"),
+                (DiffKind::Removed, "$.tags[1]"),
+                (DiffKind::Changed, "$.user.name"),
+            ]
+        );
+        assert_eq!(rows[2].left, "\"Ann\"");
+        assert!(diff_json(&old, &old.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_line_diff_pairs_changes() {
+        let rows = diff_lines("a\nb\nc\nd", "a\nB\nc\nd\ne");
+        let kinds: Vec<DiffKind> = rows.iter().map(|row| row.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![DiffKind::Same, DiffKind::Changed, DiffKind::Same, DiffKind::Same, DiffKind::Added]
+        );
+        assert_eq!((rows[1].left.as_str(), rows[1].right.as_str()), ("b", "B"));
+        assert_eq!(rows[4].right, "e");
+    }
+
+    #[test]
+    fn test_response_diff_compares_status_and_headers() {
+        let header = |key: &str, value: &str| Header {
+            key: key.to_string(),
+            value: value.to_string(),
+            enabled: true,
+        };
+        let old = Response {
+            status_code: Some(200),
+            body: "ok".into(),
+            headers: vec![header("Content-Type", "text/plain"), header("X-Old", "1")],
+            ..Response::default()
+        };
+        let new = Response {
+            status_code: Some(500),
+            body: "ok".into(),
+            headers: vec![header("content-type", "text/plain"), header("Retry-After", "5")],
+            ..Response::default()
+        };
+        let rows = diff_responses(&old, &new);
+        let changed: Vec<(DiffKind, &str)> = rows
+            .iter()
+            .filter(|row| !matches!(row.kind, DiffKind::Same | DiffKind::Section))
+            .map(|row| (row.kind, row.label.as_str()))
+            .collect();
+        assert_eq!(
+            changed,
+            vec![(DiffKind::Changed, "status"), (DiffKind::Added, "retry-after"), (DiffKind::Removed, "x-old")]
+        );
+    }
+}
@@ -1,10 +1,14 @@
-use crate::models::{Collection, Environment, HistoryEntry};
-use anyhow::Result;
+use crate::models::{
+    Collection, Environment, HistoryEntry, HostOverride, ProxySetting, Request, Settings,
+    TlsProfile,
+};
+use anyhow::{bail, Result};
 use std::collections::VecDeque;
 use std::fs;
 use std::path::PathBuf;
 
 const MAX_HISTORY: usize = 50;
+const SETTINGS_FILE: &str = "settings.yaml";
 
 /// Manages request history and file storage
 pub struct Storage {
@@ -12,6 +16,7 @@ pub struct Storage {
     pub collections: Vec<Collection>,
     pub environments: Vec<Environment>,
     pub current_env: Option<usize>,
+    pub settings: Settings,
     config_dir: PathBuf,
 }
 
@@ -26,6 +31,7 @@ impl Storage {
             collections: Vec::new(),
             environments: Vec::new(),
             current_env: None,
+            settings: Settings::default(),
             config_dir,
         };
 
@@ -48,7 +54,6 @@ impl Storage {
     }
 
     /// Substitute variables in text using current environment
-    #[allow(dead_code)] // Prepared for future environment variable feature
     pub fn substitute(&self, text: &str) -> String {
         if let Some(env) = self.current_environment() {
             env.substitute(text)
@@ -57,8 +62,56 @@ impl Storage {
         }
     }
 
+    /// Resolve the proxy for a request: the request override wins,
+    /// then the current environment, then the global settings
+    pub fn resolve_proxy(&self, request: &Request) -> ProxySetting {
+        request
+            .proxy
+            .clone()
+            .or_else(|| self.current_environment().and_then(|env| env.proxy.clone()))
+            .unwrap_or_else(|| self.settings.proxy.clone())
+    }
+
+    /// Find the TLS profile for a URL: the request's own profile if it
+    /// matches, then the first matching profile of the current environment,
+    /// then the first matching global one
+    pub fn resolve_tls(&self, request_profile: Option<&TlsProfile>, url: &str) -> Option<TlsProfile> {
+        let url = reqwest::Url::parse(&self.substitute(url)).ok()?;
+        let host = url.host_str()?;
+        let env_profiles = self
+            .current_environment()
+            .map(|env| env.tls.as_slice())
+            .unwrap_or_default();
+        request_profile
+            .into_iter()
+            .chain(env_profiles)
+            .chain(&self.settings.tls)
+            .find(|profile| profile.matches(host))
+            .cloned()
+    }
+
+    /// Find the host override for a URL: the request's own overrides
+    /// first, then the current environment's
+    pub fn resolve_host_override(
+        &self,
+        request_overrides: &[HostOverride],
+        url: &str,
+    ) -> Option<HostOverride> {
+        let url = reqwest::Url::parse(&self.substitute(url)).ok()?;
+        let host = url.host_str()?;
+        let port = url.port_or_known_default()?;
+        let env_overrides = self
+            .current_environment()
+            .map(|env| env.host_overrides.as_slice())
+            .unwrap_or_default();
+        request_overrides
+            .iter()
+            .chain(env_overrides)
+            .find(|host_override| host_override.matches(host, port))
+            .cloned()
+    }
+
     /// Ensure config directory exists
-    #[allow(dead_code)] // Used by save methods
     fn ensure_dir(&self) -> Result<()> {
         if !self.config_dir.exists() {
             fs::create_dir_all(&self.config_dir)?;
@@ -67,27 +120,96 @@ impl Storage {
     }
 
     /// Save a collection to file
-    #[allow(dead_code)] // Prepared for future collection persistence
     pub fn save_collection(&self, collection: &Collection) -> Result<()> {
+        let stem = file_stem(&collection.name);
+        if reserved_collection_stem(&stem) {
+            bail!("'{}' would be read back as settings or an environment", collection.name);
+        }
         self.ensure_dir()?;
-        let path = self.config_dir.join(format!("{}.yaml", collection.name));
+        let path = self.config_dir.join(format!("{}.yaml", stem));
         let content = serde_yaml::to_string(collection)?;
         fs::write(path, content)?;
         Ok(())
     }
 
     /// Save an environment to file
-    #[allow(dead_code)] // Prepared for future environment persistence
     pub fn save_environment(&self, environment: &Environment) -> Result<()> {
         self.ensure_dir()?;
         let path = self
             .config_dir
-            .join(format!("{}.env.yaml", environment.name));
+            .join(format!("{}.env.yaml", file_stem(&environment.name)));
         let content = serde_yaml::to_string(environment)?;
         fs::write(path, content)?;
         Ok(())
     }
 
+    /// Write the global settings to `settings.yaml`, returning its path
+    pub fn save_settings(&self) -> Result<PathBuf> {
+        self.ensure_dir()?;
+        let path = self.config_dir.join(SETTINGS_FILE);
+        fs::write(&path, serde_yaml::to_string(&self.settings)?)?;
+        Ok(path)
+    }
+
+    /// Save a collection and add it under a name no other collection or
+    /// file uses, returning that name
+    pub fn add_collection(&mut self, mut collection: Collection) -> Result<String> {
+        collection.name = unique_name(&collection.name, |name| {
+            let stem = file_stem(name);
+            reserved_collection_stem(&stem)
+                || self.collections.iter().any(|c| file_stem(&c.name).eq_ignore_ascii_case(&stem))
+                || self.config_dir.join(format!("{}.yaml", stem)).exists()
+        });
+        self.save_collection(&collection)?;
+        let name = collection.name.clone();
+        self.collections.push(collection);
+        Ok(name)
+    }
+
+    /// Save an environment and add it under a name no other environment
+    /// or file uses, returning that name
+    pub fn add_environment(&mut self, mut environment: Environment) -> Result<String> {
+        environment.name = unique_name(&environment.name, |name| {
+            let stem = file_stem(name);
+            self.environments.iter().any(|e| file_stem(&e.name).eq_ignore_ascii_case(&stem))
+                || self.config_dir.join(format!("{}.env.yaml", stem)).exists()
+        });
+        self.save_environment(&environment)?;
+        let name = environment.name.clone();
+        self.environments.push(environment);
+        Ok(name)
+    }
+
+    /// Add a collection for this session only, replacing one with the same name
+    pub fn insert_collection(&mut self, collection: Collection) {
+        match self.collections.iter_mut().find(|c| c.name == collection.name) {
+            Some(existing) => *existing = collection,
+            None => self.collections.push(collection),
+        }
+    }
+
+    /// Add an environment for this session only, replacing one with the same name
+    pub fn insert_environment(&mut self, environment: Environment) {
+        match self.environments.iter_mut().find(|e| e.name == environment.name) {
+            Some(existing) => *existing = environment,
+            None => self.environments.push(environment),
+        }
+    }
+
+    /// Find an environment by name
+    pub fn environment(&self, name: &str) -> Option<&Environment> {
+        self.environments.iter().find(|e| e.name == name)
+    }
+
+    /// Switch to the next environment, or to none after the last one
+    pub fn cycle_environment(&mut self) {
+        self.current_env = match self.current_env {
+            None if !self.environments.is_empty() => Some(0),
+            Some(i) if i + 1 < self.environments.len() => Some(i + 1),
+            _ => None,
+        };
+    }
+
     /// Load all collections and environments from disk
     pub fn load_all(&mut self) -> Result<()> {
         if !self.config_dir.exists() {
@@ -99,7 +221,13 @@ impl Storage {
             let path = entry.path();
 
             if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
-                if filename.ends_with(".env.yaml") {
+                if filename == SETTINGS_FILE {
+                    if let Ok(content) = fs::read_to_string(&path) {
+                        if let Ok(settings) = serde_yaml::from_str::<Settings>(&content) {
+                            self.settings = settings;
+                        }
+                    }
+                } else if filename.ends_with(".env.yaml") {
                     if let Ok(content) = fs::read_to_string(&path) {
                         if let Ok(env) = serde_yaml::from_str::<Environment>(&content) {
                             self.environments.push(env);
@@ -129,8 +257,89 @@ impl Storage {
     }
 }
 
+/// File name for a collection or environment; path separators would
+/// escape the config directory
+fn file_stem(name: &str) -> String {
+    name.replace(['/', '\\'], "-")
+}
+
+/// Collection file names `load_all` would read as the settings or an
+/// environment
+fn reserved_collection_stem(stem: &str) -> bool {
+    let stem = stem.to_ascii_lowercase();
+    format!("{}.yaml", stem) == SETTINGS_FILE || stem.ends_with(".env")
+}
+
+/// The name, or the first of `name (2)`, `name (3)`, ... that is not taken
+fn unique_name(name: &str, taken: impl Fn(&str) -> bool) -> String {
+    std::iter::once(name.to_string())
+        .chain((2..).map(|n| format!("{} ({})", name, n)))
+        .find(|candidate| !taken(candidate))
+        .expect("names are unbounded")
+}
+
 impl Default for Storage {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn storage_in(config_dir: PathBuf) -> Storage {
+        Storage {
+            history: VecDeque::new(),
+            collections: Vec::new(),
+            environments: Vec::new(),
+            current_env: None,
+            settings: Settings::default(),
+            config_dir,
+        }
+    }
+
+    #[test]
+    fn test_imports_do_not_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut storage = storage_in(dir.path().to_path_buf());
+
+        assert_eq!(storage.add_collection(Collection::new("Billing")).unwrap(), "Billing");
+        assert_eq!(storage.add_collection(Collection::new("Billing")).unwrap(), "Billing (2)");
+        // Same file name as `a/b`
+        assert_eq!(storage.add_collection(Collection::new("a/b")).unwrap(), "a/b");
+        assert_eq!(storage.add_collection(Collection::new("a-b")).unwrap(), "a-b (2)");
+        assert_eq!(storage.add_environment(Environment::new("Billing")).unwrap(), "Billing");
+        assert_eq!(storage.add_environment(Environment::new("Billing")).unwrap(), "Billing (2)");
+
+        // Would replace the settings file or be read as an environment
+        assert_eq!(storage.add_collection(Collection::new("Settings")).unwrap(), "Settings (2)");
+        assert_eq!(storage.add_collection(Collection::new("prod.env")).unwrap(), "prod.env (2)");
+        assert!(storage.save_collection(&Collection::new("settings")).is_err());
+        assert!(!dir.path().join(SETTINGS_FILE).exists());
+
+        // Names taken by files from an earlier session
+        let mut fresh = storage_in(dir.path().to_path_buf());
+        assert_eq!(fresh.add_collection(Collection::new("Billing")).unwrap(), "Billing (3)");
+
+        let mut reloaded = storage_in(dir.path().to_path_buf());
+        reloaded.load_all().unwrap();
+        assert_eq!(reloaded.collections.len(), 7);
+        assert_eq!(reloaded.environments.len(), 2);
+    }
+
+    #[test]
+    fn test_settings_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut storage = storage_in(dir.path().join("config"));
+        let mut proxy = crate::models::ProxyConfig::new("socks5://proxy.corp:1080");
+        proxy.no_proxy = vec!["localhost".to_string()];
+        storage.settings.proxy = ProxySetting::Manual(proxy);
+
+        let path = storage.save_settings().unwrap();
+        assert_eq!(path, dir.path().join("config").join(SETTINGS_FILE));
+        let mut reloaded = storage_in(dir.path().join("config"));
+        reloaded.load_all().unwrap();
+        assert_eq!(reloaded.settings.proxy, storage.settings.proxy);
+    }
+}
@@ -1,5 +1,12 @@
 use ratatui::{prelude::*, widgets::*};
 
+use crate::app::search::SearchMatch;
+use crate::constants::CERT_EXPIRY_WARNING_DAYS;
+use crate::formatting::BodyKind;
+use crate::json_tree::{TreeLine, TreeNode};
+use crate::models::{CertificateInfo, Response, ResponseTiming, TlsDetails};
+use crate::response_diff::{DiffKind, DiffRow};
+
 /// Renders a text input field with cursor
 #[allow(dead_code)] // Prepared for future dynamic input rendering
 pub fn render_input<'a>(
@@ -75,102 +82,443 @@ pub fn render_tabs<'a>(titles: &[&'a str], selected: usize) -> Tabs<'a> {
 /// Simple JSON syntax highlighting
 #[allow(unused_mut, dead_code)]
 pub fn highlight_json(text: &str) -> Vec<Line<'static>> {
-    let mut lines = Vec::new();
+    text.lines().map(highlight_json_line).collect()
+}
 
-    for line in text.lines() {
-        let mut spans = Vec::new();
-        let mut chars = line.chars().peekable();
-        let mut current = String::new();
-        let mut in_string = false;
-        let mut is_key = false;
-
-        for c in chars {
-            match c {
-                '"' => {
-                    if !current.is_empty() {
-                        spans.push(Span::raw(current.clone()));
-                        current.clear();
-                    }
+/// Highlight one line of JSON. Lines are independent, so any window of
+/// a document can be highlighted on its own.
+fn highlight_json_line(line: &str) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut current = String::new();
+    let mut in_string = false;
+    let mut is_key = false;
 
-                    if in_string {
-                        // End of string
-                        current.push(c);
-                        let color = if is_key { Color::Cyan } else { Color::Green };
-                        spans.push(Span::styled(current.clone(), Style::default().fg(color)));
-                        current.clear();
-                        in_string = false;
-                        is_key = false;
-                    } else {
-                        // Start of string
-                        in_string = true;
-                        current.push(c);
-                        // Check if this is a key (followed by :)
-                        is_key = line[line.find('"').unwrap_or(0)..].contains("\":");
-                    }
+    for c in line.chars() {
+        match c {
+            '"' => {
+                if !current.is_empty() {
+                    spans.push(Span::raw(current.clone()));
+                    current.clear();
                 }
-                ':' if !in_string => {
-                    if !current.is_empty() {
-                        spans.push(Span::raw(current.clone()));
-                        current.clear();
-                    }
-                    spans.push(Span::styled(":", Style::default().fg(Color::White)));
+
+                if in_string {
+                    // End of string
+                    current.push(c);
+                    let color = if is_key { Color::Cyan } else { Color::Green };
+                    spans.push(Span::styled(current.clone(), Style::default().fg(color)));
+                    current.clear();
+                    in_string = false;
+                    is_key = false;
+                } else {
+                    // Start of string
+                    in_string = true;
+                    current.push(c);
+                    // Check if this is a key (followed by :)
+                    is_key = line[line.find('"').unwrap_or(0)..].contains("\":");
                 }
-                '{' | '}' | '[' | ']' if !in_string => {
-                    if !current.is_empty() {
-                        spans.push(Span::raw(current.clone()));
-                        current.clear();
-                    }
+            }
+            ':' if !in_string => {
+                if !current.is_empty() {
+                    spans.push(Span::raw(current.clone()));
+                    current.clear();
+                }
+                spans.push(Span::styled(":", Style::default().fg(Color::White)));
+            }
+            '{' | '}' | '[' | ']' if !in_string => {
+                if !current.is_empty() {
+                    spans.push(Span::raw(current.clone()));
+                    current.clear();
+                }
+                spans.push(Span::styled(
+                    c.to_string(),
+                    Style::default().fg(Color::Yellow),
+                ));
+            }
+            '0'..='9' | '-' | '.' if !in_string => {
+                if !current.is_empty()
+                    && !current
+                        .chars()
+                        .all(|x| x.is_ascii_digit() || x == '-' || x == '.')
+                {
+                    spans.push(Span::raw(current.clone()));
+                    current.clear();
+                }
+                current.push(c);
+            }
+            't' | 'r' | 'u' | 'e' | 'f' | 'a' | 'l' | 's' | 'n' if !in_string => {
+                current.push(c);
+                // Check for true, false, null
+                if current == "true" || current == "false" || current == "null" {
                     spans.push(Span::styled(
-                        c.to_string(),
-                        Style::default().fg(Color::Yellow),
+                        current.clone(),
+                        Style::default().fg(Color::Magenta),
                     ));
+                    current.clear();
+                }
+            }
+            _ => {
+                current.push(c);
+            }
+        }
+    }
+
+    if !current.is_empty() {
+        // Color numbers
+        if current
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '-' || c == '.')
+        {
+            spans.push(Span::styled(current, Style::default().fg(Color::Yellow)));
+        } else {
+            spans.push(Span::raw(current));
+        }
+    }
+
+    Line::from(spans)
+}
+
+/// Highlight a response body according to its format. Plain text gets
+/// the JSON highlighting, which only colors strings, numbers and brackets.
+///
+/// Only the given lines are highlighted, `first_line` being the index
+/// of the first one in the body, so large bodies are highlighted a
+/// window at a time.
+pub fn highlight_lines<'a>(
+    kind: BodyKind,
+    lines: impl Iterator<Item = &'a str>,
+    first_line: usize,
+) -> Vec<Line<'static>> {
+    match kind {
+        BodyKind::Json | BodyKind::Text => lines.map(highlight_json_line).collect(),
+        BodyKind::Xml | BodyKind::Html => highlight_markup(lines),
+        BodyKind::Yaml => lines.map(highlight_yaml_line).collect(),
+        BodyKind::Csv => highlight_table(lines, first_line),
+        BodyKind::Form => lines.map(highlight_form_line).collect(),
+    }
+}
+
+/// Append a character, extending the last span if it has the same style
+fn push_char(spans: &mut Vec<Span<'static>>, c: char, style: Style) {
+    match spans.last_mut() {
+        Some(last) if last.style == style => last.content.to_mut().push(c),
+        _ => spans.push(Span::styled(c.to_string(), style)),
+    }
+}
+
+/// XML and HTML: tag names cyan, attributes yellow, values green,
+/// comments and punctuation gray. Tags may span lines; a window
+/// starting inside one highlights its rest as text.
+fn highlight_markup<'a>(text: impl Iterator<Item = &'a str>) -> Vec<Line<'static>> {
+    #[derive(PartialEq)]
+    enum State {
+        Text,
+        Tag { name_len: usize, in_name: bool },
+        Comment,
+    }
+
+    let gray = Style::default().fg(Color::DarkGray);
+    let mut state = State::Text;
+    let mut quote = None;
+    let mut lines = Vec::new();
+    for line in text {
+        let mut spans = Vec::new();
+        for (i, c) in line.char_indices() {
+            let style = match &mut state {
+                State::Text if c == '<' => {
+                    state = if line[i..].starts_with("<!--") {
+                        State::Comment
+                    } else {
+                        State::Tag { name_len: 0, in_name: true }
+                    };
+                    gray
                 }
-                '0'..='9' | '-' | '.' if !in_string => {
-                    if !current.is_empty()
-                        && !current
-                            .chars()
-                            .all(|x| x.is_ascii_digit() || x == '-' || x == '.')
-                    {
-                        spans.push(Span::raw(current.clone()));
-                        current.clear();
+                State::Text => Style::default(),
+                State::Comment => {
+                    if line[..i + c.len_utf8()].ends_with("-->") {
+                        state = State::Text;
                     }
-                    current.push(c);
+                    gray
                 }
-                't' | 'r' | 'u' | 'e' | 'f' | 'a' | 'l' | 's' | 'n' if !in_string => {
-                    current.push(c);
-                    // Check for true, false, null
-                    if current == "true" || current == "false" || current == "null" {
-                        spans.push(Span::styled(
-                            current.clone(),
-                            Style::default().fg(Color::Magenta),
-                        ));
-                        current.clear();
+                State::Tag { .. } if quote.is_some() => {
+                    if quote == Some(c) {
+                        quote = None;
                     }
+                    Style::default().fg(Color::Green)
                 }
-                _ => {
-                    current.push(c);
+                State::Tag { name_len, in_name } => {
+                    let name_char = c.is_alphanumeric() || matches!(c, ':' | '-' | '_' | '.');
+                    if *in_name && name_char {
+                        *name_len += 1;
+                        Style::default().fg(Color::Cyan)
+                    } else if *in_name && *name_len == 0 && matches!(c, '/' | '?' | '!') {
+                        gray
+                    } else {
+                        *in_name = false;
+                        match c {
+                            '>' => {
+                                state = State::Text;
+                                gray
+                            }
+                            '"' | '\'' => {
+                                quote = Some(c);
+                                Style::default().fg(Color::Green)
+                            }
+                            '=' | '/' | '?' => gray,
+                            c if c.is_whitespace() => Style::default(),
+                            _ => Style::default().fg(Color::Yellow),
+                        }
+                    }
                 }
-            }
+            };
+            push_char(&mut spans, c, style);
         }
+        lines.push(Line::from(spans));
+    }
+    lines
+}
+
+/// YAML: keys cyan, scalars colored like JSON, comments gray
+fn highlight_yaml_line(line: &str) -> Line<'static> {
+    let gray = Style::default().fg(Color::DarkGray);
+    let trimmed = line.trim_start();
+    if trimmed.starts_with('#') {
+        return Line::styled(line.to_string(), gray);
+    }
+    if trimmed == "---" || trimmed == "..." {
+        return Line::styled(line.to_string(), Style::default().fg(Color::Yellow));
+    }
+
+    let mut spans = Vec::new();
+    // Indentation and sequence dashes
+    let content_start = line
+        .char_indices()
+        .find(|(i, c)| !(c.is_whitespace() || *c == '-' && line[i + 1..].starts_with([' ', '\t'])))
+        .map(|(i, _)| i)
+        .unwrap_or(line.len());
+    if content_start > 0 {
+        spans.push(Span::styled(line[..content_start].to_string(), gray));
+    }
+    let mut rest = &line[content_start..];
+
+    let key_end = if rest.starts_with(['"', '\'']) {
+        None
+    } else {
+        rest.match_indices(':')
+            .map(|(i, _)| i)
+            .find(|i| rest[i + 1..].is_empty() || rest[i + 1..].starts_with(' '))
+            .filter(|i| !rest[..*i].contains(" #"))
+    };
+    if let Some(end) = key_end {
+        spans.push(Span::styled(rest[..end].to_string(), Style::default().fg(Color::Cyan)));
+        spans.push(Span::styled(":", gray));
+        rest = &rest[end + 1..];
+    }
 
-        if !current.is_empty() {
-            // Color numbers
-            if current
-                .chars()
-                .all(|c| c.is_ascii_digit() || c == '-' || c == '.')
-            {
-                spans.push(Span::styled(current, Style::default().fg(Color::Yellow)));
+    let (value, comment) = match rest.find(" #") {
+        Some(i) if !rest.trim_start().starts_with(['"', '\'']) => rest.split_at(i),
+        _ => (rest, ""),
+    };
+    let core = value.trim();
+    let style = match core {
+        "" => Style::default(),
+        "true" | "false" | "null" | "~" => Style::default().fg(Color::Magenta),
+        _ if core.parse::<f64>().is_ok() => Style::default().fg(Color::Yellow),
+        _ => Style::default().fg(Color::Green),
+    };
+    if !value.is_empty() {
+        spans.push(Span::styled(value.to_string(), style));
+    }
+    if !comment.is_empty() {
+        spans.push(Span::styled(comment.to_string(), gray));
+    }
+    Line::from(spans)
+}
+
+/// CSV, raw or as a table: the header row cyan, separators gray
+fn highlight_table<'a>(lines: impl Iterator<Item = &'a str>, first_line: usize) -> Vec<Line<'static>> {
+    let gray = Style::default().fg(Color::DarkGray);
+    lines
+        .enumerate()
+        .map(|(i, line)| {
+            let row = first_line + i;
+            if line.chars().all(|c| c == '─' || c == '┼') {
+                return Line::styled(line.to_string(), gray);
+            }
+            let cell = if row == 0 {
+                Style::default().fg(Color::Cyan).bold()
             } else {
-                spans.push(Span::raw(current));
+                Style::default()
+            };
+            let mut spans = Vec::new();
+            for c in line.chars() {
+                push_char(&mut spans, c, if c == '│' || c == ',' { gray } else { cell });
             }
-        }
+            Line::from(spans)
+        })
+        .collect()
+}
 
-        lines.push(Line::from(spans));
+/// Form fields, raw (`a=1&b=2`) or one per line: names cyan, values green
+fn highlight_form_line(line: &str) -> Line<'static> {
+    let gray = Style::default().fg(Color::DarkGray);
+    let mut spans = Vec::new();
+    let mut in_value = false;
+    for c in line.chars() {
+        let style = match c {
+            '=' if !in_value => {
+                in_value = true;
+                gray
+            }
+            '&' => {
+                in_value = false;
+                gray
+            }
+            _ if in_value => Style::default().fg(Color::Green),
+            _ => Style::default().fg(Color::Cyan),
+        };
+        push_char(&mut spans, c, style);
     }
+    Line::from(spans)
+}
 
+/// Highlight search matches on top of already styled lines. `current`
+/// is the index of the selected match in `matches`.
+pub fn highlight_matches(
+    mut lines: Vec<Line<'static>>,
+    matches: &[SearchMatch],
+    current: usize,
+    first_line: usize,
+) -> Vec<Line<'static>> {
+    // Matches are in line order; only those in the window are marked
+    let mut i = matches.partition_point(|m| m.line < first_line);
+    let window_end = matches.partition_point(|m| m.line < first_line + lines.len());
+    while i < window_end {
+        let line_index = matches[i].line;
+        let end = i + matches[i..window_end].iter().take_while(|m| m.line == line_index).count();
+        if let Some(line) = lines.get_mut(line_index - first_line) {
+            let line_matches = &matches[i..end];
+            let selected = current.checked_sub(i).filter(|c| *c < line_matches.len());
+            *line = mark_ranges(std::mem::take(line), line_matches, selected);
+        }
+        i = end;
+    }
     lines
 }
 
+/// Split a line's spans at the match boundaries and restyle the matches
+fn mark_ranges(line: Line<'static>, matches: &[SearchMatch], selected: Option<usize>) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut offset = 0;
+    for span in line.spans {
+        let text = span.content.as_ref();
+        let span_end = offset + text.len();
+        let mut cut = offset;
+        while cut < span_end {
+            // The match covering `cut`, or the next one starting in this span
+            let covering = matches
+                .iter()
+                .enumerate()
+                .find(|(_, m)| m.start <= cut && cut < m.end);
+            let (next_cut, style) = match covering {
+                Some((index, m)) => {
+                    let color = if Some(index) == selected { Color::LightRed } else { Color::Yellow };
+                    (m.end.min(span_end), span.style.bg(color).fg(Color::Black))
+                }
+                None => {
+                    let next_start = matches
+                        .iter()
+                        .map(|m| m.start)
+                        .filter(|start| *start > cut)
+                        .min()
+                        .unwrap_or(span_end);
+                    (next_start.min(span_end), span.style)
+                }
+            };
+            spans.push(Span::styled(text[cut - offset..next_cut - offset].to_string(), style));
+            cut = next_cut;
+        }
+        offset = span_end;
+    }
+    Line::from(spans).style(line.style)
+}
+
+/// One row of the JSON tree: fold marker, key, and the value or the
+/// size of a folded container
+pub fn tree_line(line: &TreeLine) -> Line<'static> {
+    let mut spans = vec![Span::raw("  ".repeat(line.depth))];
+    let marker = match &line.node {
+        TreeNode::Scalar(_) => "  ",
+        node if node.is_collapsed() => "▸ ",
+        _ => "▾ ",
+    };
+    spans.push(Span::styled(marker, Style::default().fg(Color::DarkGray)));
+    if let Some(key) = &line.key {
+        let color = if key.starts_with('[') { Color::DarkGray } else { Color::Cyan };
+        spans.push(Span::styled(key.clone(), Style::default().fg(color)));
+        spans.push(Span::raw(": "));
+    }
+    let plural = |len: usize, one: &str, many: &str| {
+        format!(" {} {}", len, if len == 1 { one } else { many })
+    };
+    let yellow = Style::default().fg(Color::Yellow);
+    let count = Style::default().fg(Color::DarkGray);
+    match &line.node {
+        TreeNode::Object { len, collapsed: true } => {
+            spans.push(Span::styled("{…}", yellow));
+            spans.push(Span::styled(plural(*len, "key", "keys"), count));
+        }
+        TreeNode::Array { len, collapsed: true } => {
+            spans.push(Span::styled("[…]", yellow));
+            spans.push(Span::styled(plural(*len, "item", "items"), count));
+        }
+        TreeNode::Object { .. } => spans.push(Span::styled("{", yellow)),
+        TreeNode::Array { .. } => spans.push(Span::styled("[", yellow)),
+        TreeNode::Scalar(value) => {
+            let color = match value {
+                serde_json::Value::String(_) => Color::Green,
+                serde_json::Value::Number(_) => Color::Magenta,
+                serde_json::Value::Bool(_) => Color::Blue,
+                _ => Color::DarkGray,
+            };
+            spans.push(Span::styled(value.to_string(), Style::default().fg(color)));
+        }
+    }
+    Line::from(spans)
+}
+
+/// One row of a response comparison: the older side, then the newer,
+/// each cut to `column_width` characters
+pub fn diff_line(row: &DiffRow, column_width: usize) -> Line<'static> {
+    if row.kind == DiffKind::Section {
+        return Line::from(Span::styled(row.left.clone(), Style::default().fg(Color::Cyan).bold()));
+    }
+    let (marker, left_color, right_color) = match row.kind {
+        DiffKind::Removed => ("- ", Color::Red, Color::DarkGray),
+        DiffKind::Added => ("+ ", Color::DarkGray, Color::Green),
+        DiffKind::Changed => ("~ ", Color::Red, Color::Green),
+        _ => ("  ", Color::Gray, Color::Gray),
+    };
+    let cell = |text: &str| {
+        let text = match (row.label.is_empty(), text.is_empty()) {
+            (false, false) => format!("{}: {}", row.label, text),
+            _ => text.to_string(),
+        };
+        let width = column_width.saturating_sub(2);
+        let mut cut: String = text.chars().take(width).collect();
+        if text.chars().count() > width && width > 0 {
+            cut.pop();
+            cut.push('…');
+        }
+        format!("{:width$}", cut, width = width)
+    };
+    Line::from(vec![
+        Span::styled(marker, Style::default().fg(Color::DarkGray)),
+        Span::styled(cell(&row.left), Style::default().fg(left_color)),
+        Span::styled(" │ ", Style::default().fg(Color::DarkGray)),
+        Span::styled(cell(&row.right), Style::default().fg(right_color)),
+    ])
+}
+
 /// Status code color
 pub fn status_color(code: u16) -> Color {
     match code {
@@ -182,6 +530,187 @@ pub fn status_color(code: u16) -> Color {
     }
 }
 
+/// Width of the waterfall bar in the response footer
+const WATERFALL_WIDTH: u64 = 20;
+
+/// Color for a timing phase
+fn phase_color(phase: &str) -> Color {
+    match phase {
+        "DNS" => Color::Cyan,
+        "Connect" => Color::Yellow,
+        "TLS" => Color::Magenta,
+        "TTFB" => Color::Green,
+        _ => Color::Blue,
+    }
+}
+
+/// Waterfall-style timing breakdown: a proportional bar followed by
+/// the duration of each phase
+pub fn timing_line(timing: &ResponseTiming, total_ms: u64) -> Line<'static> {
+    let phases = timing.phases();
+    let sum: u64 = phases.iter().map(|(_, ms)| ms).sum::<u64>().max(1);
+
+    // Round the running total rather than each phase, so the cells add
+    // up to exactly the bar width
+    let mut spans = vec![Span::raw(" ")];
+    let mut elapsed = 0;
+    let mut drawn = 0;
+    for (phase, ms) in &phases {
+        elapsed += ms;
+        let end = (elapsed * WATERFALL_WIDTH + sum / 2) / sum;
+        let cells = end - drawn;
+        drawn = end;
+        if cells > 0 {
+            spans.push(Span::styled(
+                "█".repeat(cells as usize),
+                Style::default().fg(phase_color(phase)),
+            ));
+        }
+    }
+    spans.push(Span::raw(" "));
+
+    if timing.reused_connection() {
+        spans.push(Span::styled("reused ", Style::default().fg(Color::DarkGray)));
+    }
+    for (phase, ms) in &phases {
+        spans.push(Span::styled(
+            format!("{} {}ms ", phase, ms),
+            Style::default().fg(phase_color(phase)),
+        ));
+    }
+    spans.push(Span::raw(format!("│ {}ms ", total_ms)));
+
+    Line::from(spans)
+}
+
+/// Label/value row of the response Info view
+fn info_row(label: &str, value: impl Into<String>, color: Color) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(
+            format!(" {:<12}", label),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Span::styled(value.into(), Style::default().fg(color)),
+    ])
+}
+
+fn section(title: &str) -> Line<'static> {
+    Line::from(Span::styled(
+        format!(" {}", title),
+        Style::default().fg(Color::Cyan).bold(),
+    ))
+}
+
+/// Color and remark for a certificate's remaining validity
+fn expiry_status(cert: &CertificateInfo) -> (Color, String) {
+    let days = cert.days_remaining();
+    if days < 0 {
+        (Color::Red, format!("expired {} days ago", -days))
+    } else if days < CERT_EXPIRY_WARNING_DAYS {
+        (Color::Yellow, format!("⚠ expires in {} days", days))
+    } else {
+        (Color::Green, format!("{} days left", days))
+    }
+}
+
+/// Short warning for the response title when a certificate is about to expire
+pub fn expiry_warning(tls: &TlsDetails) -> Option<String> {
+    let days = tls.expiring_certificate()?.days_remaining();
+    if days < 0 {
+        Some(" ⚠ certificate expired ".to_string())
+    } else {
+        Some(format!(" ⚠ certificate expires in {} days ", days))
+    }
+}
+
+/// Contents of the response Info view: status, timing and TLS details
+pub fn response_info_lines(response: &Response) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::default()];
+
+    let Some(code) = response.status_code else {
+        lines.push(info_row("Status", "No response yet", Color::DarkGray));
+        return lines;
+    };
+    lines.push(info_row("Status", code.to_string(), status_color(code)));
+    if let Some(version) = &response.http_version {
+        lines.push(info_row("Protocol", version.clone(), Color::White));
+    }
+    lines.push(info_row(
+        "Time",
+        format!("{} ms", response.time_ms),
+        Color::White,
+    ));
+    if let Some(timing) = &response.timing {
+        for (phase, ms) in timing.phases() {
+            lines.push(info_row(
+                &format!("  {}", phase),
+                format!("{} ms", ms),
+                phase_color(phase),
+            ));
+        }
+    }
+
+    if !response.headers.is_empty() {
+        lines.push(Line::default());
+        lines.push(section("Headers"));
+        for header in &response.headers {
+            lines.push(Line::from(vec![
+                Span::styled(format!(" {}: ", header.key), Style::default().fg(Color::DarkGray)),
+                Span::raw(header.value.clone()),
+            ]));
+        }
+    }
+
+    lines.push(Line::default());
+    lines.push(section("TLS"));
+    let Some(tls) = &response.tls else {
+        lines.push(info_row("", "Not a TLS connection", Color::DarkGray));
+        return lines;
+    };
+    let unknown = || "unknown (resumed connection)".to_string();
+    lines.push(info_row(
+        "Protocol",
+        tls.version.clone().unwrap_or_else(unknown),
+        Color::White,
+    ));
+    lines.push(info_row(
+        "Cipher",
+        tls.cipher.clone().unwrap_or_else(unknown),
+        Color::White,
+    ));
+
+    lines.push(Line::default());
+    lines.push(section("Certificate chain"));
+    for (i, cert) in tls.chain.iter().enumerate() {
+        let (color, remark) = expiry_status(cert);
+        lines.push(Line::from(Span::styled(
+            format!(" [{}] {}", i, cert.subject),
+            Style::default().fg(Color::White).bold(),
+        )));
+        lines.push(info_row("  Issuer", cert.issuer.clone(), Color::White));
+        if !cert.sans.is_empty() {
+            lines.push(info_row("  SANs", cert.sans.join(", "), Color::White));
+        }
+        lines.push(info_row(
+            "  Valid",
+            format!(
+                "{} → {}  ({})",
+                cert.not_before.format("%Y-%m-%d"),
+                cert.not_after.format("%Y-%m-%d"),
+                remark
+            ),
+            color,
+        ));
+        lines.push(info_row(
+            "  SHA-256",
+            cert.fingerprint.clone(),
+            Color::DarkGray,
+        ));
+    }
+
+    lines
+}
+
 /// Method color
 pub fn method_color(method: &str) -> Color {
     match method {
@@ -193,3 +722,107 @@ pub fn method_color(method: &str) -> Color {
         _ => Color::White,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timing_bar_fills_the_width() {
+        let bar_cells = |timing: &ResponseTiming| -> Vec<usize> {
+            timing_line(timing, 0)
+                .spans
+                .iter()
+                .filter(|span| span.content.starts_with('█'))
+                .map(|span| span.content.chars().count())
+                .collect()
+        };
+        // Each phase alone would round up to 7 cells, 21 in total
+        let even = ResponseTiming {
+            dns_ms: Some(10),
+            connect_ms: Some(10),
+            tls_ms: Some(10),
+            ttfb_ms: 0,
+            download_ms: 0,
+        };
+        assert_eq!(bar_cells(&even), vec![7, 6, 7]);
+
+        let skewed = ResponseTiming {
+            dns_ms: Some(1),
+            connect_ms: Some(2),
+            tls_ms: None,
+            ttfb_ms: 333,
+            download_ms: 7,
+        };
+        assert_eq!(bar_cells(&skewed).iter().sum::<usize>(), WATERFALL_WIDTH as usize);
+    }
+
+    #[test]
+    fn test_highlight_matches_splits_spans() {
+        let lines = highlight_json("{\"name\": \"Ann\"}\n[1]");
+        let matches = [
+            SearchMatch { line: 0, start: 3, end: 11 },
+            SearchMatch { line: 1, start: 1, end: 2 },
+        ];
+        let lines = highlight_matches(lines, &matches, 1, 0);
+
+        let text: Vec<String> = lines[0].spans.iter().map(|s| s.content.to_string()).collect();
+        assert_eq!(text.concat(), "{\"name\": \"Ann\"}");
+        let marked: String = lines[0]
+            .spans
+            .iter()
+            .filter(|s| s.style.bg == Some(Color::Yellow))
+            .map(|s| s.content.as_ref())
+            .collect();
+        assert_eq!(marked, "ame\": \"A");
+        assert!(lines[1]
+            .spans
+            .iter()
+            .any(|s| s.content == "1" && s.style.bg == Some(Color::LightRed)));
+
+        // A window starting at line 1 only marks that line's match
+        let window = highlight_matches(highlight_json("[1]"), &matches, 1, 1);
+        assert!(window[0].spans.iter().any(|s| s.style.bg == Some(Color::LightRed)));
+    }
+
+    #[test]
+    fn test_highlight_lines_keeps_text() {
+        let text = "<a href=\"x>y\">\n  <!-- note -->\n  <b/>\n</a>";
+        let lines = highlight_lines(BodyKind::Xml, text.lines(), 0);
+        let rendered: Vec<String> = lines
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        assert_eq!(rendered.join("\n"), text);
+        assert!(lines[0].spans.iter().any(|s| s.content == "a" && s.style.fg == Some(Color::Cyan)));
+        assert!(lines[0].spans.iter().any(|s| s.content == "\"x>y\"" && s.style.fg == Some(Color::Green)));
+
+        let yaml = highlight_lines(BodyKind::Yaml, "- name: Ann # first\n  age: 3".lines(), 0);
+        assert_eq!(yaml[0].spans[1].content, "name");
+        assert_eq!(yaml[0].spans.last().unwrap().content, " # first");
+        assert_eq!(yaml[1].spans.last().unwrap().style.fg, Some(Color::Yellow));
+    }
+
+    #[test]
+    fn test_tree_line_shows_folded_counts() {
+        let line = TreeLine {
+            depth: 1,
+            key: Some("items".to_string()),
+            node: TreeNode::Array { len: 3, collapsed: true },
+        };
+        let text: String = tree_line(&line).spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(text, "  ▸ items: […] 3 items");
+    }
+
+    #[test]
+    fn test_diff_line_cuts_both_sides() {
+        let row = DiffRow {
+            kind: DiffKind::Changed,
+            label: "$.name".to_string(),
+            left: "\"Ann\"".to_string(),
+            right: "\"Bartholomew\"".to_string(),
+        };
+        let text: String = diff_line(&row, 16).spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(text, "~ $.name: \"Ann\"  │ $.name: \"Bart…");
+    }
+}